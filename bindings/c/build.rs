@@ -0,0 +1,13 @@
+// Regenerates include/sparrow.h from the `extern "C"` API in src/lib.rs
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml")).expect("invalid cbindgen.toml"))
+        .generate()
+        .expect("could not generate C header")
+        .write_to_file(format!("{crate_dir}/include/sparrow.h"));
+}
@@ -0,0 +1,69 @@
+// Optimizes an instance for at most 60 seconds, cancelling early once the strip width reaches a target.
+//
+// cargo build --release -p sparrow-c
+// cc bindings/c/examples/example.c -Ibindings/c/include -Ltarget/release -lsparrow_c -o example
+// LD_LIBRARY_PATH=target/release ./example data/input/swim.json 6500
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <unistd.h>
+#include "sparrow.h"
+
+static char *read_file(const char *path) {
+    FILE *f = fopen(path, "rb");
+    if (!f) return NULL;
+    fseek(f, 0, SEEK_END);
+    long len = ftell(f);
+    fseek(f, 0, SEEK_SET);
+    char *buf = malloc(len + 1);
+    if (buf && fread(buf, 1, len, f) == (size_t) len) {
+        buf[len] = '\0';
+    }
+    fclose(f);
+    return buf;
+}
+
+int main(int argc, char **argv) {
+    if (argc < 3) {
+        fprintf(stderr, "usage: %s <instance.json> <target strip width>\n", argv[0]);
+        return 1;
+    }
+    char *json = read_file(argv[1]);
+    double target = atof(argv[2]);
+
+    SparrowSolver *solver = sparrow_solver_new(json);
+    free(json);
+    if (!solver) {
+        fprintf(stderr, "error: %s\n", sparrow_last_error());
+        return 1;
+    }
+
+    SparrowOptions options = sparrow_default_options();
+    options.time_limit = 60.0;
+    options.seed = 42;
+    options.use_seed = true;
+    if (sparrow_start(solver, &options) != SPARROW_STATUS_OK) {
+        fprintf(stderr, "error: %s\n", sparrow_last_error());
+        sparrow_solver_free(solver);
+        return 1;
+    }
+
+    SparrowProgress progress;
+    do {
+        sleep(1);
+        sparrow_poll(solver, &progress);
+        printf("solutions: %llu, best strip width: %.3f\n", (unsigned long long) progress.n_solutions, progress.best_strip_width);
+        if (progress.best_strip_width <= target) {
+            sparrow_cancel(solver);
+        }
+    } while (progress.state == SPARROW_STATE_RUNNING);
+    sparrow_wait(solver);
+
+    char *solution = sparrow_best_solution_json(solver);
+    if (solution) {
+        printf("%.200s...\n", solution);
+        sparrow_string_free(solution);
+    }
+    sparrow_solver_free(solver);
+    return 0;
+}
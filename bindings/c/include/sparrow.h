@@ -0,0 +1,178 @@
+#ifndef SPARROW_H
+#define SPARROW_H
+
+/* Generated by cbindgen from bindings/c/src/lib.rs, do not edit manually */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef enum SparrowStatus {
+  SPARROW_STATUS_OK = 0,
+  /**
+   * A required pointer argument was NULL
+   */
+  SPARROW_STATUS_NULL_ARGUMENT,
+  /**
+   * The instance JSON could not be parsed or imported, or the options are invalid
+   */
+  SPARROW_STATUS_INVALID_INPUT,
+  /**
+   * The optimization has already been started
+   */
+  SPARROW_STATUS_ALREADY_STARTED,
+  /**
+   * The optimization has not been started yet
+   */
+  SPARROW_STATUS_NOT_STARTED,
+  /**
+   * The optimization thread panicked
+   */
+  SPARROW_STATUS_PANICKED,
+} SparrowStatus;
+
+typedef enum SparrowState {
+  /**
+   * Created, but not yet started
+   */
+  SPARROW_STATE_IDLE = 0,
+  SPARROW_STATE_RUNNING,
+  /**
+   * Finished, either because the time limit was reached or because it was cancelled
+   */
+  SPARROW_STATE_FINISHED,
+} SparrowState;
+
+/**
+ * Opaque handle to an instance and its optimization
+ */
+typedef struct SparrowSolver SparrowSolver;
+
+/**
+ * Options for [`sparrow_start`], initialize with [`sparrow_default_options`]
+ */
+typedef struct SparrowOptions {
+  /**
+   * Seed for the random number generator, only used if `use_seed` is set
+   */
+  uint64_t seed;
+  bool use_seed;
+  /**
+   * Total time limit in seconds, split between exploration and compression
+   */
+  double time_limit;
+  /**
+   * If non-zero, limits the exploration phase by a number of separator iterations instead of time
+   */
+  uint64_t exploration_iterations;
+  /**
+   * If non-zero, limits the compression phase by a number of separator iterations instead of time
+   */
+  uint64_t compression_iterations;
+} SparrowOptions;
+
+typedef struct SparrowProgress {
+  enum SparrowState state;
+  /**
+   * Number of (improving) feasible solutions found so far
+   */
+  uint64_t n_solutions;
+  /**
+   * Strip width of the best feasible solution found so far, NaN if none
+   */
+  double best_strip_width;
+  /**
+   * Density of the best feasible solution found so far, NaN if none
+   */
+  double best_density;
+} SparrowProgress;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Message of the last error that occurred on the calling thread, or NULL if none.
+ * The returned string is owned by the library and valid until the next call on the same thread.
+ */
+const char *sparrow_last_error(void);
+
+struct SparrowOptions sparrow_default_options(void);
+
+/**
+ * Creates a solver from an instance in the JSON format of jagua-rs (or a solution JSON written by sparrow).
+ * Returns NULL on failure, see [`sparrow_last_error`].
+ *
+ * # Safety
+ * `instance_json` must be NULL or a valid null-terminated string.
+ */
+struct SparrowSolver *sparrow_solver_new(const char *instance_json);
+
+/**
+ * Imports the instance and starts the optimization on a background thread.
+ * `options` may be NULL to use [`sparrow_default_options`].
+ *
+ * # Safety
+ * `solver` must be NULL or a pointer returned by [`sparrow_solver_new`], `options` must be NULL or valid.
+ */
+enum SparrowStatus sparrow_start(const struct SparrowSolver *solver,
+                                 const struct SparrowOptions *options);
+
+/**
+ * Writes the current state of the optimization to `progress`.
+ *
+ * # Safety
+ * `solver` must be NULL or a pointer returned by [`sparrow_solver_new`], `progress` must be NULL or valid.
+ */
+enum SparrowStatus sparrow_poll(const struct SparrowSolver *solver,
+                                struct SparrowProgress *progress);
+
+/**
+ * Best feasible solution found so far, in the same JSON format as the output of the CLI.
+ * Returns NULL if no feasible solution has been found yet. Free the result with [`sparrow_string_free`].
+ *
+ * # Safety
+ * `solver` must be NULL or a pointer returned by [`sparrow_solver_new`].
+ */
+char *sparrow_best_solution_json(const struct SparrowSolver *solver);
+
+/**
+ * Requests the optimization to stop as soon as possible, the best solution remains available.
+ * Does not block, use [`sparrow_wait`] to wait for the optimization to finish.
+ *
+ * # Safety
+ * `solver` must be NULL or a pointer returned by [`sparrow_solver_new`].
+ */
+enum SparrowStatus sparrow_cancel(const struct SparrowSolver *solver);
+
+/**
+ * Blocks until the optimization has finished.
+ *
+ * # Safety
+ * `solver` must be NULL or a pointer returned by [`sparrow_solver_new`].
+ */
+enum SparrowStatus sparrow_wait(const struct SparrowSolver *solver);
+
+/**
+ * Cancels any running optimization, waits for it to finish and frees the solver.
+ *
+ * # Safety
+ * `solver` must be NULL or a pointer returned by [`sparrow_solver_new`] which has not been freed yet.
+ */
+void sparrow_solver_free(struct SparrowSolver *solver);
+
+/**
+ * Frees a string returned by the library.
+ *
+ * # Safety
+ * `s` must be NULL or a string returned by the library which has not been freed yet.
+ */
+void sparrow_string_free(char *s);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SPARROW_H */
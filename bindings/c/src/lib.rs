@@ -0,0 +1,365 @@
+//! C ABI for sparrow, see `include/sparrow.h` (generated by cbindgen) and `examples/example.c`.
+//!
+//! A [`SparrowSolver`] is created from an instance in the JSON format of jagua-rs.
+//! [`sparrow_start`] optimizes it on a background thread, during which [`sparrow_poll`] and
+//! [`sparrow_best_solution_json`] can be used to monitor the progress and retrieve the best solution found so far.
+//!
+//! Unless stated otherwise, all functions are safe to call from any thread, but not concurrently with [`sparrow_solver_free`].
+//! Strings returned by the library must be freed with [`sparrow_string_free`].
+
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::probs::spp::io::ext_repr::ExtSPInstance;
+use sparrow::bounds::LowerBounds;
+use sparrow::config::{WorkBudget, DEFAULT_SPARROW_CONFIG};
+use sparrow::optimizer::compact::remnant_area;
+use sparrow::util::io::ExtSPOutput;
+use sparrow::util::listener::{ReportType, SolutionListener};
+use sparrow::util::terminator::CancelTerminator;
+use sparrow::{Sparrow, EPOCH};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparrowStatus {
+    Ok = 0,
+    /// A required pointer argument was NULL
+    NullArgument,
+    /// The instance JSON could not be parsed or imported, or the options are invalid
+    InvalidInput,
+    /// The optimization has already been started
+    AlreadyStarted,
+    /// The optimization has not been started yet
+    NotStarted,
+    /// The optimization thread panicked
+    Panicked,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SparrowState {
+    /// Created, but not yet started
+    Idle = 0,
+    Running,
+    /// Finished, either because the time limit was reached or because it was cancelled
+    Finished,
+}
+
+/// Options for [`sparrow_start`], initialize with [`sparrow_default_options`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SparrowOptions {
+    /// Seed for the random number generator, only used if `use_seed` is set
+    pub seed: u64,
+    pub use_seed: bool,
+    /// Total time limit in seconds, split between exploration and compression
+    pub time_limit: f64,
+    /// If non-zero, limits the exploration phase by a number of separator iterations instead of time
+    pub exploration_iterations: u64,
+    /// If non-zero, limits the compression phase by a number of separator iterations instead of time
+    pub compression_iterations: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SparrowProgress {
+    pub state: SparrowState,
+    /// Number of (improving) feasible solutions found so far
+    pub n_solutions: u64,
+    /// Strip width of the best feasible solution found so far, NaN if none
+    pub best_strip_width: f64,
+    /// Density of the best feasible solution found so far, NaN if none
+    pub best_density: f64,
+}
+
+/// Opaque handle to an instance and its optimization
+pub struct SparrowSolver {
+    ext_instance: ExtSPInstance,
+    cancelled: Arc<AtomicBool>,
+    best: Arc<Mutex<BestSolution>>,
+    started: AtomicBool,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+#[derive(Default)]
+struct BestSolution {
+    instance: Option<SPInstance>,
+    lower_bounds: Option<LowerBounds>,
+    solution: Option<SPSolution>,
+    n_solutions: u64,
+    finished: bool,
+}
+
+/// Keeps track of the last feasible solution reported during the optimization
+struct BestSolutionListener {
+    best: Arc<Mutex<BestSolution>>,
+}
+
+impl SolutionListener for BestSolutionListener {
+    fn report(&mut self, report: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        if matches!(report, ReportType::ExplFeas | ReportType::CmprFeas | ReportType::Final) {
+            let mut best = self.best.lock().unwrap();
+            if best.instance.is_none() {
+                best.lower_bounds = Some(LowerBounds::new(instance));
+                best.instance = Some(instance.clone());
+            }
+            best.solution = Some(solution.clone());
+            best.n_solutions += 1;
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(msg: impl ToString) {
+    let msg = CString::new(msg.to_string().replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+/// Message of the last error that occurred on the calling thread, or NULL if none.
+/// The returned string is owned by the library and valid until the next call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn sparrow_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |msg| msg.as_ptr()))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn sparrow_default_options() -> SparrowOptions {
+    let cfg = DEFAULT_SPARROW_CONFIG;
+    SparrowOptions {
+        seed: 0,
+        use_seed: false,
+        time_limit: (cfg.expl_cfg.time_limit + cfg.cmpr_cfg.time_limit).as_secs_f64(),
+        exploration_iterations: 0,
+        compression_iterations: 0,
+    }
+}
+
+/// Creates a solver from an instance in the JSON format of jagua-rs (or a solution JSON written by sparrow).
+/// Returns NULL on failure, see [`sparrow_last_error`].
+///
+/// # Safety
+/// `instance_json` must be NULL or a valid null-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_solver_new(instance_json: *const c_char) -> *mut SparrowSolver {
+    if instance_json.is_null() {
+        set_last_error("instance_json is NULL");
+        return ptr::null_mut();
+    }
+    let json = match unsafe { CStr::from_ptr(instance_json) }.to_str() {
+        Ok(json) => json,
+        Err(e) => {
+            set_last_error(format!("instance_json is not valid UTF-8: {e}"));
+            return ptr::null_mut();
+        }
+    };
+    // Accept both plain instances and solutions written by sparrow
+    let ext_instance = serde_json::from_str::<ExtSPOutput>(json)
+        .map(|output| output.instance)
+        .or_else(|_| serde_json::from_str::<ExtSPInstance>(json));
+    match ext_instance {
+        Ok(ext_instance) => Box::into_raw(Box::new(SparrowSolver {
+            ext_instance,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            best: Arc::new(Mutex::new(BestSolution::default())),
+            started: AtomicBool::new(false),
+            thread: Mutex::new(None),
+        })),
+        Err(e) => {
+            set_last_error(format!("could not parse instance: {e}"));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Imports the instance and starts the optimization on a background thread.
+/// `options` may be NULL to use [`sparrow_default_options`].
+///
+/// # Safety
+/// `solver` must be NULL or a pointer returned by [`sparrow_solver_new`], `options` must be NULL or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_start(solver: *const SparrowSolver, options: *const SparrowOptions) -> SparrowStatus {
+    let Some(solver) = (unsafe { solver.as_ref() }) else {
+        return SparrowStatus::NullArgument;
+    };
+    // Hold the lock on the thread handle until it has been spawned, so the solver can only be started once
+    let mut thread = solver.thread.lock().unwrap();
+    if solver.started.load(Ordering::SeqCst) {
+        return SparrowStatus::AlreadyStarted;
+    }
+    let options = unsafe { options.as_ref() }.copied().unwrap_or_else(|| sparrow_default_options());
+    let Ok(time_limit) = Duration::try_from_secs_f64(options.time_limit) else {
+        set_last_error(format!("invalid time limit: {}", options.time_limit));
+        return SparrowStatus::InvalidInput;
+    };
+
+    let mut config = DEFAULT_SPARROW_CONFIG;
+    if options.exploration_iterations > 0 {
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(options.exploration_iterations as usize));
+    }
+    if options.compression_iterations > 0 {
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(options.compression_iterations as usize));
+    }
+
+    let mut builder = Sparrow::builder()
+        .instance(solver.ext_instance.clone())
+        .config(config)
+        .time_limit(time_limit)
+        .listener(BestSolutionListener { best: solver.best.clone() })
+        .terminator(CancelTerminator::new(solver.cancelled.clone()));
+    if options.use_seed {
+        builder = builder.seed(options.seed);
+    }
+    let sparrow = match builder.build() {
+        Ok(sparrow) => sparrow,
+        Err(e) => {
+            set_last_error(format!("{e:#}"));
+            return SparrowStatus::InvalidInput;
+        }
+    };
+
+    let best = solver.best.clone();
+    solver.started.store(true, Ordering::SeqCst);
+    *thread = Some(std::thread::spawn(move || {
+        // The final solution is also reported to the listener
+        sparrow.run();
+        best.lock().unwrap().finished = true;
+    }));
+    SparrowStatus::Ok
+}
+
+/// Writes the current state of the optimization to `progress`.
+///
+/// # Safety
+/// `solver` must be NULL or a pointer returned by [`sparrow_solver_new`], `progress` must be NULL or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_poll(solver: *const SparrowSolver, progress: *mut SparrowProgress) -> SparrowStatus {
+    let (Some(solver), Some(progress)) = (unsafe { solver.as_ref() }, unsafe { progress.as_mut() }) else {
+        return SparrowStatus::NullArgument;
+    };
+    let best = solver.best.lock().unwrap();
+    let state = match (solver.started.load(Ordering::SeqCst), best.finished) {
+        (false, _) => SparrowState::Idle,
+        (true, false) => SparrowState::Running,
+        (true, true) => SparrowState::Finished,
+    };
+    let (best_strip_width, best_density) = match (&best.solution, &best.instance) {
+        (Some(solution), Some(instance)) => (solution.strip_width() as f64, solution.density(instance) as f64),
+        _ => (f64::NAN, f64::NAN),
+    };
+    *progress = SparrowProgress {
+        state,
+        n_solutions: best.n_solutions,
+        best_strip_width,
+        best_density,
+    };
+    SparrowStatus::Ok
+}
+
+/// Best feasible solution found so far, in the same JSON format as the output of the CLI.
+/// Returns NULL if no feasible solution has been found yet. Free the result with [`sparrow_string_free`].
+///
+/// # Safety
+/// `solver` must be NULL or a pointer returned by [`sparrow_solver_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_best_solution_json(solver: *const SparrowSolver) -> *mut c_char {
+    let Some(solver) = (unsafe { solver.as_ref() }) else {
+        set_last_error("solver is NULL");
+        return ptr::null_mut();
+    };
+    let best = solver.best.lock().unwrap();
+    let (Some(solution), Some(instance), Some(lower_bounds)) = (&best.solution, &best.instance, &best.lower_bounds) else {
+        return ptr::null_mut();
+    };
+    let output = ExtSPOutput {
+        instance: solver.ext_instance.clone(),
+        solution: jagua_rs::probs::spp::io::export(instance, solution, *EPOCH),
+        remnant_area: Some(remnant_area(solution)),
+        lower_bound: Some(lower_bounds.best()),
+        gap: Some(lower_bounds.gap(solution.strip_width())),
+        strip_heights: None,
+        item_quantities: None,
+        item_groups: None,
+        quality_zones: None,
+        cut_tree: None,
+        guillotine_feasible: None,
+    };
+    match serde_json::to_string(&output) {
+        Ok(json) => CString::new(json).unwrap().into_raw(),
+        Err(e) => {
+            set_last_error(format!("could not serialize solution: {e}"));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Requests the optimization to stop as soon as possible, the best solution remains available.
+/// Does not block, use [`sparrow_wait`] to wait for the optimization to finish.
+///
+/// # Safety
+/// `solver` must be NULL or a pointer returned by [`sparrow_solver_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_cancel(solver: *const SparrowSolver) -> SparrowStatus {
+    let Some(solver) = (unsafe { solver.as_ref() }) else {
+        return SparrowStatus::NullArgument;
+    };
+    solver.cancelled.store(true, Ordering::SeqCst);
+    SparrowStatus::Ok
+}
+
+/// Blocks until the optimization has finished.
+///
+/// # Safety
+/// `solver` must be NULL or a pointer returned by [`sparrow_solver_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_wait(solver: *const SparrowSolver) -> SparrowStatus {
+    let Some(solver) = (unsafe { solver.as_ref() }) else {
+        return SparrowStatus::NullArgument;
+    };
+    if !solver.started.load(Ordering::SeqCst) {
+        return SparrowStatus::NotStarted;
+    }
+    let thread = solver.thread.lock().unwrap().take();
+    match thread.map(JoinHandle::join) {
+        None | Some(Ok(())) => SparrowStatus::Ok,
+        Some(Err(_)) => {
+            solver.best.lock().unwrap().finished = true;
+            set_last_error("optimization thread panicked");
+            SparrowStatus::Panicked
+        }
+    }
+}
+
+/// Cancels any running optimization, waits for it to finish and frees the solver.
+///
+/// # Safety
+/// `solver` must be NULL or a pointer returned by [`sparrow_solver_new`] which has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_solver_free(solver: *mut SparrowSolver) {
+    if solver.is_null() {
+        return;
+    }
+    let solver = unsafe { Box::from_raw(solver) };
+    solver.cancelled.store(true, Ordering::SeqCst);
+    if let Some(thread) = solver.thread.lock().unwrap().take() {
+        let _ = thread.join();
+    }
+}
+
+/// Frees a string returned by the library.
+///
+/// # Safety
+/// `s` must be NULL or a string returned by the library which has not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sparrow_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
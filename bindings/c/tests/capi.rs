@@ -0,0 +1,40 @@
+use sparrow_c::*;
+use std::ffi::{CStr, CString};
+use std::ptr;
+use std::time::Duration;
+
+const SWIM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../data/input/swim.json");
+
+#[test]
+fn start_poll_cancel_free() {
+    let json = CString::new(std::fs::read_to_string(SWIM).unwrap()).unwrap();
+    unsafe {
+        assert!(sparrow_solver_new(c"not json".as_ptr()).is_null());
+        assert!(!sparrow_last_error().is_null());
+
+        let solver = sparrow_solver_new(json.as_ptr());
+        assert!(!solver.is_null());
+        assert_eq!(sparrow_wait(solver), SparrowStatus::NotStarted);
+        assert!(sparrow_best_solution_json(solver).is_null());
+
+        let options = SparrowOptions { seed: 0, use_seed: true, time_limit: 600.0, ..sparrow_default_options() };
+        assert_eq!(sparrow_start(solver, &options), SparrowStatus::Ok);
+        assert_eq!(sparrow_start(solver, ptr::null()), SparrowStatus::AlreadyStarted);
+
+        std::thread::sleep(Duration::from_secs(1));
+        assert_eq!(sparrow_cancel(solver), SparrowStatus::Ok);
+        assert_eq!(sparrow_wait(solver), SparrowStatus::Ok);
+
+        let mut progress = std::mem::zeroed::<SparrowProgress>();
+        assert_eq!(sparrow_poll(solver, &mut progress), SparrowStatus::Ok);
+        assert_eq!(progress.state, SparrowState::Finished);
+        assert!(progress.n_solutions > 0);
+
+        let solution = sparrow_best_solution_json(solver);
+        assert!(!solution.is_null());
+        let output: serde_json::Value = serde_json::from_str(CStr::from_ptr(solution).to_str().unwrap()).unwrap();
+        assert_eq!(output["solution"]["strip_width"].as_f64().unwrap() as f32, progress.best_strip_width as f32);
+        sparrow_string_free(solution);
+        sparrow_solver_free(solver);
+    }
+}
@@ -0,0 +1,467 @@
+//! Python bindings for sparrow.
+//!
+//! ```python
+//! import sparrow
+//!
+//! instance = sparrow.Instance.from_file("data/input/swim.json")
+//! result = sparrow.optimize(instance, seed=42, time_limit=60.0, callback=lambda report, sol: print(report, sol.strip_width))
+//! print(result.solution.strip_width, result.gap)
+//! ```
+
+use clap::ValueEnum;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::probs::spp::io::ext_repr::{ExtSPInstance, ExtSPSolution};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use sparrow::config::{IslandConfig, MigrationTopology, ShrinkDecayStrategy, SparrowConfig, WorkBudget, DEFAULT_SPARROW_CONFIG};
+use sparrow::consts::{DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO, DEFAULT_FAIL_DECAY_RATIO_CMPR, DEFAULT_ISLAND_MIGRATION_INTERVAL, DEFAULT_MAX_CONSEQ_FAILS_EXPL};
+use sparrow::sample::refine::RefinerType;
+use sparrow::sample::search::ContainerSampler;
+use sparrow::util::io::{read_spp_input, ExtSPOutput};
+use sparrow::util::listener::{ReportType, SolutionListener};
+use sparrow::util::terminator::CancelTerminator;
+use sparrow::{Sparrow, EPOCH};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A strip packing instance, in the JSON format of jagua-rs
+#[pyclass(module = "sparrow", skip_from_py_object)]
+#[derive(Clone)]
+pub struct Instance {
+    pub ext: ExtSPInstance,
+}
+
+#[pymethods]
+impl Instance {
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let ext = serde_json::from_str(json).map_err(|e| PyValueError::new_err(format!("invalid instance: {e}")))?;
+        Ok(Self { ext })
+    }
+
+    #[staticmethod]
+    fn from_dict(py: Python<'_>, dict: &Bound<'_, PyDict>) -> PyResult<Self> {
+        Self::from_json(&to_json(py, dict.as_any())?)
+    }
+
+    /// Loads an instance, or the instance of a solution JSON file
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let (ext, _) = read_spp_input(Path::new(path)).map_err(|e| PyValueError::new_err(format!("{e:#}")))?;
+        Ok(Self { ext })
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.ext.name
+    }
+
+    #[getter]
+    fn strip_height(&self) -> f32 {
+        self.ext.strip_height
+    }
+
+    /// Total number of items, counting every copy of an item separately
+    #[getter]
+    fn n_items(&self) -> u64 {
+        self.ext.items.iter().map(|item| item.demand).sum()
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.ext).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        from_json(py, &self.to_json()?)
+    }
+}
+
+/// Configuration of the optimization, defaults to the configuration of the CLI
+#[pyclass(module = "sparrow", get_all, set_all, skip_from_py_object)]
+#[derive(Clone)]
+pub struct Config {
+    /// Time limit of the exploration phase in seconds
+    pub exploration_time: f64,
+    /// Time limit of the compression phase in seconds
+    pub compression_time: f64,
+    /// Limits the exploration phase by a number of separator iterations instead of time
+    pub exploration_iterations: Option<usize>,
+    /// Limits the compression phase by a number of separator iterations instead of time
+    pub compression_iterations: Option<usize>,
+    pub early_termination: bool,
+    /// Number of parallel islands to explore with
+    pub islands: Option<usize>,
+    /// Time between two migrations of the islands in seconds
+    pub migration_interval: f64,
+    /// One of "ring" or "fully-connected"
+    pub migration_topology: String,
+    pub n_workers: usize,
+    pub adaptive_sampling: bool,
+    pub gap_aware_sampling: bool,
+    /// One of "coord-descent", "pattern-search", "nelder-mead" or "pole-gradient"
+    pub exploration_refiner: String,
+    /// One of "coord-descent", "pattern-search", "nelder-mead" or "pole-gradient"
+    pub compression_refiner: String,
+}
+
+#[pymethods]
+impl Config {
+    #[new]
+    #[pyo3(signature = (**kwargs))]
+    fn new(py: Python<'_>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let cfg = DEFAULT_SPARROW_CONFIG;
+        let config = Self {
+            exploration_time: cfg.expl_cfg.time_limit.as_secs_f64(),
+            compression_time: cfg.cmpr_cfg.time_limit.as_secs_f64(),
+            exploration_iterations: None,
+            compression_iterations: None,
+            early_termination: false,
+            islands: None,
+            migration_interval: DEFAULT_ISLAND_MIGRATION_INTERVAL.as_secs_f64(),
+            migration_topology: value_name(MigrationTopology::Ring),
+            n_workers: cfg.expl_cfg.separator_config.n_workers,
+            adaptive_sampling: cfg.expl_cfg.separator_config.sample_config.adaptive,
+            gap_aware_sampling: cfg.expl_cfg.separator_config.sample_config.container_sampler == ContainerSampler::GapAware,
+            exploration_refiner: value_name(cfg.expl_cfg.separator_config.sample_config.refiner),
+            compression_refiner: value_name(cfg.cmpr_cfg.separator_config.sample_config.refiner),
+        };
+        // Set the keyword arguments through Python, so they are validated like regular attribute assignments
+        let py_config = Bound::new(py, config)?;
+        if let Some(kwargs) = kwargs {
+            for (key, value) in kwargs.iter() {
+                py_config.setattr(key.extract::<String>()?, value)?;
+            }
+        }
+        let config = py_config.borrow().clone();
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn __repr__(&self) -> String {
+        let opt = |v: Option<usize>| v.map_or("None".to_string(), |v| v.to_string());
+        let bool = |b: bool| if b { "True" } else { "False" };
+        format!(
+            "Config(exploration_time={:?}, compression_time={:?}, exploration_iterations={}, compression_iterations={}, early_termination={}, islands={}, migration_interval={:?}, migration_topology='{}', n_workers={}, adaptive_sampling={}, gap_aware_sampling={}, exploration_refiner='{}', compression_refiner='{}')",
+            self.exploration_time, self.compression_time, opt(self.exploration_iterations), opt(self.compression_iterations),
+            bool(self.early_termination), opt(self.islands), self.migration_interval, self.migration_topology, self.n_workers, bool(self.adaptive_sampling), bool(self.gap_aware_sampling),
+            self.exploration_refiner, self.compression_refiner
+        )
+    }
+}
+
+impl Config {
+    fn validate(&self) -> PyResult<()> {
+        if self.islands == Some(0) {
+            return Err(PyValueError::new_err("number of islands should be positive"));
+        }
+        if self.migration_interval.is_nan() || self.migration_interval <= 0.0 {
+            return Err(PyValueError::new_err("migration interval should be positive"));
+        }
+        Ok(())
+    }
+
+    fn to_sparrow_config(&self) -> PyResult<SparrowConfig> {
+        self.validate()?;
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.time_limit = Duration::try_from_secs_f64(self.exploration_time).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        config.cmpr_cfg.time_limit = Duration::try_from_secs_f64(self.compression_time).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        config.expl_cfg.work_budget = self.exploration_iterations.map(WorkBudget::Iterations);
+        config.cmpr_cfg.work_budget = self.compression_iterations.map(WorkBudget::Iterations);
+        if self.early_termination {
+            config.expl_cfg.max_conseq_failed_attempts = Some(DEFAULT_MAX_CONSEQ_FAILS_EXPL);
+            config.cmpr_cfg.shrink_decay = ShrinkDecayStrategy::FailureBased(DEFAULT_FAIL_DECAY_RATIO_CMPR);
+        }
+        let topology = MigrationTopology::from_str(&self.migration_topology, true)
+            .map_err(|e| PyValueError::new_err(format!("invalid migration topology '{}': {e}", self.migration_topology)))?;
+        let migration_interval = Duration::try_from_secs_f64(self.migration_interval).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        config.expl_cfg.island_config = self.islands.map(|n_islands| IslandConfig { n_islands, migration_interval, topology });
+        let container_sampler = match self.gap_aware_sampling {
+            true => ContainerSampler::GapAware,
+            false => ContainerSampler::Uniform,
+        };
+        for (sep_config, refiner) in [
+            (&mut config.expl_cfg.separator_config, &self.exploration_refiner),
+            (&mut config.cmpr_cfg.separator_config, &self.compression_refiner),
+        ] {
+            sep_config.n_workers = self.n_workers;
+            sep_config.sample_config.adaptive = self.adaptive_sampling;
+            sep_config.sample_config.container_sampler = container_sampler;
+            sep_config.sample_config.refiner = RefinerType::from_str(refiner, true)
+                .map_err(|e| PyValueError::new_err(format!("invalid refiner '{refiner}': {e}")))?;
+        }
+        Ok(config)
+    }
+}
+
+/// Name of an option as accepted by the CLI
+fn value_name(value: impl ValueEnum) -> String {
+    value.to_possible_value().expect("no skipped variants").get_name().to_string()
+}
+
+/// Placement of a single item, as in the JSON output
+#[pyclass(module = "sparrow", get_all, frozen, skip_from_py_object)]
+#[derive(Clone)]
+pub struct Placement {
+    pub item_id: u64,
+    /// Rotation in degrees
+    pub rotation: f32,
+    pub x: f32,
+    pub y: f32,
+}
+
+#[pymethods]
+impl Placement {
+    fn __repr__(&self) -> String {
+        format!("Placement(item_id={}, rotation={}, x={}, y={})", self.item_id, self.rotation, self.x, self.y)
+    }
+}
+
+/// A (possibly infeasible) solution, as in the JSON output
+#[pyclass(module = "sparrow", frozen, from_py_object)]
+#[derive(Clone)]
+pub struct Solution {
+    pub ext: ExtSPSolution,
+}
+
+impl Solution {
+    fn new(instance: &SPInstance, solution: &SPSolution) -> Self {
+        Self { ext: jagua_rs::probs::spp::io::export(instance, solution, *EPOCH) }
+    }
+}
+
+#[pymethods]
+impl Solution {
+    #[getter]
+    fn strip_width(&self) -> f32 {
+        self.ext.strip_width
+    }
+
+    #[getter]
+    fn density(&self) -> f32 {
+        self.ext.density
+    }
+
+    #[getter]
+    fn placements(&self) -> Vec<Placement> {
+        self.ext.layout.placed_items.iter()
+            .map(|pi| Placement {
+                item_id: pi.item_id,
+                rotation: pi.transformation.rotation,
+                x: pi.transformation.translation.0,
+                y: pi.transformation.translation.1,
+            })
+            .collect()
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.ext).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        from_json(py, &self.to_json()?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Solution(strip_width={}, density={}, n_placements={})", self.ext.strip_width, self.ext.density, self.ext.layout.placed_items.len())
+    }
+}
+
+/// Final solution of [`optimize`], together with statistics and timings (in seconds)
+#[pyclass(module = "sparrow", frozen)]
+pub struct OptimizeResult {
+    #[pyo3(get)]
+    pub solution: Solution,
+    /// Area of the free rectangle at the right end of the strip
+    #[pyo3(get)]
+    pub remnant_area: f32,
+    #[pyo3(get)]
+    pub lower_bound: f32,
+    /// Relative gap between the strip width and the lower bound
+    #[pyo3(get)]
+    pub gap: f32,
+    /// Strip width of the final solution of the exploration phase
+    #[pyo3(get)]
+    pub exploration_strip_width: f32,
+    #[pyo3(get)]
+    pub exploration_time: f64,
+    #[pyo3(get)]
+    pub compression_time: f64,
+    #[pyo3(get)]
+    pub total_time: f64,
+    /// Whether the optimization was cancelled before its time limit
+    #[pyo3(get)]
+    pub cancelled: bool,
+    output: ExtSPOutput,
+}
+
+#[pymethods]
+impl OptimizeResult {
+    /// Same format as the JSON written by the CLI, can be used as input to warm start from
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.output).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        from_json(py, &self.to_json()?)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("OptimizeResult(strip_width={}, density={}, gap={}, total_time={:.3})", self.solution.ext.strip_width, self.solution.ext.density, self.gap, self.total_time)
+    }
+}
+
+/// Can be passed to [`optimize`] and cancelled from another Python thread
+#[pyclass(module = "sparrow", frozen, from_py_object)]
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    pub flag: Arc<AtomicBool>,
+}
+
+#[pymethods]
+impl CancelToken {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn cancel(&self) {
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    #[getter]
+    fn cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+/// Forwards every reported solution to a Python callable `callback(report_type: str, solution: Solution)`.
+/// Returning `False` from the callback cancels the optimization, as does raising an exception (which is re-raised by [`optimize`]).
+struct PyCallbackListener {
+    callback: Py<PyAny>,
+    cancel: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<PyErr>>>,
+}
+
+impl SolutionListener for PyCallbackListener {
+    fn report(&mut self, report: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        let report = match report {
+            ReportType::ExplFeas => "expl_feas",
+            ReportType::ExplInfeas => "expl_infeas",
+            ReportType::ExplImproving => "expl_improving",
+            ReportType::CmprFeas => "cmpr_feas",
+            ReportType::Final => "final",
+        };
+        let solution = Solution::new(instance, solution);
+        Python::attach(|py| {
+            match self.callback.call1(py, (report, solution)) {
+                Ok(ret) => {
+                    if ret.bind(py).extract::<bool>().is_ok_and(|proceed| !proceed) {
+                        self.cancel.store(true, Ordering::SeqCst);
+                    }
+                }
+                Err(err) => {
+                    self.cancel.store(true, Ordering::SeqCst);
+                    self.error.lock().unwrap().get_or_insert(err);
+                }
+            }
+        });
+    }
+}
+
+/// Optimizes an instance and returns the final solution.
+///
+/// `time_limit` (in seconds) is split between exploration and compression and overrides the times of `config`.
+/// `callback(report_type: str, solution: Solution)` is called for every intermediate solution.
+/// `warm_start` is an `OptimizeResult` or a `Solution` of the same instance to start from.
+/// The GIL is released during the optimization, so `cancel` can be triggered from another Python thread.
+#[pyfunction]
+#[pyo3(signature = (instance, config=None, seed=None, time_limit=None, callback=None, cancel=None, warm_start=None))]
+#[allow(clippy::too_many_arguments)]
+fn optimize(
+    py: Python<'_>,
+    instance: &Instance,
+    config: Option<&Config>,
+    seed: Option<u64>,
+    time_limit: Option<f64>,
+    callback: Option<Py<PyAny>>,
+    cancel: Option<CancelToken>,
+    warm_start: Option<&Bound<'_, PyAny>>,
+) -> PyResult<OptimizeResult> {
+    let sparrow_config = match config {
+        Some(config) => config.to_sparrow_config()?,
+        None => DEFAULT_SPARROW_CONFIG,
+    };
+    let warm_start = match warm_start {
+        None => None,
+        Some(ws) => match ws.cast::<OptimizeResult>() {
+            Ok(result) => Some(result.get().solution.ext.clone()),
+            Err(_) => Some(ws.extract::<Solution>()?.ext),
+        },
+    };
+
+    let cancel = cancel.unwrap_or_default();
+    let error = Arc::new(Mutex::new(None));
+
+    let mut builder = Sparrow::builder()
+        .instance(instance.ext.clone())
+        .config(sparrow_config)
+        .terminator(CancelTerminator::new(cancel.flag.clone()))
+        .listener(callback.map(|callback| PyCallbackListener {
+            callback,
+            cancel: cancel.flag.clone(),
+            error: error.clone(),
+        }));
+    if let Some(seed) = seed {
+        builder = builder.seed(seed);
+    }
+    if let Some(time_limit) = time_limit {
+        builder = builder.time_limit(Duration::try_from_secs_f64(time_limit).map_err(|e| PyValueError::new_err(e.to_string()))?);
+    }
+    if let Some(ws) = warm_start {
+        builder = builder.warm_start(ws);
+    }
+    let sparrow = builder.build().map_err(|e| PyValueError::new_err(format!("{e:#}")))?;
+
+    let result = py.detach(|| sparrow.run());
+
+    if let Some(err) = error.lock().unwrap().take() {
+        return Err(err);
+    }
+    Ok(OptimizeResult {
+        solution: Solution::new(&result.instance, &result.solution),
+        remnant_area: result.remnant_area,
+        lower_bound: result.lower_bounds.best(),
+        gap: result.gap,
+        exploration_strip_width: result.exploration_strip_width,
+        exploration_time: result.timings.exploration.as_secs_f64(),
+        compression_time: (result.timings.compression + result.timings.compaction).as_secs_f64(),
+        total_time: result.timings.total.as_secs_f64(),
+        cancelled: cancel.cancelled(),
+        output: result.to_ext_output().expect("instance was provided in its external representation"),
+    })
+}
+
+fn to_json(py: Python<'_>, obj: &Bound<'_, PyAny>) -> PyResult<String> {
+    py.import("json")?.call_method1("dumps", (obj,))?.extract()
+}
+
+fn from_json<'py>(py: Python<'py>, json: &str) -> PyResult<Bound<'py, PyAny>> {
+    py.import("json")?.call_method1("loads", (json,))
+}
+
+#[pymodule(name = "sparrow")]
+fn sparrow_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Instance>()?;
+    m.add_class::<Config>()?;
+    m.add_class::<Placement>()?;
+    m.add_class::<Solution>()?;
+    m.add_class::<OptimizeResult>()?;
+    m.add_class::<CancelToken>()?;
+    m.add_function(wrap_pyfunction!(optimize, m)?)?;
+    m.add("DEFAULT_EXPLORE_TIME_RATIO", DEFAULT_EXPLORE_TIME_RATIO)?;
+    m.add("DEFAULT_COMPRESS_TIME_RATIO", DEFAULT_COMPRESS_TIME_RATIO)?;
+    Ok(())
+}
@@ -0,0 +1,29 @@
+import threading
+from pathlib import Path
+
+import sparrow
+
+SWIM = Path(__file__).parents[3] / "data" / "input" / "swim.json"
+
+
+def test_optimize_deterministic():
+    instance = sparrow.Instance.from_dict(sparrow.Instance.from_file(str(SWIM)).to_dict())
+    config = sparrow.Config(exploration_iterations=10, compression_iterations=5)
+
+    reports = []
+    result = sparrow.optimize(instance, config=config, seed=0, callback=lambda report, sol: reports.append(report))
+    again = sparrow.optimize(instance, config=config, seed=0)
+
+    assert reports[-1] == "final"
+    assert len(result.solution.placements) == instance.n_items
+    assert result.solution.to_dict() == again.solution.to_dict()
+
+
+def test_cancel():
+    instance = sparrow.Instance.from_file(str(SWIM))
+    token = sparrow.CancelToken()
+    threading.Timer(1.0, token.cancel).start()
+
+    result = sparrow.optimize(instance, seed=0, time_limit=600.0, cancel=token)
+    assert result.cancelled
+    assert result.total_time < 60.0
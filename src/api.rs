@@ -0,0 +1,510 @@
+use crate::bounds::{min_item_width, min_strip_height, LowerBounds};
+use crate::config::{IslandConfig, SparrowConfig, DEFAULT_SPARROW_CONFIG};
+use crate::consts::{DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO};
+use crate::groups::{ItemGroup, ItemGroups};
+use crate::guillotine::{bounding_rect_instance, cut_tree, CutTree};
+use crate::optimizer::compact::remnant_area;
+use crate::optimizer::demand::{placed_quantities, remove_optional_items, required_instance, validate_demand_ranges, DemandRange};
+use crate::optimizer::heights::{optimize_strip_heights, StripHeightOption};
+use crate::optimizer::state::OptimizerState;
+use crate::util::io::{ExtItemGroup, ExtItemQuantity, ExtSPOutput, ExtStripHeightReport};
+use crate::util::listener::{DummySolListener, SolutionListener};
+use crate::util::terminator::{BasicTerminator, Terminator};
+use crate::zones::{export_quality_zones, import_quality_zones};
+use crate::EPOCH;
+use anyhow::{ensure, Context, Result};
+use jagua_rs::entities::InferiorQualityZone;
+use jagua_rs::io::ext_repr::ExtQualityZone;
+use jagua_rs::io::import::Importer;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::probs::spp::io::ext_repr::{ExtItem, ExtSPInstance, ExtSPSolution};
+use jagua_rs::Instant;
+use log::{info, warn};
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::SeedableRng;
+use std::time::Duration;
+
+/// Entry point for using sparrow as a library.
+///
+/// ```no_run
+/// # use sparrow::Sparrow;
+/// # use std::time::Duration;
+/// # fn run(ext_instance: jagua_rs::probs::spp::io::ext_repr::ExtSPInstance) -> anyhow::Result<()> {
+/// let result = Sparrow::builder()
+///     .instance(ext_instance)
+///     .seed(42)
+///     .time_limit(Duration::from_secs(60))
+///     .build()?
+///     .run();
+/// println!("strip width: {}", result.strip_width);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Sparrow<L: SolutionListener, T: Terminator + Sync> {
+    pub instance: SPInstance,
+    /// The instance in its external representation, if one was provided
+    pub ext_instance: Option<ExtSPInstance>,
+    pub config: SparrowConfig,
+    pub rng: Xoshiro256PlusPlus,
+    pub listener: L,
+    pub terminator: T,
+    pub warm_start: Option<SPSolution>,
+    /// Demand ranges of the items (indexed by id), the quantities of `instance` are the required ones
+    pub demand_ranges: Option<Vec<DemandRange>>,
+    pub item_groups: ItemGroups,
+    /// Quality zones of the strip
+    pub quality_zones: Vec<InferiorQualityZone>,
+    /// Candidate strip heights, if the instance is optimized for multiple heights
+    pub strip_heights: Vec<StripHeightOption>,
+}
+
+impl Sparrow<DummySolListener, BasicTerminator> {
+    pub fn builder() -> SparrowBuilder<DummySolListener, BasicTerminator> {
+        SparrowBuilder {
+            instance: None,
+            config: DEFAULT_SPARROW_CONFIG,
+            listener: DummySolListener,
+            terminator: BasicTerminator::new(),
+            warm_start: None,
+            demand_ranges: None,
+            item_groups: vec![],
+            quality_zones: vec![],
+            strip_heights: vec![],
+        }
+    }
+}
+
+impl<L: SolutionListener, T: Terminator + Sync> Sparrow<L, T> {
+    /// Runs the full optimization (exploration, compression and compaction).
+    /// With multiple candidate strip heights, the result contains the layout of the cheapest one.
+    pub fn run(mut self) -> SparrowResult {
+        if !self.strip_heights.is_empty() {
+            return self.run_strip_heights();
+        }
+        let start = Instant::now();
+        let lower_bounds = LowerBounds::new(&self.instance);
+        let mut state = OptimizerState::with_constraints(
+            self.instance.clone(),
+            lower_bounds,
+            self.item_groups.clone(),
+            &self.quality_zones,
+            self.rng,
+            &mut self.listener,
+            &mut self.terminator,
+            &self.config.expl_cfg,
+            &self.config.cmpr_cfg,
+            self.warm_start.as_ref(),
+        );
+        state.demand_ranges = self.demand_ranges.clone();
+        let (solution, report) = state.run(&mut self.listener, &mut self.terminator);
+        let total_time = start.elapsed();
+
+        let cut_tree = self.config.cmpr_cfg.guillotine.then(|| cut_tree(&solution)).flatten();
+        let guillotine_feasible = self.config.cmpr_cfg.guillotine.then_some(cut_tree.is_some());
+        SparrowResult {
+            strip_width: solution.strip_width(),
+            density: solution.density(&self.instance),
+            remnant_area: remnant_area(&solution),
+            gap: lower_bounds.gap(solution.strip_width()),
+            lower_bounds,
+            exploration_strip_width: report.exploration_solution.strip_width(),
+            exploration_density: report.exploration_solution.density(&self.instance),
+            timings: Timings {
+                exploration: report.exploration_time,
+                compression: report.compression_time,
+                compaction: report.compaction_time,
+                total: total_time,
+            },
+            solution,
+            instance: self.instance,
+            ext_instance: self.ext_instance,
+            demand_ranges: self.demand_ranges,
+            cut_tree,
+            guillotine_feasible,
+            item_groups: self.item_groups,
+            strip_heights: None,
+        }
+    }
+
+    fn run_strip_heights(mut self) -> SparrowResult {
+        let start = Instant::now();
+        let ext_instance = self.ext_instance.as_ref().expect("strip heights require an external instance, checked when building");
+        let mut result = optimize_strip_heights(ext_instance, &self.strip_heights, &self.config, self.rng, &mut self.listener, &mut self.terminator)
+            .expect("strip heights were validated when building");
+        let total_time = start.elapsed();
+
+        let comparison = result.comparison();
+        let best = result.candidates.swap_remove(result.selected);
+        let (solution, report) = (best.solution.expect("selected height is optimized"), best.report.expect("selected height is optimized"));
+        SparrowResult {
+            strip_width: solution.strip_width(),
+            density: solution.density(&best.instance),
+            remnant_area: remnant_area(&solution),
+            gap: best.lower_bounds.gap(solution.strip_width()),
+            lower_bounds: best.lower_bounds,
+            exploration_strip_width: report.exploration_solution.strip_width(),
+            exploration_density: report.exploration_solution.density(&best.instance),
+            timings: Timings {
+                exploration: report.exploration_time,
+                compression: report.compression_time,
+                compaction: report.compaction_time,
+                total: total_time,
+            },
+            solution,
+            instance: best.instance,
+            ext_instance: self.ext_instance,
+            demand_ranges: None,
+            cut_tree: None,
+            guillotine_feasible: None,
+            item_groups: self.item_groups,
+            strip_heights: Some(comparison),
+        }
+    }
+}
+
+/// Builder for [`Sparrow`], see [`Sparrow::builder`]
+pub struct SparrowBuilder<L: SolutionListener, T: Terminator + Sync> {
+    instance: Option<InstanceInput>,
+    config: SparrowConfig,
+    listener: L,
+    terminator: T,
+    warm_start: Option<WarmStart>,
+    demand_ranges: Option<Vec<DemandRange>>,
+    item_groups: Vec<ItemGroup>,
+    quality_zones: Vec<ExtQualityZone>,
+    strip_heights: Vec<StripHeightOption>,
+}
+
+/// An instance to optimize, either in its external representation or already imported
+pub enum InstanceInput {
+    Ext(ExtSPInstance),
+    Imported(SPInstance),
+}
+
+impl From<ExtSPInstance> for InstanceInput {
+    fn from(ext_instance: ExtSPInstance) -> Self {
+        InstanceInput::Ext(ext_instance)
+    }
+}
+
+impl From<SPInstance> for InstanceInput {
+    fn from(instance: SPInstance) -> Self {
+        InstanceInput::Imported(instance)
+    }
+}
+
+/// A solution to start the optimization from, either in its external representation or already imported
+pub enum WarmStart {
+    Ext(ExtSPSolution),
+    Imported(Box<SPSolution>),
+}
+
+impl From<ExtSPSolution> for WarmStart {
+    fn from(ext_solution: ExtSPSolution) -> Self {
+        WarmStart::Ext(ext_solution)
+    }
+}
+
+impl From<SPSolution> for WarmStart {
+    fn from(solution: SPSolution) -> Self {
+        WarmStart::Imported(Box::new(solution))
+    }
+}
+
+impl<L: SolutionListener, T: Terminator + Sync> SparrowBuilder<L, T> {
+    pub fn instance(mut self, instance: impl Into<InstanceInput>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Replaces the entire configuration (including any seed or time limit set before)
+    pub fn config(mut self, config: SparrowConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.rng_seed = Some(seed as usize);
+        self
+    }
+
+    /// Splits the time limit over exploration and compression using the default ratios
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.config.expl_cfg.time_limit = time_limit.mul_f32(DEFAULT_EXPLORE_TIME_RATIO);
+        self.config.cmpr_cfg.time_limit = time_limit.mul_f32(DEFAULT_COMPRESS_TIME_RATIO);
+        self
+    }
+
+    /// Explores with multiple parallel islands that periodically exchange solutions
+    pub fn islands(mut self, island_config: IslandConfig) -> Self {
+        self.config.expl_cfg.island_config = Some(island_config);
+        self
+    }
+
+    pub fn warm_start(mut self, solution: impl Into<WarmStart>) -> Self {
+        self.warm_start = Some(solution.into());
+        self
+    }
+
+    /// Minimum and maximum quantity of every item (indexed by id), replacing the demands of the instance.
+    /// Only the minimum quantities are optimized, after which as many optional copies as possible are inserted into the free space.
+    pub fn demand_ranges(mut self, ranges: Vec<DemandRange>) -> Self {
+        self.demand_ranges = Some(ranges);
+        self
+    }
+
+    /// Groups of items (by id) whose placed copies must all lie within a maximum horizontal span
+    pub fn item_groups(mut self, groups: Vec<ItemGroup>) -> Self {
+        self.item_groups = groups;
+        self
+    }
+
+    /// Zones of the strip with an inferior quality, which only items with a sufficiently low `min_quality` can overlap
+    pub fn quality_zones(mut self, zones: Vec<ExtQualityZone>) -> Self {
+        self.quality_zones = zones;
+        self
+    }
+
+    /// Candidate strip (roll or sheet) heights with their cost per unit of area, the cheapest layout is selected.
+    /// See [`optimize_strip_heights`] for how the time limit is divided over the heights.
+    /// Not supported together with demand ranges, item groups, quality zones, guillotine cuts or a warm start.
+    pub fn strip_heights(mut self, options: Vec<StripHeightOption>) -> Self {
+        self.strip_heights = options;
+        self
+    }
+
+    pub fn listener<L2: SolutionListener>(self, listener: L2) -> SparrowBuilder<L2, T> {
+        SparrowBuilder {
+            instance: self.instance,
+            config: self.config,
+            listener,
+            terminator: self.terminator,
+            warm_start: self.warm_start,
+            demand_ranges: self.demand_ranges,
+            item_groups: self.item_groups,
+            quality_zones: self.quality_zones,
+            strip_heights: self.strip_heights,
+        }
+    }
+
+    pub fn terminator<T2: Terminator + Sync>(self, terminator: T2) -> SparrowBuilder<L, T2> {
+        SparrowBuilder {
+            instance: self.instance,
+            config: self.config,
+            listener: self.listener,
+            terminator,
+            warm_start: self.warm_start,
+            demand_ranges: self.demand_ranges,
+            item_groups: self.item_groups,
+            quality_zones: self.quality_zones,
+            strip_heights: self.strip_heights,
+        }
+    }
+
+    /// Imports the instance (and warm start) and seeds the random number generator
+    pub fn build(self) -> Result<Sparrow<L, T>> {
+        let config = self.config;
+        if let Some(island_config) = &config.expl_cfg.island_config {
+            ensure!(island_config.n_islands > 0, "number of islands should be positive");
+            ensure!(!island_config.migration_interval.is_zero(), "migration interval should be positive");
+        }
+        let mut warm_start = self.warm_start;
+        if !self.strip_heights.is_empty() {
+            ensure!(matches!(self.instance, Some(InstanceInput::Ext(_))), "multiple strip heights require the instance in its external representation");
+            ensure!(self.demand_ranges.is_none(), "demand ranges (max_demand) are not supported with multiple strip heights");
+            ensure!(self.item_groups.is_empty(), "item groups are not supported with multiple strip heights");
+            ensure!(self.quality_zones.is_empty(), "quality zones are not supported with multiple strip heights");
+            ensure!(!config.cmpr_cfg.guillotine, "guillotine cuts are not supported with multiple strip heights");
+            if warm_start.take().is_some() {
+                warn!("[API] warm starting is not supported with multiple strip heights, ignoring the provided solution");
+            }
+        }
+        let (instance, ext_instance) = match self.instance.context("no instance provided")? {
+            InstanceInput::Imported(instance) => (instance, None),
+            InstanceInput::Ext(ext_instance) => {
+                let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+                // Import every item with its maximum quantity, items without required copies would be dropped otherwise
+                let max_demand_instance = match &self.demand_ranges {
+                    None => ext_instance.clone(),
+                    Some(ranges) => ExtSPInstance {
+                        items: ext_instance.items.iter()
+                            .map(|i| ExtItem { demand: ranges.get(i.base.id as usize).map_or(i.demand, |r| r.max as u64), ..i.clone() })
+                            .collect(),
+                        ..ext_instance.clone()
+                    },
+                };
+                let instance = jagua_rs::probs::spp::io::import_instance(&importer, &max_demand_instance)?;
+                info!("[API] loaded instance {} with #{} items", ext_instance.name, instance.total_item_qty());
+                (instance, Some(ext_instance))
+            }
+        };
+
+        // For guillotine cuts, items collide as their bounding rectangles (also in the warm start)
+        let instance = match config.cmpr_cfg.guillotine {
+            true => bounding_rect_instance(&instance)?,
+            false => instance,
+        };
+
+        if !self.strip_heights.is_empty() {
+            let min_height = min_strip_height(&instance);
+            ensure!(self.strip_heights.iter().any(|o| o.height >= min_height), "none of the strip heights fit all items (minimum height: {min_height:.3})");
+        }
+
+        let warm_start = match warm_start {
+            None => None,
+            Some(WarmStart::Imported(solution)) => Some(*solution),
+            Some(WarmStart::Ext(ext_solution)) => Some(jagua_rs::probs::spp::io::import_solution(&instance, &ext_solution)),
+        };
+
+        // Only the required copies are optimized, the optional ones are inserted at the end
+        let (instance, warm_start) = match &self.demand_ranges {
+            None => (instance, warm_start),
+            Some(ranges) => {
+                validate_demand_ranges(&instance, ranges)?;
+                let warm_start = warm_start.map(|sol| remove_optional_items(&instance, ranges, &sol));
+                let n_optional = ranges.iter().map(|r| r.n_optional()).sum::<usize>();
+                info!("[API] demand ranges: #{} required and #{} optional items", ranges.iter().map(|r| r.min).sum::<usize>(), n_optional);
+                (required_instance(&instance, ranges), warm_start)
+            }
+        };
+
+        let item_groups = ItemGroups::new(self.item_groups, instance.items.len())?;
+        for (g, group) in item_groups.groups().iter().enumerate() {
+            for &id in &group.item_ids {
+                let width = min_item_width(&instance.items[id].0, instance.base_strip.fixed_height);
+                ensure!(width <= group.max_x_span, "item {id} is wider ({width:.3}) than the maximum x-span of item group {g} ({:.3})", group.max_x_span);
+            }
+        }
+        if !item_groups.is_empty() {
+            info!("[API] {} item groups", item_groups.groups().len());
+        }
+
+        let quality_zones = import_quality_zones(&self.quality_zones, &instance.base_strip)?;
+        if !quality_zones.is_empty() {
+            info!("[API] {} quality zones", self.quality_zones.len());
+        }
+
+        if config.cmpr_cfg.guillotine {
+            // The repair shifts items horizontally, without regard for their groups or the zones below them
+            ensure!(item_groups.is_empty(), "item groups are not supported with guillotine cuts");
+            ensure!(quality_zones.is_empty(), "quality zones are not supported with guillotine cuts");
+        }
+
+        let rng = match config.rng_seed {
+            Some(seed) => {
+                info!("[API] using seed: {}", seed);
+                Xoshiro256PlusPlus::seed_from_u64(seed as u64)
+            }
+            None => {
+                let seed = rand::random();
+                warn!("[API] no seed provided, using: {}", seed);
+                Xoshiro256PlusPlus::seed_from_u64(seed)
+            }
+        };
+
+        Ok(Sparrow {
+            instance,
+            ext_instance,
+            config,
+            rng,
+            listener: self.listener,
+            terminator: self.terminator,
+            warm_start,
+            demand_ranges: self.demand_ranges,
+            item_groups,
+            quality_zones,
+            strip_heights: self.strip_heights,
+        })
+    }
+}
+
+/// Final solution of a [`Sparrow`] run, together with statistics and timings
+#[derive(Clone)]
+pub struct SparrowResult {
+    pub solution: SPSolution,
+    pub instance: SPInstance,
+    /// The instance in its external representation, if one was provided
+    pub ext_instance: Option<ExtSPInstance>,
+    /// Demand ranges of the items (indexed by id), if provided
+    pub demand_ranges: Option<Vec<DemandRange>>,
+    pub item_groups: ItemGroups,
+    /// Comparison of all candidate strip heights, if multiple were provided
+    pub strip_heights: Option<Vec<ExtStripHeightReport>>,
+    /// Guillotine cut tree of the solution, if guillotine cuts were required
+    pub cut_tree: Option<CutTree>,
+    /// Whether the solution is separable by guillotine cuts, if they were required.
+    /// Guillotine cuts are only enforced by a repair after the optimization, which can fail.
+    pub guillotine_feasible: Option<bool>,
+    pub strip_width: f32,
+    pub density: f32,
+    /// Area of the free rectangle at the right end of the strip
+    pub remnant_area: f32,
+    pub lower_bounds: LowerBounds,
+    /// Relative gap between the strip width and the best lower bound
+    pub gap: f32,
+    /// Strip width of the final solution of the exploration phase
+    pub exploration_strip_width: f32,
+    /// Density of the final solution of the exploration phase
+    pub exploration_density: f32,
+    pub timings: Timings,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Timings {
+    pub exploration: Duration,
+    pub compression: Duration,
+    pub compaction: Duration,
+    pub total: Duration,
+}
+
+impl SparrowResult {
+    /// Converts the result to the JSON output format, requires the instance to have been provided in its external representation.
+    /// The height of the strip in the output is that of the solution.
+    pub fn to_ext_output(&self) -> Option<ExtSPOutput> {
+        let ext_instance = self.ext_instance.clone()?;
+        // The lower bounds only hold for the height of the instance, which may have changed in open-dimension mode
+        let fixed_height = self.solution.strip.fixed_height == self.instance.base_strip.fixed_height;
+        Some(ExtSPOutput {
+            instance: ExtSPInstance { strip_height: self.solution.strip.fixed_height, ..ext_instance },
+            solution: jagua_rs::probs::spp::io::export(&self.instance, &self.solution, *EPOCH),
+            remnant_area: Some(self.remnant_area),
+            lower_bound: fixed_height.then(|| self.lower_bounds.best()),
+            gap: fixed_height.then_some(self.gap),
+            strip_heights: self.strip_heights.clone(),
+            item_quantities: self.item_quantities(),
+            item_groups: self.item_group_spans(),
+            quality_zones: export_quality_zones(&self.solution.layout_snapshot.container),
+            cut_tree: self.cut_tree.clone(),
+            guillotine_feasible: self.guillotine_feasible,
+        })
+    }
+
+    /// Placed quantity of every item with its demand range, if the instance has demand ranges
+    pub fn item_quantities(&self) -> Option<Vec<ExtItemQuantity>> {
+        let ranges = self.demand_ranges.as_ref()?;
+        let placed = placed_quantities(ranges.len(), &self.solution);
+        Some(ranges.iter().zip(placed).enumerate()
+            .map(|(id, (range, placed))| ExtItemQuantity {
+                item_id: id as u64,
+                demand: range.min as u64,
+                max_demand: range.max as u64,
+                placed: placed as u64,
+            })
+            .collect())
+    }
+
+    /// Every item group with the span of its items in the solution, if the instance has item groups
+    pub fn item_group_spans(&self) -> Option<Vec<ExtItemGroup>> {
+        if self.item_groups.is_empty() {
+            return None;
+        }
+        let spans = self.item_groups.spans(self.solution.layout_snapshot.placed_items.values());
+        Some(self.item_groups.groups().iter().zip(spans)
+            .map(|(group, span)| ExtItemGroup {
+                item_ids: group.item_ids.iter().map(|&id| id as u64).collect(),
+                max_x_span: group.max_x_span,
+                x_span: Some(span),
+            })
+            .collect())
+    }
+}
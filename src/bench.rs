@@ -1,13 +1,12 @@
 extern crate core;
-use sparrow::util::terminator::Terminator;
 
-use jagua_rs::Instant;
 use ordered_float::OrderedFloat;
-use rand::{Rng, RngExt, SeedableRng};
+use rand::{RngExt, SeedableRng};
+use sparrow::bounds::LowerBounds;
 use sparrow::config::*;
-use sparrow::optimizer::lbf::LBFBuilder;
-use sparrow::optimizer::separator::Separator;
+use sparrow::sample::search::ContainerSampler;
 use sparrow::util::io;
+use sparrow::Sparrow;
 use std::env::args;
 use std::fs;
 use std::path::Path;
@@ -17,11 +16,7 @@ use anyhow::Result;
 use jagua_rs::io::import::Importer;
 use jagua_rs::io::svg::s_layout_to_svg;
 use rand::rngs::Xoshiro256PlusPlus;
-use sparrow::consts::{DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO, DRAW_OPTIONS, LBF_SAMPLE_CONFIG};
-use sparrow::optimizer::compress::compression_phase;
-use sparrow::optimizer::explore::exploration_phase;
-use sparrow::util::listener::DummySolListener;
-use sparrow::util::terminator::BasicTerminator;
+use sparrow::consts::{DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO, DRAW_OPTIONS};
 
 pub const OUTPUT_DIR: &str = "output";
 
@@ -36,12 +31,21 @@ fn main() -> Result<()> {
     let n_runs_total = args().nth(3).expect("third argument must be the number of runs")
         .parse().expect("third argument must be the number of runs");
 
+    //optionally, the container-wide sampler is the fourth argument ("compare" benches both samplers with the same seeds)
+    let container_samplers = match args().nth(4).as_deref() {
+        None => vec![config.expl_cfg.separator_config.sample_config.container_sampler],
+        Some("uniform") => vec![ContainerSampler::Uniform],
+        Some("gap-aware") => vec![ContainerSampler::GapAware],
+        Some("compare") => vec![ContainerSampler::Uniform, ContainerSampler::GapAware],
+        _ => panic!("fourth argument must be the container sampler: uniform, gap-aware or compare"),
+    };
+
     fs::create_dir_all(OUTPUT_DIR).expect("could not create output directory");
 
     println!("[BENCH] git commit hash: {}", get_git_commit_hash());
     println!("[BENCH] system time: {}", jiff::Timestamp::now());
 
-    let mut rng = match config.rng_seed {
+    let rng = match config.rng_seed {
         Some(seed) => {
             println!("[BENCH] using provided seed: {}", seed);
             Xoshiro256PlusPlus::seed_from_u64(seed as u64)
@@ -69,97 +73,131 @@ fn main() -> Result<()> {
     let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
     let instance = jagua_rs::probs::spp::io::import_instance(&importer, &ext_instance)?;
 
-    let mut final_solutions = vec![];
-
+    let lower_bounds = LowerBounds::new(&instance);
+    println!("[BENCH] lower bound on strip width: {lower_bounds}");
+
+    let mut sampler_gaps = vec![];
+
+    for &container_sampler in container_samplers.iter() {
+        config.expl_cfg.separator_config.sample_config.container_sampler = container_sampler;
+        config.cmpr_cfg.separator_config.sample_config.container_sampler = container_sampler;
+        println!("[BENCH] container sampler: {:?}", container_sampler);
+        //every sampler gets the same seeds
+        let mut rng = rng.clone();
+        //svg files are only tagged with the sampler when comparing
+        let tag = match container_samplers.len() {
+            1 => String::new(),
+            _ => format!("{container_sampler:?}_").to_lowercase(),
+        };
+
+        let mut final_solutions = vec![];
+
+        for i in 0..n_batches {
+            println!("[BENCH] batch {}/{}", i + 1, n_batches);
+            println!("[BENCH] system time: {}", jiff::Timestamp::now());
+            let mut iter_solutions = vec![None; n_runs_per_iter];
+            rayon::scope(|s| {
+                for (j, sol_slice) in iter_solutions.iter_mut().enumerate() {
+                    let bench_idx = i * n_runs_per_iter + j;
+                    let instance = instance.clone();
+                    let seed = rng.random();
+                    let tag = tag.as_str();
+
+                    s.spawn(move |_| {
+                        let result = Sparrow::builder()
+                            .instance(instance)
+                            .config(config)
+                            .seed(seed)
+                            .build()
+                            .expect("could not build optimizer")
+                            .run();
+
+                        println!("[BENCH] [id:{:>3}] finished, expl: {:.3}% ({}s), cmpr: {:.3}% (+{:.3}%) ({}s)",
+                                 bench_idx,
+                                 result.exploration_density * 100.0, result.timings.exploration.as_secs(),
+                                 result.density * 100.0,
+                                 result.density * 100.0 - result.exploration_density * 100.0,
+                                 (result.timings.compression + result.timings.compaction).as_secs()
+                        );
+
+                        io::write_svg(
+                            &s_layout_to_svg(&result.solution.layout_snapshot, &result.instance, DRAW_OPTIONS, &format!("final_bench_{tag}{}", bench_idx)),
+                            Path::new(&format!("{OUTPUT_DIR}/final_bench_{tag}{}.svg", bench_idx)),
+                            log::Level::Info,
+                        ).unwrap_or_else(|_| panic!("could not write svg output of bench {}", bench_idx));
+
+                        *sol_slice = Some(result.solution);
+                    })
+                }
+            });
+            final_solutions.extend(iter_solutions.into_iter().flatten());
+        }
 
-    for i in 0..n_batches {
-        println!("[BENCH] batch {}/{}", i + 1, n_batches);
+        //print statistics about the solutions, print best, worst, median and average
+        let (final_widths, final_usages): (Vec<f32>, Vec<f32>) = final_solutions
+            .iter()
+            .map(|s| {
+                let width = s.strip_width();
+                let usage = s.layout_snapshot.density(&instance);
+                (width, usage * 100.0)
+            })
+            .unzip();
+        let final_gaps = final_widths.iter().map(|w| lower_bounds.gap(*w) * 100.0).collect::<Vec<f32>>();
+
+        let best_final_solution = final_solutions.iter().max_by_key(|s| OrderedFloat(s.density(&instance))).unwrap();
+
+        io::write_svg(
+            &s_layout_to_svg(&best_final_solution.layout_snapshot, &instance, DRAW_OPTIONS, "final_best"),
+            Path::new(format!("{OUTPUT_DIR}/final_best_{tag}{}.svg", ext_instance.name).as_str()),
+            log::Level::Info,
+        )?;
+
+        println!("==== BENCH FINISHED ====");
+
+        println!("widths:\n{:?}", &final_widths);
+        println!("usages:\n{:?}", &final_usages);
+        println!("gaps:\n{:?}", &final_gaps);
+
+        println!("---- WIDTH STATS ----");
+        println!("worst:  {:.3}", final_widths.iter().max_by_key(|&x| OrderedFloat(*x)).unwrap());
+        println!("25%:    {:.3}", calculate_percentile(&final_widths, 0.75));
+        println!("med:    {:.3}", calculate_median(&final_widths));
+        println!("75%:    {:.3}", calculate_percentile(&final_widths, 0.25));
+        println!("best:   {:.3}", final_widths.iter().min_by_key(|&x| OrderedFloat(*x)).unwrap());
+        println!("avg:    {:.3}", calculate_average(&final_widths));
+        println!("stddev: {:.3}", calculate_stddev(&final_widths));
+        println!("---- USAGE STATS ----");
+        println!("worst:  {:.3}", final_usages.iter().min_by_key(|&x| OrderedFloat(*x)).unwrap());
+        println!("25%:    {:.3}", calculate_percentile(&final_usages, 0.25));
+        println!("median: {:.3}", calculate_median(&final_usages));
+        println!("75%:    {:.3}", calculate_percentile(&final_usages, 0.75));
+        println!("best:   {:.3}", final_usages.iter().max_by_key(|&x| OrderedFloat(*x)).unwrap());
+        println!("avg:    {:.3}", calculate_average(&final_usages));
+        println!("stddev: {:.3}", calculate_stddev(&final_usages));
+        println!("---- GAP STATS (lb: {:.3}) ----", lower_bounds.best());
+        println!("worst:  {:.3}", final_gaps.iter().max_by_key(|&x| OrderedFloat(*x)).unwrap());
+        println!("median: {:.3}", calculate_median(&final_gaps));
+        println!("best:   {:.3}", final_gaps.iter().min_by_key(|&x| OrderedFloat(*x)).unwrap());
+        println!("avg:    {:.3}", calculate_average(&final_gaps));
+        println!("======================");
         println!("[BENCH] system time: {}", jiff::Timestamp::now());
-        let mut iter_solutions = vec![None; n_runs_per_iter];
-        rayon::scope(|s| {
-            for (j, sol_slice) in iter_solutions.iter_mut().enumerate() {
-                let bench_idx = i * n_runs_per_iter + j;
-                let instance = instance.clone();
-                let mut rng = Xoshiro256PlusPlus::seed_from_u64(rng.random());
-                let mut terminator = BasicTerminator::new();
-
-                s.spawn(move |_| {
-                    let mut next_rng = || Xoshiro256PlusPlus::seed_from_u64(rng.next_u64());
-                    let builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG).construct();
-                    let mut expl_separator = Separator::new(builder.instance, builder.prob, next_rng(), config.expl_cfg.separator_config);
-
-                    terminator.new_timeout(config.expl_cfg.time_limit);
-                    let solutions = exploration_phase(&instance, &mut expl_separator, &mut DummySolListener, &terminator, &config.expl_cfg);
-                    let final_explore_sol = solutions.last().expect("no solutions found during exploration");
-
-                    let start_comp = Instant::now();
-
-                    terminator.new_timeout(config.cmpr_cfg.time_limit);
-                    let mut cmpr_separator = Separator::new(expl_separator.instance, expl_separator.prob, next_rng(), config.cmpr_cfg.separator_config);
-                    let cmpr_sol = compression_phase(&instance, &mut cmpr_separator, final_explore_sol, &mut DummySolListener, &terminator, &config.cmpr_cfg);
-
-                    println!("[BENCH] [id:{:>3}] finished, expl: {:.3}% ({}s), cmpr: {:.3}% (+{:.3}%) ({}s)",
-                             bench_idx,
-                             final_explore_sol.density(&instance) * 100.0, time_limit.mul_f32(DEFAULT_EXPLORE_TIME_RATIO).as_secs(),
-                             cmpr_sol.density(&instance) * 100.0,
-                             cmpr_sol.density(&instance) * 100.0 - final_explore_sol.density(&instance) * 100.0,
-                             start_comp.elapsed().as_secs()
-                    );
-
-                    io::write_svg(
-                        &s_layout_to_svg(&cmpr_sol.layout_snapshot, &instance, DRAW_OPTIONS, &format!("final_bench_{}", bench_idx)),
-                        Path::new(&format!("{OUTPUT_DIR}/final_bench_{}.svg", bench_idx)),
-                        log::Level::Info,
-                    ).unwrap_or_else(|_| panic!("could not write svg output of bench {}", bench_idx));
-
-                    *sol_slice = Some(cmpr_sol);
-                })
-            }
-        });
-        final_solutions.extend(iter_solutions.into_iter().flatten());
+
+        sampler_gaps.push((container_sampler, final_gaps));
     }
 
-    //print statistics about the solutions, print best, worst, median and average
-    let (final_widths, final_usages): (Vec<f32>, Vec<f32>) = final_solutions
-        .iter()
-        .map(|s| {
-            let width = s.strip_width();
-            let usage = s.layout_snapshot.density(&instance);
-            (width, usage * 100.0)
-        })
-        .unzip();
-
-    let best_final_solution = final_solutions.iter().max_by_key(|s| OrderedFloat(s.density(&instance))).unwrap();
-
-    io::write_svg(
-        &s_layout_to_svg(&best_final_solution.layout_snapshot, &instance, DRAW_OPTIONS, "final_best"),
-        Path::new(format!("{OUTPUT_DIR}/final_best_{}.svg", ext_instance.name).as_str()),
-        log::Level::Info,
-    )?;
-
-    println!("==== BENCH FINISHED ====");
-
-    println!("widths:\n{:?}", &final_widths);
-    println!("usages:\n{:?}", &final_usages);
-
-    println!("---- WIDTH STATS ----");
-    println!("worst:  {:.3}", final_widths.iter().max_by_key(|&x| OrderedFloat(*x)).unwrap());
-    println!("25%:    {:.3}", calculate_percentile(&final_widths, 0.75));
-    println!("med:    {:.3}", calculate_median(&final_widths));
-    println!("75%:    {:.3}", calculate_percentile(&final_widths, 0.25));
-    println!("best:   {:.3}", final_widths.iter().min_by_key(|&x| OrderedFloat(*x)).unwrap());
-    println!("avg:    {:.3}", calculate_average(&final_widths));
-    println!("stddev: {:.3}", calculate_stddev(&final_widths));
-    println!("---- USAGE STATS ----");
-    println!("worst:  {:.3}", final_usages.iter().min_by_key(|&x| OrderedFloat(*x)).unwrap());
-    println!("25%:    {:.3}", calculate_percentile(&final_usages, 0.25));
-    println!("median: {:.3}", calculate_median(&final_usages));
-    println!("75%:    {:.3}", calculate_percentile(&final_usages, 0.75));
-    println!("best:   {:.3}", final_usages.iter().max_by_key(|&x| OrderedFloat(*x)).unwrap());
-    println!("avg:    {:.3}", calculate_average(&final_usages));
-    println!("stddev: {:.3}", calculate_stddev(&final_usages));
-    println!("======================");
-    println!("[BENCH] system time: {}", jiff::Timestamp::now());
+    if sampler_gaps.len() > 1 {
+        println!("==== SAMPLER COMPARISON (gap %, lb: {:.3}) ====", lower_bounds.best());
+        println!("{:<10} {:>8} {:>8} {:>8}", "sampler", "best", "median", "avg");
+        for (container_sampler, gaps) in sampler_gaps.iter() {
+            println!("{:<10} {:>8.3} {:>8.3} {:>8.3}",
+                     format!("{container_sampler:?}"),
+                     gaps.iter().min_by_key(|&x| OrderedFloat(*x)).unwrap(),
+                     calculate_median(gaps),
+                     calculate_average(gaps),
+            );
+        }
+        println!("======================");
+    }
 
     Ok(())
 }
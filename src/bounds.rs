@@ -0,0 +1,172 @@
+use crate::consts::LB_REACHED_TOLERANCE;
+use crate::util::geometry::rotated_bbox;
+use itertools::Itertools;
+use jagua_rs::entities::Item;
+use jagua_rs::geometry::geo_enums::RotationRange;
+use jagua_rs::geometry::primitives::Point;
+use jagua_rs::probs::spp::entities::SPInstance;
+use ordered_float::OrderedFloat;
+use std::cmp::Reverse;
+use std::fmt::Display;
+
+/// Simple lower bounds on the strip width of any feasible solution of an instance.
+#[derive(Debug, Clone, Copy)]
+pub struct LowerBounds {
+    /// Total item area divided by the strip height
+    pub area: f32,
+    /// Minimum width required by the widest item, considering its allowed rotations
+    pub item_dim: f32,
+    /// Width required by a set of items which are pairwise incompatible: none of them can be stacked on top of each other
+    pub incompatibility: f32,
+}
+
+impl LowerBounds {
+    pub fn new(instance: &SPInstance) -> Self {
+        let height = instance.base_strip.fixed_height;
+        Self {
+            area: instance.item_area() / height,
+            item_dim: item_dim_bound(instance),
+            incompatibility: incompatibility_bound(instance),
+        }
+    }
+
+    /// The tightest of all bounds
+    pub fn best(&self) -> f32 {
+        self.area.max(self.item_dim).max(self.incompatibility)
+    }
+
+    /// Relative gap between a strip width and the best lower bound
+    pub fn gap(&self, width: f32) -> f32 {
+        ((width - self.best()) / width).max(0.0)
+    }
+
+    /// Whether a strip width is (practically) equal to the best lower bound, and can thus not be improved upon
+    pub fn is_reached(&self, width: f32) -> bool {
+        width <= self.best() * (1.0 + LB_REACHED_TOLERANCE)
+    }
+}
+
+impl Display for LowerBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.3} (area: {:.3}, item dim: {:.3}, incompatibility: {:.3})", self.best(), self.area, self.item_dim, self.incompatibility)
+    }
+}
+
+/// Every item needs to fit in the strip, in at least one of its allowed rotations
+fn item_dim_bound(instance: &SPInstance) -> f32 {
+    let height = instance.base_strip.fixed_height;
+    instance.items.iter()
+        .filter(|(_, qty)| *qty > 0)
+        .map(|(item, _)| min_item_width(item, height))
+        .fold(0.0, f32::max)
+}
+
+/// Narrowest horizontal extent of an item in any of its allowed rotations, preferring the rotations in which it fits a strip of `height`
+pub fn min_item_width(item: &Item, height: f32) -> f32 {
+    let shape = item.shape_cd.as_ref();
+    match &item.allowed_rotation {
+        RotationRange::None => shape.bbox.width(),
+        RotationRange::Discrete(rotations) => {
+            let extents = rotations.iter().map(|&r| rotated_bbox(&shape.vertices, r)).map(|b| (b.width(), b.height())).collect_vec();
+            // Only consider the rotations in which the item fits the height of the strip (if any)
+            let fitting = extents.iter().filter(|(_, h)| *h <= height).map(|(w, _)| *w);
+            let all = extents.iter().map(|(w, _)| *w);
+            fitting.min_by_key(|w| OrderedFloat(*w))
+                .or(all.min_by_key(|w| OrderedFloat(*w)))
+                .unwrap_or(0.0)
+        }
+        RotationRange::Continuous => {
+            // The narrowest extent of the item in any direction
+            let hull = shape.surrogate().convex_hull_indices.iter().map(|&i| shape.vertex(i)).collect_vec();
+            min_caliper_width(&hull).min(shape.bbox.width())
+        }
+    }
+}
+
+/// Whether every item fits within the height of the strip, in at least one of its allowed rotations
+pub fn items_fit_strip(instance: &SPInstance) -> bool {
+    min_strip_height(instance) <= instance.base_strip.fixed_height
+}
+
+/// Minimum height of a strip in which every item fits, in at least one of its allowed rotations
+pub fn min_strip_height(instance: &SPInstance) -> f32 {
+    instance.items.iter()
+        .filter(|(_, qty)| *qty > 0)
+        .map(|(item, _)| {
+            let shape = item.shape_cd.as_ref();
+            match &item.allowed_rotation {
+                RotationRange::None => shape.bbox.height(),
+                RotationRange::Discrete(rotations) => rotations.iter()
+                    .map(|&r| rotated_bbox(&shape.vertices, r).height())
+                    .fold(f32::INFINITY, f32::min),
+                RotationRange::Continuous => {
+                    let hull = shape.surrogate().convex_hull_indices.iter().map(|&i| shape.vertex(i)).collect_vec();
+                    min_caliper_width(&hull)
+                }
+            }
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Uses the pole of inaccessibility (largest inscribed circle) of every item, which is independent of its rotation.
+/// If the circles of two items are too large to be stacked in the strip, their centers are separated by a minimum horizontal distance.
+/// A set of pairwise incompatible items is ordered horizontally, so the sum of these distances along this order
+/// (bounded from below by the minimum spanning tree) plus the radii of the two outer circles bounds the width.
+///
+/// The distance only depends on the radii and increases with both of them, so the minimum spanning tree is the star connecting
+/// the smallest circle to all others. Adding another copy of the smallest circle only increases the bound,
+/// so only the sets of all copies of the largest items (plus possibly a single copy of the next item) are considered.
+fn incompatibility_bound(instance: &SPInstance) -> f32 {
+    let height = instance.base_strip.fixed_height;
+
+    // Radii of all items with their quantity, in decreasing order
+    let radii = instance.items.iter()
+        .filter(|(_, qty)| *qty > 0)
+        .map(|(item, qty)| (item.shape_cd.poi.radius, *qty))
+        .sorted_by_key(|(r, _)| Reverse(OrderedFloat(*r)))
+        .collect_vec();
+
+    let min_dx = |r1: f32, r2: f32| -> Option<f32> {
+        let max_dy = height - r1 - r2;
+        let min_dist = r1 + r2;
+        (max_dy < min_dist).then(|| (min_dist.powi(2) - max_dy.max(0.0).powi(2)).sqrt())
+    };
+
+    let mut best = 0.0;
+    for (i, &(r, qty)) in radii.iter().enumerate() {
+        let larger = &radii[..i];
+        if let Some(&(r_prev, _)) = larger.last() && min_dx(r_prev, r).is_none() {
+            // Compatible with the larger circles, and so are all smaller ones
+            break;
+        }
+        // All copies if they are incompatible with each other, otherwise a single one
+        let self_dx = min_dx(r, r);
+        let n_copies = if self_dx.is_some() { qty } else { 1 };
+        let n_larger = larger.iter().map(|(_, q)| *q).sum::<usize>();
+        if n_larger + n_copies >= 2 {
+            // Star from one copy to all other circles in the set
+            let star_weight = larger.iter().map(|&(r_l, q_l)| q_l as f32 * min_dx(r_l, r).unwrap()).sum::<f32>()
+                + (n_copies - 1) as f32 * self_dx.unwrap_or(0.0);
+            let second_smallest = if n_copies >= 2 { r } else { larger.last().unwrap().0 };
+            best = f32::max(best, star_weight + r + second_smallest);
+        }
+        if self_dx.is_none() {
+            break;
+        }
+    }
+    best
+}
+
+/// Minimum width of a convex polygon over all directions, attained perpendicular to one of its edges
+fn min_caliper_width(hull: &[Point]) -> f32 {
+    (0..hull.len())
+        .map(|i| {
+            let (Point(x1, y1), Point(x2, y2)) = (hull[i], hull[(i + 1) % hull.len()]);
+            let len = ((x2 - x1).powi(2) + (y2 - y1).powi(2)).sqrt();
+            hull.iter()
+                .map(|Point(x, y)| ((x2 - x1) * (y1 - y) - (x1 - x) * (y2 - y1)).abs() / len)
+                .fold(0.0, f32::max)
+        })
+        .filter(|w| w.is_finite())
+        .fold(f32::INFINITY, f32::min)
+}
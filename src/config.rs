@@ -1,5 +1,7 @@
+use crate::consts::SPARSE_TRACKER_THRESHOLD;
 use crate::optimizer::separator::SeparatorConfig;
-use crate::sample::search::SampleConfig;
+use crate::sample::refine::RefinerType;
+use crate::sample::search::{ContainerSampler, SampleConfig};
 use jagua_rs::collision_detection::CDEConfig;
 use jagua_rs::geometry::fail_fast::SPSurrogateConfig;
 use std::time::Duration;
@@ -33,7 +35,68 @@ pub struct ExplorationConfig {
     pub max_conseq_failed_attempts: Option<usize>,
     pub solution_pool_distribution_stddev: f32,
     pub separator_config: SeparatorConfig,
-    pub large_item_ch_area_cutoff_percentile: f32
+    pub large_item_ch_area_cutoff_percentile: f32,
+    /// Configuration of how infeasible solutions selected from the pool are disrupted
+    pub disruption: DisruptionConfig,
+    /// If provided, the phase is limited by this amount of work instead of `time_limit`.
+    /// See [`WorkBudget`] for more details.
+    pub work_budget: Option<WorkBudget>,
+    /// Runs the exploration phase as multiple parallel islands if provided.
+    /// See [`IslandConfig`] for more details.
+    pub island_config: Option<IslandConfig>,
+    /// Shrinks the height of the strip as well (minimizing its area) if provided.
+    /// See [`OpenDimension`] for more details.
+    pub open_dimension: Option<OpenDimension>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DisruptionConfig {
+    /// Strategies that can be used to disrupt infeasible solutions selected from the pool
+    pub strategies: &'static [DisruptionStrategy],
+    /// How to select among the strategies
+    pub selection: DisruptorSelection,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisruptionStrategy {
+    /// Swap two large items (and the items they practically contain)
+    LargeItemSwap,
+    /// Recombine the selected solution with a second one from the pool
+    Crossover,
+    /// Re-place all items within a random vertical band of the strip
+    RuinRecreateBand,
+    /// Rotate a cluster of neighbouring items as a whole
+    RotateCluster,
+    /// Reflect the positions of all items within a random vertical band of the strip (the items keep their orientation)
+    ReflectPositions,
+    /// Re-place a few random items at random positions
+    RandomReplace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisruptorSelection {
+    /// Strategies are selected uniformly at random
+    Random,
+    /// Strategies are selected proportionally to how often they led to a feasible solution in the past
+    Adaptive,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IslandConfig {
+    /// Number of independent explorations running in parallel (each with its own separator and workers)
+    pub n_islands: usize,
+    /// Time between two consecutive migrations.
+    /// Ignored if the exploration is limited by a work budget, see [`crate::consts::WORK_BUDGET_EPOCH_RATIO`] instead.
+    pub migration_interval: Duration,
+    pub topology: MigrationTopology,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MigrationTopology {
+    /// Every island receives the best feasible solution of its predecessor in a ring
+    Ring,
+    /// Every island receives the best feasible solution across all islands
+    FullyConnected,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -42,6 +105,49 @@ pub struct CompressionConfig {
     pub time_limit: Duration,
     pub shrink_decay: ShrinkDecayStrategy,
     pub separator_config: SeparatorConfig,
+    /// If provided, the phase is limited by this amount of work instead of `time_limit`.
+    /// See [`WorkBudget`] for more details.
+    pub work_budget: Option<WorkBudget>,
+    /// Slide all items of the final solution left and down as far as possible (see [`crate::optimizer::compact::compaction_phase`])
+    pub post_compaction: bool,
+    /// Repair the final solution to be separable by edge-to-edge guillotine cuts (see [`crate::guillotine::guillotine_repair`]).
+    /// Only enforced after the optimization: the separator does not penalize layouts that cannot be cut, and if the repair fails,
+    /// the final solution is not separable (see [`crate::api::SparrowResult::guillotine_feasible`]).
+    pub guillotine: bool,
+    /// Shrinks the height of the strip as well (minimizing its area) if provided.
+    /// See [`OpenDimension`] for more details.
+    pub open_dimension: Option<OpenDimension>,
+}
+
+/// Amount of work after which a phase of the optimization terminates.
+/// Unlike time limits, work budgets do not depend on the speed of the machine:
+/// combined with a fixed seed, they make the result of the optimization reproducible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkBudget {
+    /// Number of separator iterations (every item in collision gets the chance to move once)
+    Iterations(usize),
+    /// Number of evaluated samples
+    Evaluations(usize),
+}
+
+impl WorkBudget {
+    /// Returns a fraction of this budget (of at least one unit of work)
+    pub fn scale(&self, ratio: f32) -> Self {
+        let scale = |n: usize| ((n as f32 * ratio) as usize).max(1);
+        match *self {
+            WorkBudget::Iterations(n) => WorkBudget::Iterations(scale(n)),
+            WorkBudget::Evaluations(n) => WorkBudget::Evaluations(scale(n)),
+        }
+    }
+}
+
+/// Open-dimension mode: both the width and the height of the strip are minimized, instead of only the width.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OpenDimension {
+    /// Alternately shrinks the width and the height of the strip, starting from the height of the instance
+    Alternating,
+    /// Keeps the ratio between the width and the height of the strip fixed (`1.0` for a square), shrinking both at once
+    AspectRatio(f32),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -50,6 +156,13 @@ pub enum ShrinkDecayStrategy {
     TimeBased,
     /// The shrink ratio decays by a fixed ratio every time it fails to compress into a feasible solution
     FailureBased(f32),
+    /// The shrink ratio decays linearly with time (as [`ShrinkDecayStrategy::TimeBased`]), but failed compressions can be accepted
+    /// as the starting point of the next attempt, according to a simulated annealing criterion.
+    /// The temperature starts at `init_temp` and decays linearly to zero over the time limit.
+    /// The temperature is unitless: the loss of a failed attempt is divided by the loss of the first failed attempt,
+    /// so a temperature of `1.0` accepts an attempt as bad as the first one with a probability of `1/e`.
+    /// An accepted solution that fails to compress again is abandoned for the best solution.
+    Annealing { init_temp: f32 },
 }
 
 pub const DEFAULT_SPARROW_CONFIG: SparrowConfig = SparrowConfig {
@@ -68,9 +181,20 @@ pub const DEFAULT_SPARROW_CONFIG: SparrowConfig = SparrowConfig {
                 n_container_samples: 50,
                 n_focussed_samples: 25,
                 n_coord_descents: 3,
+                adaptive: false,
+                container_sampler: ContainerSampler::Uniform,
+                refiner: RefinerType::CoordDescent,
             },
+            sparse_tracker_threshold: SPARSE_TRACKER_THRESHOLD,
+        },
+        large_item_ch_area_cutoff_percentile: 0.75,
+        disruption: DisruptionConfig {
+            strategies: &[DisruptionStrategy::LargeItemSwap],
+            selection: DisruptorSelection::Random,
         },
-        large_item_ch_area_cutoff_percentile: 0.75
+        work_budget: None,
+        island_config: None,
+        open_dimension: None,
     },
     cmpr_cfg: CompressionConfig {
         shrink_range: (0.0005, 0.00001),
@@ -85,8 +209,16 @@ pub const DEFAULT_SPARROW_CONFIG: SparrowConfig = SparrowConfig {
                 n_container_samples: 50,
                 n_focussed_samples: 25,
                 n_coord_descents: 3,
+                adaptive: false,
+                container_sampler: ContainerSampler::Uniform,
+                refiner: RefinerType::CoordDescent,
             },
+            sparse_tracker_threshold: SPARSE_TRACKER_THRESHOLD,
         },
+        work_budget: None,
+        post_compaction: true,
+        guillotine: false,
+        open_dimension: None,
     },
     cde_config: CDEConfig {
         quadtree_depth: 4,
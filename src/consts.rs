@@ -1,11 +1,16 @@
-use crate::sample::search::SampleConfig;
+use crate::sample::refine::RefinerType;
+use crate::sample::search::{ContainerSampler, SampleConfig};
 use jagua_rs::io::svg::{SvgDrawOptions, SvgLayoutTheme};
+use std::time::Duration;
 
 pub const GLS_WEIGHT_MAX_INC_RATIO: f32 = 2.0;
 pub const GLS_WEIGHT_MIN_INC_RATIO: f32 = 1.2;
 pub const GLS_WEIGHT_DECAY: f32 = 0.95;
 pub const OVERLAP_PROXY_EPSILON_DIAM_RATIO: f32 = 0.01;
 
+/// Default number of items above which the collision tracker stores only the non-neutral pairs of items (instead of all of them)
+pub const SPARSE_TRACKER_THRESHOLD: usize = 1000;
+
 
 /// Coordinate descent step multiplier on success
 pub const CD_STEP_SUCCESS: f32 = 1.1;
@@ -25,9 +30,45 @@ pub const SND_REFINE_CD_TL_RATIOS: (f32, f32) = (0.01, 0.001);
 /// Step sizes for rotation in the second (final) refinement
 pub const SND_REFINE_CD_R_STEPS: (f32, f32) = (f32::to_radians(0.5), f32::to_radians(0.05));
 
+/// Maximum number of iterations of the Nelder-Mead refiner
+pub const NM_MAX_ITERATIONS: usize = 100;
+
 /// If two samples are closer than this ratio of the item's min dimension, they are considered duplicates
 pub const UNIQUE_SAMPLE_THRESHOLD: f32 = 0.05;
 
+/// Bounds of the factor by which adaptive sampling scales the number of container and focussed samples of an item
+pub const ADAPTIVE_SAMPLE_FACTOR_RANGE: (f32, f32) = (0.5, 2.0);
+
+/// Smoothing factor of the exponential moving average of an item's move success, used by adaptive sampling
+pub const ADAPTIVE_SUCCESS_EMA_ALPHA: f32 = 0.2;
+
+/// Number of longest convex hull edges of an item with continuous rotation to align with the axes of the strip when sampling
+pub const ROT_N_HULL_EDGES: usize = 4;
+
+/// Number of surrounding items an item with continuous rotation is aligned with when sampling around its current placement
+pub const ROT_N_NEIGHBOURS: usize = 4;
+
+/// Fraction of samples for items with continuous rotation which use a uniformly random rotation
+pub const ROT_UNIFORM_SAMPLE_RATIO: f32 = 0.25;
+
+/// Fraction of samples for items with continuous rotation which use the current rotation of the item with some jitter
+pub const ROT_JITTER_SAMPLE_RATIO: f32 = 0.25;
+
+/// Number of evenly spaced rotations tried for an item with continuous rotation when none of its hull-aligned rotations fit the sample bounding box
+pub const ROT_FALLBACK_N_ROTATIONS: usize = 360;
+
+/// Number of attempts to sample a placement whose bounding box is not centered in a zone the item should not overlap
+pub const ZONE_SAMPLE_ATTEMPTS: usize = 8;
+
+/// Maximum jitter (in radians) applied to the current rotation of an item with continuous rotation when sampling
+pub const ROT_JITTER: f32 = f32::to_radians(10.0);
+
+/// Number of rows of the occupancy grid used by the gap-aware sampler (columns are derived from the strip width)
+pub const GAP_GRID_RESOLUTION: usize = 32;
+
+/// Number of uniform candidates the gap-aware sampler draws per sample, the one in the emptiest region is kept
+pub const GAP_SAMPLER_N_CANDIDATES: usize = 8;
+
 pub const DEFAULT_EXPLORE_TIME_RATIO: f32 = 0.8;
 pub const DEFAULT_COMPRESS_TIME_RATIO: f32 = 0.2;
 
@@ -35,6 +76,58 @@ pub const DEFAULT_MAX_CONSEQ_FAILS_EXPL: usize = 10;
 
 pub const DEFAULT_FAIL_DECAY_RATIO_CMPR: f32 = 0.9;
 
+/// Width of the band disrupted by the ruin-and-recreate and reflection strategies, relative to the strip width
+pub const DSRP_BAND_WIDTH_RATIO: f32 = 0.1;
+
+/// Number of samples evaluated per item when recreating a ruined band
+pub const DSRP_RECREATE_N_SAMPLES: usize = 50;
+
+/// Radius of the cluster rotated as a whole, relative to the seed item's diameter
+pub const DSRP_CLUSTER_RADIUS_RATIO: f32 = 1.0;
+
+/// Maximum deviation (in radians) between a rotation and an allowed rotation of an item, for the item to be rotated along with its cluster
+pub const DSRP_CLUSTER_ROTATION_TOLERANCE: f32 = f32::to_radians(0.1);
+
+/// Number of items re-placed at random positions
+pub const DSRP_N_REPLACED_ITEMS: usize = 3;
+
+/// Minimum ratio between the areas of a leftover item of parent A and an unused placement of parent B (of another item type),
+/// for the item to take over the placement during crossover
+pub const XOVR_SIMILAR_AREA_RATIO: f32 = 0.8;
+
+/// Reaction factor of the adaptive disruption strategy selection: how strongly new outcomes affect the weights
+pub const ADAPTIVE_DSRP_REACTION: f32 = 0.2;
+
+/// Minimum weight of a disruption strategy in adaptive selection, so that no strategy is ever abandoned
+pub const ADAPTIVE_DSRP_MIN_WEIGHT: f32 = 0.1;
+
+/// Time between two consecutive migrations in island mode
+pub const DEFAULT_ISLAND_MIGRATION_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Fraction of the work budget of the exploration phase spent (by all islands together) between two migrations, if limited by a work budget
+pub const WORK_BUDGET_EPOCH_RATIO: f32 = 0.05;
+
+/// Fraction of the time limit (or work budget) spent screening all candidate strip heights, before the dominated ones are dropped
+pub const HEIGHT_SCREENING_RATIO: f32 = 0.2;
+
+/// Candidate strip heights costing more than this ratio above the cheapest one after screening are considered dominated
+pub const HEIGHT_DOMINANCE_MARGIN: f32 = 0.05;
+
+/// Expected density of the initial solution, used to choose its height when the aspect ratio of the strip is fixed
+pub const OPEN_DIM_INITIAL_DENSITY: f32 = 0.6;
+
+/// Maximum number of passes over all items during the post-processing compaction
+pub const COMPACT_MAX_PASSES: usize = 10;
+
+/// Ratio of the item's min dimension to be used as initial and limit step size when sliding items during compaction
+pub const COMPACT_STEP_RATIOS: (f32, f32) = (0.25, 0.001);
+
+/// Maximum overlap (relative to the height of the strip) between the bounding boxes of items on either side of a guillotine cut
+pub const GUILLOTINE_CUT_TOLERANCE: f32 = 1e-5;
+
+/// Relative tolerance within which a strip width is considered equal to its lower bound
+pub const LB_REACHED_TOLERANCE: f32 = 1e-4;
+
 pub const LOG_LEVEL_FILTER_RELEASE: log::LevelFilter = log::LevelFilter::Info;
 
 pub const LOG_LEVEL_FILTER_DEBUG: log::LevelFilter = log::LevelFilter::Debug;
@@ -52,4 +145,18 @@ pub const LBF_SAMPLE_CONFIG: SampleConfig = SampleConfig {
     n_container_samples: 1000,
     n_focussed_samples: 0,
     n_coord_descents: 3,
+    adaptive: false,
+    container_sampler: ContainerSampler::Uniform,
+    refiner: RefinerType::CoordDescent,
+};
+
+/// Sample configuration used to insert optional copies of items into the free space of the final solution.
+/// Gap-aware, as the free space is scattered over a dense layout.
+pub const OPTIONAL_ITEM_SAMPLE_CONFIG: SampleConfig = SampleConfig {
+    n_container_samples: 2000,
+    n_focussed_samples: 0,
+    n_coord_descents: 3,
+    adaptive: false,
+    container_sampler: ContainerSampler::GapAware,
+    refiner: RefinerType::CoordDescent,
 };
\ No newline at end of file
@@ -1,5 +1,7 @@
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
-use jagua_rs::collision_detection::hazards::filter::NoFilter;
+use crate::groups::GroupWindow;
+use crate::zones::allowed_zones;
+use jagua_rs::collision_detection::hazards::filter::HazKeyFilter;
 use jagua_rs::entities::Item;
 use jagua_rs::entities::Layout;
 use jagua_rs::geometry::geo_traits::TransformableFrom;
@@ -16,7 +18,13 @@ pub struct LBFEvaluator<'a> {
     layout: &'a Layout,
     item: &'a Item,
     shape_buff: SPolygon,
-    n_evals: usize
+    n_evals: usize,
+    /// If provided, placements have to respect the maximum span of the item's group
+    group_window: Option<GroupWindow>,
+    /// Placements have to lie entirely to the right of this position
+    min_x: f32,
+    /// Zones of the container the item is allowed to overlap
+    zone_filter: HazKeyFilter,
 }
 
 impl<'a> LBFEvaluator<'a> {
@@ -25,9 +33,28 @@ impl<'a> LBFEvaluator<'a> {
             layout,
             item,
             shape_buff: item.shape_cd.as_ref().clone(),
-            n_evals: 0
+            n_evals: 0,
+            group_window: None,
+            min_x: f32::NEG_INFINITY,
+            zone_filter: allowed_zones(layout, item.min_quality),
         }
     }
+
+    pub fn with_group_window(mut self, group_window: Option<GroupWindow>) -> Self {
+        self.group_window = group_window;
+        self
+    }
+
+    pub fn with_min_x(mut self, min_x: f32) -> Self {
+        self.min_x = min_x;
+        self
+    }
+
+    /// Whether the (transformed) shape buffer lies right of `min_x` and within the span of its group
+    fn within_bounds(&self) -> bool {
+        let bbox = self.shape_buff.bbox;
+        bbox.x_min >= self.min_x && self.group_window.is_none_or(|w| w.excess(bbox.x_min, bbox.x_max) == 0.0)
+    }
 }
 
 impl<'a> SampleEvaluator for LBFEvaluator<'a> {
@@ -35,11 +62,14 @@ impl<'a> SampleEvaluator for LBFEvaluator<'a> {
         self.n_evals += 1;
         let cde = self.layout.cde();
         let transf = dt.into();
-        match cde.detect_surrogate_collision(self.item.shape_cd.surrogate(), &transf, &NoFilter) {
+        match cde.detect_surrogate_collision(self.item.shape_cd.surrogate(), &transf, &self.zone_filter) {
             true => SampleEval::Invalid, // Surrogate collides with something
             false => {
                 self.shape_buff.transform_from(&self.item.shape_cd, &transf);
-                match cde.detect_poly_collision(&self.shape_buff, &NoFilter) {
+                if !self.within_bounds() {
+                    return SampleEval::Invalid;
+                }
+                match cde.detect_poly_collision(&self.shape_buff, &self.zone_filter) {
                     true => SampleEval::Invalid, // Exact shape collides with something
                     false => {
                         // No collisions
@@ -41,4 +41,10 @@ pub trait SampleEvaluator {
     fn evaluate_sample(&mut self, dt: DTransformation, upper_bound: Option<SampleEval>) -> SampleEval;
 
     fn n_evals(&self) -> usize;
+
+    /// Direction `[x, y, rotation]` in which the item is pushed by the hazards it collides with at `dt`.
+    /// Returns `None` if there are no collisions, or if the evaluator does not support it.
+    fn overlap_direction(&mut self, _dt: DTransformation) -> Option<[f32; 3]> {
+        None
+    }
 }
\ No newline at end of file
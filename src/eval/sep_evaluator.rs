@@ -1,11 +1,15 @@
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
 use crate::eval::specialized_jaguars_pipeline::{collect_poly_collisions_in_detector_custom, SpecializedHazardCollector};
 use crate::quantify::tracker::CollisionTracker;
+use crate::groups::GroupWindow;
+use crate::quantify::overlap_proxy::overlap_push;
+use crate::quantify::quantify_group_span_violation;
 use jagua_rs::collision_detection::hazards::collector::HazardCollector;
+use jagua_rs::collision_detection::hazards::HazardEntity;
 use jagua_rs::entities::Item;
 use jagua_rs::entities::Layout;
 use jagua_rs::entities::PItemKey;
-use jagua_rs::geometry::primitives::SPolygon;
+use jagua_rs::geometry::primitives::{Point, SPolygon};
 use jagua_rs::geometry::DTransformation;
 
 pub struct SeparationEvaluator<'a> {
@@ -14,6 +18,8 @@ pub struct SeparationEvaluator<'a> {
     collector: SpecializedHazardCollector<'a>,
     shape_buff: SPolygon,
     n_evals: usize,
+    /// Extent of the other items of the item's group (if any), with the weight and shape penalty of the group span violation
+    group: Option<(GroupWindow, f32, f32)>,
 }
 
 impl<'a> SeparationEvaluator<'a> {
@@ -24,6 +30,8 @@ impl<'a> SeparationEvaluator<'a> {
         ct: &'a CollisionTracker,
     ) -> Self {
         let collector = SpecializedHazardCollector::new(layout, ct, current_pk);
+        let group = ct.get_group_window(current_pk)
+            .map(|window| (window, ct.get_group_weight(current_pk), ct.get_group_penalty(current_pk)));
 
         Self {
             layout,
@@ -31,6 +39,22 @@ impl<'a> SeparationEvaluator<'a> {
             collector,
             shape_buff: item.shape_cd.as_ref().clone(),
             n_evals: 0,
+            group,
+        }
+    }
+
+    /// Weighted loss of the group span violation of the (transformed) shape buffer
+    fn group_loss(&self) -> f32 {
+        match self.group {
+            Some((window, weight, penalty)) => {
+                let bbox = self.shape_buff.bbox;
+                let excess = window.excess(bbox.x_min, bbox.x_max);
+                match excess > 0.0 {
+                    true => weight * quantify_group_span_violation(excess, bbox.height(), penalty),
+                    false => 0.0,
+                }
+            }
+            None => 0.0,
         }
     }
 }
@@ -60,13 +84,18 @@ impl<'a> SampleEvaluator for SeparationEvaluator<'a> {
             // Note that we might have exited before detecting/quantifying all collisions.
             // However, since we can asure that this sample will always be rejected, we don't need to spend any more time on it and just return `Invalid`.
             SampleEval::Invalid
-        } else if self.collector.is_empty() {
-            // No collisions detected, return clear
-            SampleEval::Clear { loss: 0.0 }
         } else {
-            // Some collisions detected but withing the upper bound, return collision with total loss
-            SampleEval::Collision {
-                loss: self.collector.loss(&self.shape_buff),
+            let group_loss = self.group_loss();
+            if self.collector.is_empty() && group_loss == 0.0 {
+                // No collisions detected, return clear
+                SampleEval::Clear { loss: 0.0 }
+            } else {
+                // Some collisions detected, return collision with total loss (including the group span violation) if within the upper bound
+                let loss = self.collector.loss(&self.shape_buff) + group_loss;
+                match loss > loss_bound {
+                    true => SampleEval::Invalid,
+                    false => SampleEval::Collision { loss },
+                }
             }
         }
     }
@@ -74,5 +103,46 @@ impl<'a> SampleEvaluator for SeparationEvaluator<'a> {
     fn n_evals(&self) -> usize {
         self.n_evals
     }
+
+    fn overlap_direction(&mut self, dt: DTransformation) -> Option<[f32; 3]> {
+        // Detect all collisions at the transformation (populates the collector and the shape buffer)
+        if !matches!(self.evaluate_sample(dt, None), SampleEval::Collision { .. }) {
+            return None;
+        }
+        let shape = &self.shape_buff;
+        let pivot = Point::from(dt.translation());
+        let ct = self.collector.ct;
+        let current_pk = self.collector.current_pk;
+
+        let mut direction = [0.0; 3];
+        for (hkey, entity) in self.collector.iter() {
+            let (push, weight) = match entity {
+                HazardEntity::PlacedItem { pk, .. } => {
+                    let other = &self.layout.placed_items[*pk].shape;
+                    (overlap_push(shape.surrogate(), other.surrogate(), pivot), ct.get_pair_weight(current_pk, *pk))
+                }
+                HazardEntity::Exterior => {
+                    // Push the item back into the container, proportional to how far it sticks out
+                    let (bbox, c_bbox) = (shape.bbox, self.layout.container.outer_cd.bbox);
+                    let dx = f32::max(0.0, c_bbox.x_min - bbox.x_min) - f32::max(0.0, bbox.x_max - c_bbox.x_max);
+                    let dy = f32::max(0.0, c_bbox.y_min - bbox.y_min) - f32::max(0.0, bbox.y_max - c_bbox.y_max);
+                    ([dx * shape.diameter, dy * shape.diameter, 0.0], ct.get_container_weight(current_pk))
+                }
+                HazardEntity::InferiorQualityZone { .. } | HazardEntity::Hole { .. } => {
+                    // Push the item out of the forbidden zone, as if it were an item
+                    let zone = &self.layout.cde().hazards_map[hkey].shape;
+                    (overlap_push(shape.surrogate(), zone.surrogate(), pivot), ct.get_container_weight(current_pk))
+                }
+            };
+            for d in 0..3 {
+                direction[d] += push[d] * weight;
+            }
+        }
+        if let Some((window, weight, _)) = self.group {
+            // Push the item back toward the other items of its group
+            direction[0] += window.push(shape.bbox.x_min, shape.bbox.x_max) * shape.diameter * weight;
+        }
+        Some(direction)
+    }
 }
 
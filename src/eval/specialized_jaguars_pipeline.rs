@@ -1,15 +1,13 @@
-use crate::quantify::quantify_collision_poly_container;
-#[cfg(not(feature = "simd"))]
-use crate::quantify::quantify_collision_poly_poly;
-#[cfg(feature = "simd")]
+use crate::quantify::{quantify_collision_poly_container, quantify_collision_poly_zone};
 use crate::quantify::simd::circles_soa::CirclesSoA;
-#[cfg(feature = "simd")]
 use crate::quantify::simd::quantify_collision_poly_poly_simd;
 use crate::quantify::tracker::CollisionTracker;
 use crate::util::assertions;
 use crate::util::bit_reversal_iterator::BitReversalIterator;
+use crate::zones::allowed_zones;
 use float_cmp::approx_eq;
 use jagua_rs::collision_detection::hazards::collector::HazardCollector;
+use jagua_rs::collision_detection::hazards::filter::HazKeyFilter;
 use jagua_rs::collision_detection::hazards::{HazKey, HazardEntity};
 use jagua_rs::collision_detection::quadtree::QTHazPresence;
 use jagua_rs::collision_detection::CDEngine;
@@ -35,7 +33,6 @@ pub fn collect_poly_collisions_in_detector_custom(
     // transform the shape buffer to the new position
     let shape = shape_buffer.transform_from(reference_shape, &t);
 
-    #[cfg(feature = "simd")]
     collector.poles_soa.load(&shape.surrogate().poles);
     
 
@@ -96,11 +93,12 @@ pub struct SpecializedHazardCollector<'a> {
     pub ct: &'a CollisionTracker,
     pub current_pk: PItemKey,
     pub current_haz_key: HazKey,
+    /// Zones of the container the current item is allowed to overlap, ignored like the item itself
+    pub allowed_zones: HazKeyFilter,
     pub detected: SecondaryMap<HazKey, (HazardEntity, usize)>,
     pub idx_counter: usize,
     pub loss_cache: (usize, f32),
     pub loss_bound: f32,
-    #[cfg(feature = "simd")]
     pub poles_soa: CirclesSoA,
 }
 
@@ -116,11 +114,11 @@ impl<'a> SpecializedHazardCollector<'a> {
             ct,
             current_pk,
             current_haz_key,
+            allowed_zones: allowed_zones(layout, ct.get_min_quality(current_pk)),
             detected: SecondaryMap::with_capacity(layout.placed_items.len() + 1),
             idx_counter: 0,
             loss_cache: (0, 0.0),
             loss_bound: f32::INFINITY,
-            #[cfg(feature = "simd")]
             poles_soa: CirclesSoA::new(),
         }
     }
@@ -144,24 +142,21 @@ impl<'a> SpecializedHazardCollector<'a> {
         let (cache_idx, cached_loss) = self.loss_cache;
         if cache_idx < self.idx_counter {
             // additional hazards were detected, update the cache
-            let extra_loss: f32 = self.iter_with_index()
-                .filter(|(_, idx)| *idx >= cache_idx)
-                .map(|(h, _)| self.calc_weighted_loss(h, shape))
+            let extra_loss: f32 = self.detected.iter()
+                .filter(|(_, (_, idx))| *idx >= cache_idx)
+                .map(|(hkey, (h, _))| self.calc_weighted_loss(hkey, h, shape))
                 .sum();
             self.loss_cache = (self.idx_counter, cached_loss + extra_loss);
         }
-        debug_assert!(approx_eq!(f32, self.loss_cache.1, self.iter().map(|(_, he)| self.calc_weighted_loss(he, shape)).sum()));
+        debug_assert!(approx_eq!(f32, self.loss_cache.1, self.iter().map(|(hkey, he)| self.calc_weighted_loss(hkey, he, shape)).sum()));
         self.loss_cache.1
     }
 
-    fn calc_weighted_loss(&self, haz: &HazardEntity, shape: &SPolygon) -> f32 {
+    fn calc_weighted_loss(&self, hkey: HazKey, haz: &HazardEntity, shape: &SPolygon) -> f32 {
         match haz {
             HazardEntity::PlacedItem { pk: other_pk, .. } => {
                 let other_shape = &self.layout.placed_items[*other_pk].shape;
 
-                #[cfg(not(feature = "simd"))]
-                let loss = quantify_collision_poly_poly(other_shape, shape);
-                #[cfg(feature = "simd")]
                 let loss = quantify_collision_poly_poly_simd(other_shape, shape, &self.poles_soa);
 
                 let weight = self.ct.get_pair_weight(self.current_pk, *other_pk);
@@ -172,14 +167,19 @@ impl<'a> SpecializedHazardCollector<'a> {
                 let weight = self.ct.get_container_weight(self.current_pk);
                 loss * weight
             }
-            _ => unimplemented!("unsupported hazard entity"),
+            HazardEntity::InferiorQualityZone { .. } | HazardEntity::Hole { .. } => {
+                // Only forbidden zones are detected, see `allowed_zones`
+                let loss = quantify_collision_poly_zone(shape, &self.layout.cde().hazards_map[hkey].shape);
+                let weight = self.ct.get_container_weight(self.current_pk);
+                loss * weight
+            }
         }
     }
 }
 
 impl<'a> HazardCollector for SpecializedHazardCollector<'a> {
     fn contains_key(&self, hkey: HazKey) -> bool {
-        self.detected.contains_key(hkey) || hkey == self.current_haz_key
+        self.detected.contains_key(hkey) || hkey == self.current_haz_key || self.allowed_zones.0.contains_key(hkey)
     }
 
     fn insert(&mut self, hkey: HazKey, entity: HazardEntity) {
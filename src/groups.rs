@@ -0,0 +1,109 @@
+use anyhow::{ensure, Result};
+use jagua_rs::entities::{Layout, PItemKey, PlacedItem};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Set of items whose placements must all lie within a maximum horizontal span of the strip,
+/// e.g. all pieces of one size of a garment kit, so they can be bundled after cutting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemGroup {
+    pub item_ids: Vec<usize>,
+    /// Maximum distance between the leftmost and the rightmost point of all placed copies of the group's items
+    pub max_x_span: f32,
+}
+
+/// All item groups of an instance, with a lookup of the group of every item.
+/// Cheap to clone, as it is shared by all collision trackers.
+#[derive(Debug, Clone, Default)]
+pub struct ItemGroups(Arc<ItemGroupsInner>);
+
+#[derive(Debug, Default)]
+struct ItemGroupsInner {
+    groups: Vec<ItemGroup>,
+    /// Group of every item (indexed by item id)
+    item_group: Vec<Option<usize>>,
+}
+
+impl ItemGroups {
+    /// Every item can be part of at most one group
+    pub fn new(groups: Vec<ItemGroup>, n_items: usize) -> Result<Self> {
+        let mut item_group = vec![None; n_items];
+        for (g, group) in groups.iter().enumerate() {
+            ensure!(group.max_x_span > 0.0, "maximum x-span of item group {g} should be positive");
+            for &id in &group.item_ids {
+                ensure!(id < n_items, "item group {g} contains unknown item {id}");
+                ensure!(item_group[id].is_none(), "item {id} is part of multiple item groups");
+                item_group[id] = Some(g);
+            }
+        }
+        Ok(Self(Arc::new(ItemGroupsInner { groups, item_group })))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.groups.is_empty()
+    }
+
+    pub fn groups(&self) -> &[ItemGroup] {
+        &self.0.groups
+    }
+
+    pub fn group_of(&self, item_id: usize) -> Option<usize> {
+        self.0.item_group.get(item_id).copied().flatten()
+    }
+
+    /// Horizontal extent of the placed items of the item's group, excluding `exclude` (if any).
+    /// `None` if the item is not part of a group.
+    pub fn window(&self, l: &Layout, item_id: usize, exclude: Option<PItemKey>) -> Option<GroupWindow> {
+        let g = self.group_of(item_id)?;
+        let window = l.placed_items.iter()
+            .filter(|(pk, pi)| Some(*pk) != exclude && self.group_of(pi.item_id) == Some(g))
+            .fold(GroupWindow::empty(self.0.groups[g].max_x_span), |w, (_, pi)| w.extend(pi.shape.bbox.x_min, pi.shape.bbox.x_max));
+        Some(window)
+    }
+
+    /// Horizontal span of all placed items of every group
+    pub fn spans<'a>(&self, placed_items: impl IntoIterator<Item = &'a PlacedItem>) -> Vec<f32> {
+        let mut windows = self.groups().iter().map(|group| GroupWindow::empty(group.max_x_span)).collect::<Vec<_>>();
+        for pi in placed_items {
+            if let Some(g) = self.group_of(pi.item_id) {
+                windows[g] = windows[g].extend(pi.shape.bbox.x_min, pi.shape.bbox.x_max);
+            }
+        }
+        windows.iter().map(|w| w.span()).collect()
+    }
+}
+
+/// Horizontal extent of (a subset of) the placed items of a group
+#[derive(Debug, Clone, Copy)]
+pub struct GroupWindow {
+    pub x_min: f32,
+    pub x_max: f32,
+    pub max_x_span: f32,
+}
+
+impl GroupWindow {
+    pub fn empty(max_x_span: f32) -> Self {
+        Self { x_min: f32::INFINITY, x_max: f32::NEG_INFINITY, max_x_span }
+    }
+
+    pub fn extend(self, x_min: f32, x_max: f32) -> Self {
+        Self { x_min: self.x_min.min(x_min), x_max: self.x_max.max(x_max), ..self }
+    }
+
+    pub fn span(&self) -> f32 {
+        (self.x_max - self.x_min).max(0.0)
+    }
+
+    /// Distance by which the group would exceed its maximum span, if an item spanning `x_min..x_max` were added
+    pub fn excess(&self, x_min: f32, x_max: f32) -> f32 {
+        (self.extend(x_min, x_max).span() - self.max_x_span).max(0.0)
+    }
+
+    /// Horizontal translation that moves an item spanning `x_min..x_max` back toward the window, zero if it does not exceed the span
+    pub fn push(&self, x_min: f32, x_max: f32) -> f32 {
+        if self.x_min > self.x_max {
+            return 0.0;
+        }
+        (self.x_max - self.max_x_span - x_min).max(0.0) - (x_max - self.x_min - self.max_x_span).max(0.0)
+    }
+}
@@ -0,0 +1,211 @@
+use crate::consts::GUILLOTINE_CUT_TOLERANCE;
+use crate::optimizer::separator::change_strip_width;
+use crate::zones::restore_problem;
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+use jagua_rs::entities::Item;
+use jagua_rs::geometry::primitives::{Rect, SPolygon};
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPSolution};
+use log::info;
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Orientation of a guillotine cut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CutOrientation {
+    /// Cuts parallel to the y-axis, dividing a region into parts from left to right
+    Vertical,
+    /// Cuts parallel to the x-axis, dividing a region into parts from bottom to top
+    Horizontal,
+}
+
+impl CutOrientation {
+    /// Extent of the rectangle perpendicular to the cuts
+    fn range(&self, r: &Rect) -> (f32, f32) {
+        match self {
+            CutOrientation::Vertical => (r.x_min, r.x_max),
+            CutOrientation::Horizontal => (r.y_min, r.y_max),
+        }
+    }
+}
+
+/// Guillotine cut tree of a layout, based on the bounding boxes of the placed items.
+/// Every node is a region of the strip, which either contains a single item or is divided by parallel edge-to-edge cuts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CutTree {
+    /// Region containing a single item, `index` is its position in the placed items of the solution
+    Item { index: usize, item_id: usize },
+    /// Region divided into `parts` by cuts at `positions` (ascending, one fewer than the parts)
+    Cuts { orientation: CutOrientation, positions: Vec<f32>, parts: Vec<CutTree> },
+}
+
+/// Copy of the instance in which every item collides as its bounding rectangle, in which guillotine layouts are far more likely.
+/// Only the shapes used for collision detection change, the original shapes (and thus the exported placements) are kept.
+pub fn bounding_rect_instance(instance: &SPInstance) -> Result<SPInstance> {
+    let items = instance.items.iter()
+        .map(|(item, qty)| {
+            let mut rect = SPolygon::from(item.shape_cd.bbox);
+            rect.generate_surrogate(item.surrogate_config)?;
+            Ok((Item { shape_cd: Arc::new(rect), ..item.clone() }, *qty))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(SPInstance::new(items, instance.base_strip))
+}
+
+/// Cut tree of the solution, `None` if its items cannot be separated by guillotine cuts (or there are none)
+pub fn cut_tree(solution: &SPSolution) -> Option<CutTree> {
+    let placed_items = solution.layout_snapshot.placed_items.values().collect_vec();
+    let bboxes = placed_items.iter().map(|pi| pi.shape.bbox).collect_vec();
+    let tolerance = GUILLOTINE_CUT_TOLERANCE * solution.strip.fixed_height;
+    let idxs = (0..bboxes.len()).collect_vec();
+    match idxs.is_empty() {
+        true => None,
+        false => build_tree(&bboxes, &idxs, tolerance, &|idx| placed_items[idx].item_id),
+    }
+}
+
+fn build_tree(bboxes: &[Rect], idxs: &[usize], tolerance: f32, item_id: &impl Fn(usize) -> usize) -> Option<CutTree> {
+    if let [idx] = idxs {
+        return Some(CutTree::Item { index: *idx, item_id: item_id(*idx) });
+    }
+    for orientation in [CutOrientation::Vertical, CutOrientation::Horizontal] {
+        let parts = split(bboxes, idxs, orientation, tolerance);
+        if parts.len() > 1 {
+            let positions = parts.iter().tuple_windows()
+                .map(|(a, b)| {
+                    let a_max = a.iter().map(|&i| orientation.range(&bboxes[i]).1).fold(f32::NEG_INFINITY, f32::max);
+                    let b_min = b.iter().map(|&i| orientation.range(&bboxes[i]).0).fold(f32::INFINITY, f32::min);
+                    (a_max + b_min) / 2.0
+                })
+                .collect();
+            let parts = parts.iter()
+                .map(|part| build_tree(bboxes, part, tolerance, item_id))
+                .collect::<Option<Vec<_>>>()?;
+            return Some(CutTree::Cuts { orientation, positions, parts });
+        }
+    }
+    None
+}
+
+/// Divides the items into the parts separated by edge-to-edge cuts of the given orientation, in order along the cuts' normal.
+/// Bounding boxes on either side of a cut may overlap by at most `tolerance`.
+fn split(bboxes: &[Rect], idxs: &[usize], orientation: CutOrientation, tolerance: f32) -> Vec<Vec<usize>> {
+    let mut parts: Vec<Vec<usize>> = vec![];
+    let mut reach = f32::NEG_INFINITY;
+    for idx in idxs.iter().copied().sorted_by_key(|&i| OrderedFloat(orientation.range(&bboxes[i]).0)) {
+        let (min, max) = orientation.range(&bboxes[idx]);
+        match parts.last_mut() {
+            Some(part) if min < reach - tolerance => part.push(idx),
+            _ => parts.push(vec![idx]),
+        }
+        reach = reach.max(max);
+    }
+    parts
+}
+
+/// Makes the solution separable by guillotine cuts, by shifting items to the right (widening the strip if needed).
+/// Regions whose items cannot be separated are divided by the vertical cut requiring the smallest shift,
+/// after which all items right of the cut (within the region and in the regions right of it) are shifted.
+/// Layouts of [`bounding_rect_instance`] only need repairs for interlocking patterns (e.g. pinwheels), which the separator does not penalize.
+/// Returns an error if the repaired layout is still not separable, or contains collisions.
+pub fn guillotine_repair(instance: &SPInstance, solution: &SPSolution) -> Result<SPSolution> {
+    if cut_tree(solution).is_some() {
+        return Ok(solution.clone());
+    }
+    let mut prob = restore_problem(instance, solution);
+    let (pks, old_bboxes): (Vec<_>, Vec<_>) = prob.layout.placed_items.iter()
+        .map(|(pk, pi)| (pk, pi.shape.bbox))
+        .unzip();
+
+    let mut bboxes = old_bboxes.clone();
+    let idxs = (0..bboxes.len()).collect_vec();
+    let widening = repair_region(&mut bboxes, &idxs, GUILLOTINE_CUT_TOLERANCE * prob.strip.fixed_height);
+
+    let init_width = prob.strip_width();
+    if widening > 0.0 {
+        // The rightmost item keeps the same distance to the end of the strip
+        change_strip_width(&mut prob, init_width + widening);
+    }
+    let moves = pks.into_iter().zip(old_bboxes.iter().zip(bboxes.iter()))
+        .map(|(pk, (old, new))| (pk, new.x_min - old.x_min))
+        .filter(|(_, dx)| *dx > 0.0)
+        .collect_vec();
+    for (pk, dx) in moves {
+        let SPPlacement { item_id, d_transf } = prob.remove_item(pk);
+        let (tx, ty) = d_transf.translation();
+        prob.place_item(SPPlacement { item_id, d_transf: DTransformation::new(d_transf.rotation(), (tx + dx, ty)) });
+    }
+
+    let repaired = prob.save();
+    let (separable, feasible) = (cut_tree(&repaired).is_some(), prob.layout.is_feasible());
+    ensure!(separable && feasible, "failed to repair layout for guillotine cuts (separable: {separable}, feasible: {feasible})");
+    info!("[GLT] repaired layout for guillotine cuts, width: {:.3} -> {:.3}", init_width, repaired.strip_width());
+    Ok(repaired)
+}
+
+/// Repairs the items of a region and returns how far the region now extends beyond its original right edge
+fn repair_region(bboxes: &mut [Rect], idxs: &[usize], tolerance: f32) -> f32 {
+    if idxs.len() <= 1 {
+        return 0.0;
+    }
+    let x_max = |bboxes: &[Rect]| idxs.iter().map(|&i| bboxes[i].x_max).fold(f32::NEG_INFINITY, f32::max);
+    let init_x_max = x_max(bboxes);
+
+    let columns = split(bboxes, idxs, CutOrientation::Vertical, tolerance);
+    if columns.len() > 1 {
+        // Every column is shifted by the widening of all columns left of it
+        let mut offset = 0.0;
+        for column in &columns {
+            shift(bboxes, column, offset);
+            offset += repair_region(bboxes, column, tolerance);
+        }
+    } else {
+        let rows = split(bboxes, idxs, CutOrientation::Horizontal, tolerance);
+        if rows.len() > 1 {
+            // Rows are stacked vertically, widening one does not affect the others
+            for row in &rows {
+                repair_region(bboxes, row, tolerance);
+            }
+        } else {
+            // Not separable: shift the items right of the cheapest vertical cut, after which the region divides into columns
+            let (left, right) = cheapest_vertical_cut(bboxes, idxs);
+            let left_x_max = left.iter().map(|&i| bboxes[i].x_max).fold(f32::NEG_INFINITY, f32::max);
+            let right_x_min = right.iter().map(|&i| bboxes[i].x_min).fold(f32::INFINITY, f32::min);
+            shift(bboxes, &right, (left_x_max - right_x_min).max(0.0));
+            repair_region(bboxes, idxs, tolerance);
+        }
+    }
+    (x_max(bboxes) - init_x_max).max(0.0)
+}
+
+/// Divides the items (ordered by the center of their bounding box) into a left and right part,
+/// such that the right part has to be shifted the least to be separable from the left part by a vertical cut.
+fn cheapest_vertical_cut(bboxes: &[Rect], idxs: &[usize]) -> (Vec<usize>, Vec<usize>) {
+    let sorted = idxs.iter().copied().sorted_by_key(|&i| OrderedFloat(bboxes[i].centroid().0)).collect_vec();
+    let prefix_x_max = sorted.iter()
+        .scan(f32::NEG_INFINITY, |m, &i| { *m = f32::max(*m, bboxes[i].x_max); Some(*m) })
+        .collect_vec();
+    let mut suffix_x_min = sorted.iter().rev()
+        .scan(f32::INFINITY, |m, &i| { *m = f32::min(*m, bboxes[i].x_min); Some(*m) })
+        .collect_vec();
+    suffix_x_min.reverse();
+
+    let k = (1..sorted.len())
+        .min_by_key(|&k| OrderedFloat(prefix_x_max[k - 1] - suffix_x_min[k]))
+        .expect("a region to divide should contain at least two items");
+    let (left, right) = sorted.split_at(k);
+    (left.to_vec(), right.to_vec())
+}
+
+fn shift(bboxes: &mut [Rect], idxs: &[usize], dx: f32) {
+    if dx != 0.0 {
+        for &i in idxs {
+            let b = bboxes[i];
+            bboxes[i] = Rect { x_min: b.x_min + dx, x_max: b.x_max + dx, ..b };
+        }
+    }
+}
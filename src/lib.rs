@@ -1,4 +1,3 @@
-#![cfg_attr(feature = "simd", feature(portable_simd))]
 #![allow(const_item_mutation)]
 #![allow(unused_imports)]
 
@@ -13,6 +12,15 @@ pub mod util;
 pub mod config;
 pub mod eval;
 pub mod consts;
+pub mod bounds;
+pub mod groups;
+pub mod zones;
+pub mod guillotine;
+pub mod api;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use api::{Sparrow, SparrowBuilder, SparrowResult};
 
 pub static EPOCH: LazyLock<Instant> = LazyLock::new(Instant::now);
 
@@ -1,21 +1,18 @@
 extern crate core;
 
 use clap::Parser as Clap;
-use jagua_rs::io::import::Importer;
 use log::{info, warn, Level};
-use rand::SeedableRng;
 use sparrow::config::*;
-use sparrow::optimizer::optimize;
+use sparrow::Sparrow;
+use sparrow::sample::search::ContainerSampler;
 use sparrow::util::io;
-use sparrow::util::io::{ExtSPOutput, MainCli};
-use sparrow::EPOCH;
+use sparrow::util::io::MainCli;
 use std::fs;
 use std::path::Path;
 use std::time::Duration;
 
-use anyhow::{bail, Result};
-use rand::rngs::Xoshiro256PlusPlus;
-use sparrow::consts::{DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO, DEFAULT_FAIL_DECAY_RATIO_CMPR, DEFAULT_MAX_CONSEQ_FAILS_EXPL, LOG_LEVEL_FILTER_DEBUG, LOG_LEVEL_FILTER_RELEASE};
+use anyhow::{bail, ensure, Result};
+use sparrow::consts::{DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO, DEFAULT_FAIL_DECAY_RATIO_CMPR, DEFAULT_ISLAND_MIGRATION_INTERVAL, DEFAULT_MAX_CONSEQ_FAILS_EXPL, LOG_LEVEL_FILTER_DEBUG, LOG_LEVEL_FILTER_RELEASE};
 use sparrow::util::ctrlc_terminator::CtrlCTerminator;
 use sparrow::util::svg_exporter::SvgExporter;
 
@@ -58,35 +55,67 @@ fn main() -> Result<()>{
     if let Some(arg_rng_seed) = args.rng_seed {
         config.rng_seed = Some(arg_rng_seed as usize);
     }
-
-    info!("[MAIN] configured to explore for {}s and compress for {}s", explore_dur.as_secs(), compress_dur.as_secs());
-
-    let rng = match config.rng_seed {
-        Some(seed) => {
-            info!("[MAIN] using seed: {}", seed);
-            Xoshiro256PlusPlus::seed_from_u64(seed as u64)
-        },
-        None => {
-            let seed = rand::random();
-            warn!("[MAIN] no seed provided, using: {}", seed);
-            Xoshiro256PlusPlus::seed_from_u64(seed)
+    if let (Some(expl_iters), Some(cmpr_iters)) = (args.exploration_iterations, args.compression_iterations) {
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(expl_iters));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(cmpr_iters));
+        info!("[MAIN] deterministic mode: exploring for {expl_iters} iterations and compressing for {cmpr_iters} iterations");
+        if config.rng_seed.is_none() {
+            warn!("[MAIN] deterministic mode without a fixed seed, results will not be reproducible");
+        }
+    }
+    if args.adaptive_sampling {
+        config.expl_cfg.separator_config.sample_config.adaptive = true;
+        config.cmpr_cfg.separator_config.sample_config.adaptive = true;
+    }
+    if args.gap_aware_sampling {
+        config.expl_cfg.separator_config.sample_config.container_sampler = ContainerSampler::GapAware;
+        config.cmpr_cfg.separator_config.sample_config.container_sampler = ContainerSampler::GapAware;
+    }
+    config.expl_cfg.separator_config.sample_config.refiner = args.exploration_refiner;
+    config.cmpr_cfg.separator_config.sample_config.refiner = args.compression_refiner;
+    match args.annealing_temp {
+        Some(init_temp) if init_temp <= 0.0 => bail!("annealing temperature must be positive"),
+        Some(init_temp) => {
+            config.cmpr_cfg.shrink_decay = ShrinkDecayStrategy::Annealing { init_temp };
+            info!("[MAIN] compressing with simulated annealing (initial temperature: {init_temp})");
         }
+        None => {}
+    }
+    if let Some(n_islands) = args.islands {
+        config.expl_cfg.island_config = Some(IslandConfig {
+            n_islands,
+            migration_interval: args.migration_interval.map_or(DEFAULT_ISLAND_MIGRATION_INTERVAL, Duration::from_secs),
+            topology: args.migration_topology,
+        });
+        info!("[MAIN] exploring with {} islands", n_islands);
+    }
+
+    let open_dimension = match (args.open_dimension, args.aspect_ratio) {
+        (_, Some(ratio)) if ratio <= 0.0 => bail!("aspect ratio must be positive"),
+        (_, Some(ratio)) => Some(OpenDimension::AspectRatio(ratio)),
+        (true, None) => Some(OpenDimension::Alternating),
+        (false, None) => None,
     };
+    if let Some(open_dimension) = open_dimension {
+        config.expl_cfg.open_dimension = Some(open_dimension);
+        config.cmpr_cfg.open_dimension = Some(open_dimension);
+        info!("[MAIN] open-dimension mode: {:?}", open_dimension);
+    }
+    if args.guillotine {
+        config.cmpr_cfg.guillotine = true;
+        info!("[MAIN] guillotine cuts required");
+    }
+
+    info!("[MAIN] configured to explore for {}s and compress for {}s", explore_dur.as_secs(), compress_dur.as_secs());
 
     info!("[MAIN] system time: {}", jiff::Timestamp::now());
 
     let (ext_instance, ext_solution) = io::read_spp_input(Path::new(&input_file_path))?;
+    let demand_ranges = io::read_demand_ranges(Path::new(&input_file_path))?;
+    let item_groups = io::read_item_groups(Path::new(&input_file_path))?;
+    let quality_zones = io::read_quality_zones(Path::new(&input_file_path))?;
 
-    let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
-    let instance = jagua_rs::probs::spp::io::import_instance(&importer, &ext_instance)?;
-
-    let initial_solution = ext_solution.map(|e|
-        jagua_rs::probs::spp::io::import_solution(&instance, &e)
-    );
-
-    info!("[MAIN] loaded instance {} with #{} items", ext_instance.name, instance.total_item_qty());
-    
-    let mut svg_exporter = {
+    let svg_exporter = {
         let final_svg_path = Some(format!("{OUTPUT_DIR}/final_{}.svg", ext_instance.name));
 
         let intermediate_svg_dir = match cfg!(feature = "only_final_svg") {
@@ -106,24 +135,30 @@ fn main() -> Result<()>{
         )
     };
     
-    let mut ctrlc_terminator = CtrlCTerminator::new();
-
-    let solution = optimize(
-        instance.clone(),
-        rng,
-        &mut svg_exporter,
-        &mut ctrlc_terminator,
-        &config.expl_cfg,
-        &config.cmpr_cfg,
-        initial_solution.as_ref()
-    );
-
-    let json_path = format!("{OUTPUT_DIR}/final_{}.json", ext_instance.name);
-    let json_output = ExtSPOutput {
-        instance: ext_instance,
-        solution: jagua_rs::probs::spp::io::export(&instance, &solution, *EPOCH)
-    };
+    let mut builder = Sparrow::builder()
+        .instance(ext_instance)
+        .config(config)
+        .listener(svg_exporter)
+        .terminator(CtrlCTerminator::new())
+        .strip_heights(args.strip_heights);
+    if let Some(ext_solution) = ext_solution {
+        builder = builder.warm_start(ext_solution);
+    }
+    if let Some(demand_ranges) = demand_ranges {
+        builder = builder.demand_ranges(demand_ranges);
+    }
+    if let Some(item_groups) = item_groups {
+        builder = builder.item_groups(item_groups);
+    }
+    if let Some(quality_zones) = quality_zones {
+        builder = builder.quality_zones(quality_zones);
+    }
+    let result = builder.build()?.run();
+
+    let json_output = result.to_ext_output().expect("instance was provided in its external representation");
+    let json_path = format!("{OUTPUT_DIR}/final_{}.json", json_output.instance.name);
     io::write_json(&json_output, Path::new(json_path.as_str()), Level::Info)?;
 
+    ensure!(result.guillotine_feasible != Some(false), "the final solution (written to {json_path}) is not separable by guillotine cuts");
     Ok(())
 }
@@ -0,0 +1,113 @@
+use crate::consts::{COMPACT_MAX_PASSES, COMPACT_STEP_RATIOS};
+use crate::groups::ItemGroups;
+use crate::zones::{allowed_zones, restore_problem};
+use crate::FMT;
+use itertools::Itertools;
+use crate::groups::GroupWindow;
+use jagua_rs::entities::{Instance, Item, Layout};
+use jagua_rs::geometry::geo_traits::TransformableFrom;
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem, SPSolution};
+use jagua_rs::Instant;
+use log::{debug, info};
+use ordered_float::OrderedFloat;
+
+/// Deterministic post-processing of a feasible solution: slides every item left and down as far as possible without collision.
+/// Items are processed from left to right, each one being moved against the (fixed) rest of the layout,
+/// so feasibility is preserved throughout. This pushes the used length of the strip to the left,
+/// maximizing the rectangular remnant at the right end of the strip.
+/// Items of a group are never moved beyond the group's maximum span.
+pub fn compaction_phase(instance: &SPInstance, sol: &SPSolution, item_groups: &ItemGroups) -> SPSolution {
+    let start = Instant::now();
+    let mut prob = restore_problem(instance, sol);
+
+    let init_remnant = remnant_area(sol);
+
+    for pass in 0..COMPACT_MAX_PASSES {
+        // Process the items from left to right (and bottom to top)
+        let order = prob.layout.placed_items.iter()
+            .sorted_by_key(|(_, pi)| (OrderedFloat(pi.shape.bbox.x_min), OrderedFloat(pi.shape.bbox.y_min)))
+            .map(|(pk, _)| pk)
+            .collect_vec();
+
+        let mut n_moved = 0;
+        for pk in order {
+            let SPPlacement { item_id, d_transf } = prob.remove_item(pk);
+            let window = item_groups.window(&prob.layout, item_id, None);
+            let compacted_dt = slide_item(&prob.layout, instance.item(item_id), d_transf, window);
+            if compacted_dt != d_transf {
+                n_moved += 1;
+            }
+            prob.place_item(SPPlacement { item_id, d_transf: compacted_dt });
+        }
+        debug!("[CMPT] pass {pass}: moved {n_moved} items");
+        if n_moved == 0 {
+            break;
+        }
+    }
+
+    let compacted_sol = prob.save();
+    info!("[CMPT] compacted layout, remnant area: {} -> {} (in {:?})",
+        FMT().fmt2(init_remnant),
+        FMT().fmt2(remnant_area(&compacted_sol)),
+        start.elapsed()
+    );
+    compacted_sol
+}
+
+/// Area of the free rectangle between the rightmost point of any placed item and the end of the strip
+pub fn remnant_area(sol: &SPSolution) -> f32 {
+    let used_length = sol.layout_snapshot.placed_items.values()
+        .map(|pi| pi.shape.bbox.x_max)
+        .fold(0.0, f32::max);
+    (sol.strip_width() - used_length).max(0.0) * sol.strip.fixed_height
+}
+
+/// Alternately slides the item left and down until it can move in neither direction.
+/// The item should not be present in the layout, `window` contains the other placed items of its group (if any).
+fn slide_item(l: &Layout, item: &Item, d_transf: DTransformation, window: Option<GroupWindow>) -> DTransformation {
+    let mut shape_buff = item.shape_cd.as_ref().clone();
+    let zone_filter = allowed_zones(l, item.min_quality);
+    let mut is_free = |dt: DTransformation| -> bool {
+        let transf = dt.into();
+        let cde = l.cde();
+        if cde.detect_surrogate_collision(item.shape_cd.surrogate(), &transf, &zone_filter) {
+            return false;
+        }
+        shape_buff.transform_from(&item.shape_cd, &transf);
+        let bbox = shape_buff.bbox;
+        let within_group_span = window.is_none_or(|w| w.excess(bbox.x_min, bbox.x_max) == 0.0);
+        within_group_span && !cde.detect_poly_collision(&shape_buff, &zone_filter)
+    };
+
+    if !is_free(d_transf) {
+        // Should not happen for feasible solutions, but never move an item that is already colliding
+        return d_transf;
+    }
+
+    let item_min_dim = f32::min(item.shape_cd.bbox.width(), item.shape_cd.bbox.height());
+    let (init_step, limit_step) = (item_min_dim * COMPACT_STEP_RATIOS.0, item_min_dim * COMPACT_STEP_RATIOS.1);
+
+    let mut current = d_transf;
+    loop {
+        let mut moved = false;
+        for dir in [(-1.0, 0.0), (0.0, -1.0)] {
+            // Keep moving with the same step as long as possible, halve it when blocked
+            let mut step = init_step;
+            while step >= limit_step {
+                let (tx, ty) = current.translation();
+                let candidate = DTransformation::new(current.rotation(), (tx + dir.0 * step, ty + dir.1 * step));
+                match is_free(candidate) {
+                    true => {
+                        current = candidate;
+                        moved = true;
+                    }
+                    false => step *= 0.5,
+                }
+            }
+        }
+        if !moved {
+            return current;
+        }
+    }
+}
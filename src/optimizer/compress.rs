@@ -1,10 +1,13 @@
+use crate::bounds::{min_strip_height, LowerBounds};
 use crate::config::{CompressionConfig, ShrinkDecayStrategy};
+use crate::optimizer::open_dim::shrink_factors;
 use crate::optimizer::separator::Separator;
 use crate::util::listener::{ReportType, SolutionListener};
 use crate::util::terminator::Terminator;
+use crate::FMT;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
 use jagua_rs::Instant;
-use log::info;
+use log::{debug, info};
 use rand::{Rng, RngExt};
 
 /// Algorithm 13 from https://doi.org/10.48550/arXiv.2509.13329
@@ -16,58 +19,199 @@ pub fn compression_phase(
     term: &impl Terminator,
     config: &CompressionConfig
 ) -> SPSolution {
-    let mut best_sol = init_sol.clone();
-    let start = Instant::now();
-    let mut n_failed_attempts = 0;
+    let mut state = CompressionState::new(instance, init_sol, LowerBounds::new(instance));
 
-    // Create the function to calculate the shrink step size.
-    let shrink_step_size = |n_failed_attempts: i32| -> f32 {
-        match config.shrink_decay {
-            ShrinkDecayStrategy::TimeBased => {
-                let range = config.shrink_range.1 - config.shrink_range.0;
-                let elapsed = start.elapsed();
-                let ratio = elapsed.as_secs_f32() / config.time_limit.as_secs_f32();
-                config.shrink_range.0 + ratio * range
-            }
-            ShrinkDecayStrategy::FailureBased(r) => {
-                config.shrink_range.0 * r.powi(n_failed_attempts)
-            }
+    while !term.kill() && state.step(instance, sep, sol_listener, term, config) {}
+
+    state.finish(instance)
+}
+
+/// State of the compression phase, which can be advanced one compression attempt at a time.
+/// An attempt interrupted by the terminator is resumed by the next step.
+pub struct CompressionState {
+    pub init_sol: SPSolution,
+    pub best_sol: SPSolution,
+    /// Solution from which the next compression is attempted, always at the dimensions of the best solution
+    pub current_sol: SPSolution,
+    pub n_failed_attempts: i32,
+    pub lower_bounds: LowerBounds,
+    start: Instant,
+    /// Shrink step and split positions of an attempt that was interrupted by the terminator
+    interrupted: Option<(f32, Split)>,
+    /// Number of attempts started, used to alternate between the dimensions in open-dimension mode
+    n_attempts: usize,
+    /// Minimum height of the strip in which all items fit
+    min_height: f32,
+    /// Loss of the first failed attempt, relative to which the losses of failed attempts are compared during annealing
+    ref_loss: Option<f32>,
+    /// Whether `current_sol` is an accepted failed attempt rather than the best solution
+    accepted: bool,
+}
+
+impl CompressionState {
+    pub fn new(instance: &SPInstance, init_sol: &SPSolution, lower_bounds: LowerBounds) -> Self {
+        Self {
+            init_sol: init_sol.clone(),
+            best_sol: init_sol.clone(),
+            current_sol: init_sol.clone(),
+            n_failed_attempts: 0,
+            lower_bounds,
+            start: Instant::now(),
+            interrupted: None,
+            n_attempts: 0,
+            min_height: min_strip_height(instance),
+            ref_loss: None,
+            accepted: false,
         }
-    };
+    }
 
-    // As long as the shrink step size is above the minimum, keep attempting to compress
-    while !term.kill() && let step = shrink_step_size(n_failed_attempts) && step >= config.shrink_range.1 {
-        match attempt_to_compress(sep, &best_sol, step, term, sol_listener) {
-            Some(compacted_sol) => {
-                info!("[CMPR] success at {:.3}% ({:.3} | {:.3}%)", step * 100.0, compacted_sol.strip_width(), compacted_sol.density(instance) * 100.0);
+    /// Performs (or resumes) a single compression attempt.
+    /// Returns `false` if the compression phase should not be continued.
+    pub fn step(&mut self, instance: &SPInstance, sep: &mut Separator, sol_listener: &mut impl SolutionListener, term: &impl Terminator, config: &CompressionConfig) -> bool {
+        let attempt = match self.interrupted.take() {
+            Some((step, split)) => (step, resume_compression(sep, split, term, sol_listener)),
+            None => {
+                // As long as the shrink step size is above the minimum, keep attempting to compress
+                let step = self.shrink_step_size(term, config);
+                let lb_reached = config.open_dimension.is_none() && self.lower_bounds.is_reached(self.best_sol.strip_width());
+                if lb_reached || step < config.shrink_range.1 {
+                    return false;
+                }
+                let factors = shrink_factors(config.open_dimension, self.n_attempts, step, self.current_sol.strip.fixed_height, self.min_height);
+                self.n_attempts += 1;
+                (step, attempt_to_compress(sep, &self.current_sol, factors, term, sol_listener))
+            }
+        };
+        match attempt {
+            (step, Attempt::Feasible(compacted_sol)) => {
+                info!("[CMPR] success at {:.3}% ({:.3} | {:.3}%, gap: {:.3}%)", step * 100.0, compacted_sol.strip_width(), compacted_sol.density(instance) * 100.0, self.lower_bounds.gap(compacted_sol.strip_width()) * 100.0);
                 sol_listener.report(ReportType::CmprFeas, &compacted_sol, instance);
-                best_sol = compacted_sol;
+                self.best_sol = compacted_sol;
+                self.current_sol = self.best_sol.clone();
+                self.accepted = false;
             }
-            None => {
+            (step, Attempt::Infeasible(failed)) if term.kill() => {
+                // The separation was interrupted, leave the separator as is so the attempt can be resumed
+                debug!("[CMPR] attempt at {:.3}% interrupted (min loss: {})", step * 100.0, FMT().fmt2(failed.loss));
+                self.interrupted = Some((step, failed.split));
+            }
+            (step, Attempt::Infeasible(failed)) => {
                 info!("[CMPR] failed at {:.3}%", step * 100.0);
-                n_failed_attempts += 1;
+                self.n_failed_attempts += 1;
+
+                if let ShrinkDecayStrategy::Annealing { init_temp } = config.shrink_decay {
+                    if self.accepted {
+                        // Attempts from an accepted solution that fail again return to the best solution
+                        debug!("[CMPR] returning to the best solution");
+                        self.current_sol = self.best_sol.clone();
+                        self.accepted = false;
+                    } else {
+                        // Accept the failed attempt as the next starting point with a probability depending on its relative loss and the temperature
+                        let ref_loss = *self.ref_loss.get_or_insert(failed.loss);
+                        let temp = init_temp * (1.0 - self.elapsed_ratio(term, config)).max(0.0);
+                        let delta = failed.loss / ref_loss;
+                        let p_accept = if temp > 0.0 { (-delta / temp).exp() } else { 0.0 };
+                        if sep.rng.random::<f32>() < p_accept {
+                            info!("[CMPR] accepting nearly feasible solution (l: {}, t: {:.2e}, p: {:.3})", FMT().fmt2(failed.loss), temp, p_accept);
+                            self.current_sol = expand(sep, &failed, &self.best_sol);
+                            self.accepted = true;
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Concludes the compression phase, returning the best solution found.
+    pub fn finish(self, instance: &SPInstance) -> SPSolution {
+        let (init_dens, best_dens) = (self.init_sol.density(instance), self.best_sol.density(instance));
+        info!("[CMPR] finished, compressed from {:.3}% to {:.3}% (+{:.3}%)", init_dens * 100.0, best_dens * 100.0, (best_dens - init_dens) * 100.0);
+        self.best_sol
+    }
+
+    /// Ratio of the work budget that has been spent, or of the time limit that has elapsed
+    fn elapsed_ratio(&self, term: &impl Terminator, config: &CompressionConfig) -> f32 {
+        term.work_progress()
+            .unwrap_or_else(|| self.start.elapsed().as_secs_f32() / config.time_limit.as_secs_f32())
+    }
+
+    fn shrink_step_size(&self, term: &impl Terminator, config: &CompressionConfig) -> f32 {
+        match config.shrink_decay {
+            ShrinkDecayStrategy::TimeBased | ShrinkDecayStrategy::Annealing { .. } => {
+                let range = config.shrink_range.1 - config.shrink_range.0;
+                config.shrink_range.0 + self.elapsed_ratio(term, config) * range
+            }
+            ShrinkDecayStrategy::FailureBased(r) => {
+                config.shrink_range.0 * r.powi(self.n_failed_attempts)
             }
         }
     }
-    info!("[CMPR] finished, compressed from {:.3}% to {:.3}% (+{:.3}%)", init_sol.density(instance) * 100.0, best_sol.density(instance) * 100.0, (best_sol.density(instance) - init_sol.density(instance)) * 100.0);
-    best_sol
 }
 
+/// Outcome of a compression attempt
+enum Attempt {
+    Feasible(SPSolution),
+    Infeasible(FailedAttempt),
+}
+
+/// The least infeasible solution reached by an unsuccessful compression attempt
+struct FailedAttempt {
+    sol: SPSolution,
+    loss: f32,
+    split: Split,
+}
+
+/// Positions at which the strip was shrunk horizontally (`x`) and/or vertically (`y`)
+#[derive(Debug, Clone, Copy)]
+struct Split {
+    x: Option<f32>,
+    y: Option<f32>,
+}
 
-fn attempt_to_compress(sep: &mut Separator, init_sol: &SPSolution, r_shrink: f32, term: &impl Terminator, sol_listener: &mut impl SolutionListener) -> Option<SPSolution> {
-    // Restore to the initial solution and width
+fn attempt_to_compress(sep: &mut Separator, init_sol: &SPSolution, (width_factor, height_factor): (f32, f32), term: &impl Terminator, sol_listener: &mut impl SolutionListener) -> Attempt {
+    // Restore to the initial solution and dimensions
     sep.change_strip_width(init_sol.strip_width(), None);
+    sep.change_strip_height(init_sol.strip.fixed_height, None);
     sep.rollback(init_sol, None);
 
-    // Shrink the container by the provided amount at a random position
-    let new_width = init_sol.strip_width() * (1.0 - r_shrink);
-    let split_pos = sep.rng.random_range(0.0..sep.prob.strip_width());
-    sep.change_strip_width(new_width, Some(split_pos));
+    // Shrink the container by the provided factors at random positions
+    let split = Split {
+        x: (width_factor < 1.0).then(|| sep.rng.random_range(0.0..sep.prob.strip_width())),
+        y: (height_factor < 1.0).then(|| sep.rng.random_range(0.0..sep.prob.strip.fixed_height)),
+    };
+    if let Some(x) = split.x {
+        sep.change_strip_width(init_sol.strip_width() * width_factor, Some(x));
+    }
+    if let Some(y) = split.y {
+        sep.change_strip_height(init_sol.strip.fixed_height * height_factor, Some(y));
+    }
+
+    resume_compression(sep, split, term, sol_listener)
+}
 
+/// Separates the current layout of the separator, the strip of which was shrunk at `split`.
+fn resume_compression(sep: &mut Separator, split: Split, term: &impl Terminator, sol_listener: &mut impl SolutionListener) -> Attempt {
     // Try to separate layout, if all collisions are eliminated, return the solution
     let (compacted_sol, ot) = sep.separate(term, sol_listener);
-    match ot.get_total_loss() == 0.0 {
-        true => Some(compacted_sol),
-        false => None,
+    let loss = ot.get_total_loss();
+    match loss == 0.0 {
+        true => Attempt::Feasible(compacted_sol),
+        false => Attempt::Infeasible(FailedAttempt { sol: compacted_sol, loss, split }),
     }
-}
\ No newline at end of file
+}
+
+/// Widens the solution of a failed attempt back to the dimensions of `target`, by reinserting the space at the positions where it was removed.
+/// The resulting solution is not necessarily feasible, but generally close to it.
+fn expand(sep: &mut Separator, failed: &FailedAttempt, target: &SPSolution) -> SPSolution {
+    sep.change_strip_width(failed.sol.strip_width(), None);
+    sep.change_strip_height(failed.sol.strip.fixed_height, None);
+    sep.rollback(&failed.sol, None);
+    if let Some(x) = failed.split.x {
+        sep.change_strip_width(target.strip_width(), Some(x));
+    }
+    if let Some(y) = failed.split.y {
+        sep.change_strip_height(target.strip.fixed_height, Some(y));
+    }
+    sep.prob.save()
+}
@@ -0,0 +1,123 @@
+use crate::consts::OPTIONAL_ITEM_SAMPLE_CONFIG;
+use crate::eval::lbf_evaluator::LBFEvaluator;
+use crate::eval::sample_eval::SampleEval;
+use crate::groups::ItemGroups;
+use crate::sample::gap_sampler::OccupancyGrid;
+use crate::sample::search::search_placement;
+use crate::zones::restore_problem;
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+use jagua_rs::entities::Instance;
+use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPSolution};
+use log::info;
+use ordered_float::OrderedFloat;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Number of copies of an item to produce: `min` copies are required, up to `max` copies are produced if they fit.
+/// Filler items (only used to fill leftover space) have a `min` of zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DemandRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl DemandRange {
+    pub fn n_optional(&self) -> usize {
+        self.max - self.min
+    }
+}
+
+/// Checks the demand ranges (indexed by item id) against an instance containing the maximum quantity of every item
+pub fn validate_demand_ranges(instance: &SPInstance, ranges: &[DemandRange]) -> Result<()> {
+    ensure!(ranges.len() == instance.items.len(), "expected a demand range for all {} items, got {}", instance.items.len(), ranges.len());
+    for (id, range) in ranges.iter().enumerate() {
+        ensure!(range.min <= range.max, "minimum demand of item {id} exceeds its maximum demand ({} > {})", range.min, range.max);
+    }
+    ensure!(ranges.iter().any(|r| r.min > 0), "at least one item should have a positive (minimum) demand");
+    Ok(())
+}
+
+/// Copy of the instance in which every item has its required quantity, items with only optional copies are kept with a quantity of zero
+pub fn required_instance(instance: &SPInstance, ranges: &[DemandRange]) -> SPInstance {
+    with_quantities(instance, |id| ranges[id].min)
+}
+
+/// Copy of the instance in which every item has its maximum quantity
+pub fn full_instance(instance: &SPInstance, ranges: &[DemandRange]) -> SPInstance {
+    with_quantities(instance, |id| ranges[id].max)
+}
+
+fn with_quantities(instance: &SPInstance, qty: impl Fn(usize) -> usize) -> SPInstance {
+    let items = instance.items.iter()
+        .map(|(item, _)| (item.clone(), qty(item.id)))
+        .collect();
+    SPInstance::new(items, instance.base_strip)
+}
+
+/// Inserts as many optional copies of items as possible into the free space of a solution, without changing the strip.
+/// Items are inserted from large to small (as in [`LBFBuilder`](crate::optimizer::lbf::LBFBuilder)),
+/// once a copy of an item no longer fits, its remaining copies are skipped.
+/// Copies of grouped items are only inserted within the maximum span of their group.
+pub fn insert_optional_items(instance: &SPInstance, ranges: &[DemandRange], item_groups: &ItemGroups, solution: &SPSolution, rng: &mut impl Rng) -> SPSolution {
+    let full_instance = full_instance(instance, ranges);
+    let mut prob = restore_problem(&full_instance, solution);
+    let mut grid = OccupancyGrid::new(&prob.layout);
+
+    let sorted_item_ids = (0..full_instance.items.len())
+        .filter(|&id| prob.item_demand_qtys[id] > 0)
+        .sorted_by_cached_key(|&id| {
+            let item_shape = full_instance.item(id).shape_cd.as_ref();
+            Reverse(OrderedFloat(item_shape.surrogate().convex_hull_area * item_shape.diameter))
+        })
+        .collect_vec();
+
+    let mut n_inserted = 0;
+    for item_id in sorted_item_ids {
+        while prob.item_demand_qtys[item_id] > 0 {
+            let item = full_instance.item(item_id);
+            let evaluator = LBFEvaluator::new(&prob.layout, item)
+                .with_group_window(item_groups.window(&prob.layout, item_id, None));
+            match search_placement(&prob.layout, item, None, Some(&grid), evaluator, OPTIONAL_ITEM_SAMPLE_CONFIG, rng).0 {
+                Some((d_transf, SampleEval::Clear { .. })) => {
+                    let pk = prob.place_item(SPPlacement { item_id, d_transf });
+                    grid.register_placement(&prob.layout.placed_items[pk]);
+                    n_inserted += 1;
+                }
+                _ => break,
+            }
+        }
+    }
+
+    let n_optional = ranges.iter().map(|r| r.n_optional()).sum::<usize>();
+    info!("[DMD] inserted {n_inserted}/{n_optional} optional items (dens: {:.3}%)", prob.density() * 100.0);
+    prob.save()
+}
+
+/// Removes all copies of items beyond their required quantity from a solution (e.g. a warm start), `instance` should contain the maximum quantities.
+pub fn remove_optional_items(instance: &SPInstance, ranges: &[DemandRange], solution: &SPSolution) -> SPSolution {
+    let mut prob = restore_problem(instance, solution);
+
+    let mut n_placed = vec![0; ranges.len()];
+    let surplus = prob.layout.placed_items.iter()
+        .filter(|(_, pi)| {
+            n_placed[pi.item_id] += 1;
+            n_placed[pi.item_id] > ranges[pi.item_id].min
+        })
+        .map(|(pk, _)| pk)
+        .collect_vec();
+    for pk in surplus {
+        prob.remove_item(pk);
+    }
+    prob.save()
+}
+
+/// Number of placed copies of every item in a solution, indexed by item id
+pub fn placed_quantities(n_items: usize, solution: &SPSolution) -> Vec<usize> {
+    let mut quantities = vec![0; n_items];
+    for pi in solution.layout_snapshot.placed_items.values() {
+        quantities[pi.item_id] += 1;
+    }
+    quantities
+}
@@ -0,0 +1,47 @@
+use crate::consts::{DSRP_BAND_WIDTH_RATIO, DSRP_RECREATE_N_SAMPLES};
+use crate::optimizer::disrupt::{best_sample_in, clamp_into_container, items_in_band, random_band, DisruptionContext, Disruptor};
+use crate::optimizer::separator::Separator;
+use log::info;
+use rand::prelude::SliceRandom;
+
+/// Ruins a random vertical band of the strip and recreates it by re-placing all its items at the best of a set of samples within the band.
+pub struct RuinRecreateBand;
+
+impl Disruptor for RuinRecreateBand {
+    fn disrupt(&self, sep: &mut Separator, _ctx: &DisruptionContext) {
+        let band = random_band(sep, DSRP_BAND_WIDTH_RATIO);
+        let mut ruined = items_in_band(sep, band);
+        ruined.shuffle(&mut sep.rng);
+
+        info!("[DSRP] ruining and recreating {} items in band [{:.3}, {:.3}]", ruined.len(), band.x_min, band.x_max);
+
+        for pk in ruined {
+            if let Some(dt) = best_sample_in(sep, pk, band, DSRP_RECREATE_N_SAMPLES) {
+                sep.move_item(pk, dt);
+            }
+        }
+    }
+}
+
+/// Reflects the positions (centroids) of all items within a random vertical band of the strip around the band's center line.
+/// Only the positions are reflected, the items themselves are translated and keep their orientation,
+/// as the allowed transformations of items do not include reflections.
+pub struct ReflectPositions;
+
+impl Disruptor for ReflectPositions {
+    fn disrupt(&self, sep: &mut Separator, _ctx: &DisruptionContext) {
+        let band = random_band(sep, DSRP_BAND_WIDTH_RATIO);
+        let reflected = items_in_band(sep, band);
+
+        info!("[DSRP] reflecting the positions of {} items in band [{:.3}, {:.3}]", reflected.len(), band.x_min, band.x_max);
+
+        for pk in reflected {
+            let pi = &sep.prob.layout.placed_items[pk];
+            // horizontal translation that reflects the item's centroid around the center line of the band
+            let dx = (band.x_min + band.x_max) - 2.0 * pi.shape.centroid().0;
+            let new_dt = pi.d_transf.compose().translate((dx, 0.0)).decompose();
+            let new_dt = clamp_into_container(sep, pi.item_id, new_dt);
+            sep.move_item(pk, new_dt);
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::consts::{DSRP_CLUSTER_RADIUS_RATIO, DSRP_CLUSTER_ROTATION_TOLERANCE};
+use crate::optimizer::disrupt::{clamp_into_container, DisruptionContext, Disruptor};
+use crate::optimizer::separator::Separator;
+use crate::sample::uniform_sampler::convert_sample_to_closest_feasible;
+use itertools::Itertools;
+use jagua_rs::entities::{Instance, Item};
+use jagua_rs::geometry::geo_traits::DistanceTo;
+use jagua_rs::geometry::{normalize_rotation, DTransformation};
+use log::info;
+use rand::prelude::{IndexedRandom, IteratorRandom};
+use std::f32::consts::PI;
+
+/// Rotates a cluster of neighbouring items as a whole, by a random quarter turn around the centroid of a random seed item.
+/// Only the items which allow the rotation are part of the cluster, so all of them keep their relative positions.
+/// The quarter turn is chosen among those allowed by the most items of the neighbourhood (ideally all of them).
+pub struct RotateCluster;
+
+impl Disruptor for RotateCluster {
+    fn disrupt(&self, sep: &mut Separator, _ctx: &DisruptionContext) {
+        let seed = sep.prob.layout.placed_items.values()
+            .choose(&mut sep.rng)
+            .expect("[DSRP] failed to choose seed item");
+
+        let center = seed.shape.centroid();
+        let radius = seed.shape.diameter * DSRP_CLUSTER_RADIUS_RATIO;
+
+        let neighbourhood = sep.prob.layout.placed_items.iter()
+            .filter(|(_, pi)| pi.shape.centroid().distance_to(&center) <= radius)
+            .map(|(pk, pi)| (pk, pi.item_id, pi.d_transf))
+            .collect_vec();
+
+        let allows = |item_id: usize, dt: DTransformation, angle: f32| allows_rotation(sep.prob.instance.item(item_id), dt.rotation() + angle);
+        let angle_counts = [0.5 * PI, PI, 1.5 * PI]
+            .map(|angle| (angle, neighbourhood.iter().filter(|(_, item_id, dt)| allows(*item_id, *dt, angle)).count()));
+        let max_count = angle_counts.iter().map(|(_, count)| *count).max().unwrap();
+        if max_count == 0 {
+            info!("[DSRP] none of the {} items around item {} allow a quarter turn", neighbourhood.len(), seed.item_id);
+            return;
+        }
+        let angle = angle_counts.iter()
+            .filter(|(_, count)| *count == max_count)
+            .map(|(angle, _)| *angle)
+            .collect_vec()
+            .choose(&mut sep.rng)
+            .copied()
+            .unwrap();
+
+        let cluster = neighbourhood.into_iter()
+            .filter(|(_, item_id, dt)| allows(*item_id, *dt, angle))
+            .collect_vec();
+
+        info!("[DSRP] rotating cluster of {} items around item {} by {:.0}°", cluster.len(), seed.item_id, angle.to_degrees());
+
+        for (pk, item_id, dt) in cluster {
+            let new_dt = dt.compose()
+                .translate((-center.0, -center.1))
+                .rotate(angle)
+                .translate((center.0, center.1))
+                .decompose();
+            // Only removes numerical deviations from the allowed rotation
+            let new_feasible_dt = convert_sample_to_closest_feasible(new_dt, sep.prob.instance.item(item_id));
+            // Make sure the item does not end up outside the container entirely
+            let new_feasible_dt = clamp_into_container(sep, item_id, new_feasible_dt);
+            sep.move_item(pk, new_feasible_dt);
+        }
+    }
+}
+
+/// Whether the item can be placed with the given rotation
+fn allows_rotation(item: &Item, rotation: f32) -> bool {
+    let closest = convert_sample_to_closest_feasible(DTransformation::new(rotation, (0.0, 0.0)), item).rotation();
+    normalize_rotation(closest - rotation).abs() <= DSRP_CLUSTER_ROTATION_TOLERANCE
+}
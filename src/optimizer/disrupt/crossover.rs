@@ -0,0 +1,113 @@
+use crate::consts::XOVR_SIMILAR_AREA_RATIO;
+use crate::optimizer::disrupt::{clamp_into_container, DisruptionContext, Disruptor};
+use crate::optimizer::explore::select_from_pool;
+use crate::optimizer::separator::Separator;
+use itertools::Itertools;
+use jagua_rs::entities::PItemKey;
+use jagua_rs::geometry::primitives::Point;
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::spp::entities::SPSolution;
+use log::info;
+use ordered_float::OrderedFloat;
+use rand::prelude::SliceRandom;
+use rand::prelude::IteratorRandom;
+use rand::RngExt;
+
+/// Recombines the selected solution with a second solution from the infeasible solution pool.
+pub struct Crossover {
+    /// Standard deviation of the distribution used to select the second solution from the pool
+    pub pool_distribution_stddev: f32,
+}
+
+impl Disruptor for Crossover {
+    fn is_applicable(&self, sep: &Separator, ctx: &DisruptionContext) -> bool {
+        ctx.pool.len() >= 2 && sep.prob.layout.placed_items.len() >= 2
+    }
+
+    fn disrupt(&self, sep: &mut Separator, ctx: &DisruptionContext) {
+        // Select a second, different, solution from the pool to recombine with
+        let mut other_idx = select_from_pool(ctx.pool.len(), self.pool_distribution_stddev, &mut sep.rng);
+        if other_idx == ctx.selected_idx {
+            other_idx = (0..ctx.pool.len())
+                .filter(|i| *i != ctx.selected_idx)
+                .choose(&mut sep.rng)
+                .unwrap();
+        }
+        crossover(sep, &ctx.pool[other_idx].0);
+    }
+}
+
+/// Recombines the separator's current layout (parent A) with another layout (parent B) of the same width.
+/// The child keeps all items of A left of a random vertical cut and takes the placements of B right of the cut.
+/// Items are reassigned per item type, so the demand of every item remains satisfied.
+/// Items of A for which B has no placement of the same type left take over an unused placement of B of a similar size,
+/// or otherwise stay in place.
+/// Any overlap introduced by the recombination is left for the separator to repair.
+pub fn crossover(sep: &mut Separator, parent_b: &SPSolution) {
+    assert_eq!(parent_b.strip_width(), sep.prob.strip_width(), "crossover requires parents of the same strip width");
+
+    let cut = sep.rng.random_range(0.0..sep.prob.strip_width());
+
+    // Placements of B right of the cut, grouped per item type
+    let mut b_right: Vec<Vec<Slot>> = vec![vec![]; sep.instance.items.len()];
+    parent_b.layout_snapshot.placed_items.values()
+        .filter(|pi| pi.shape.centroid().0 >= cut)
+        .for_each(|pi| b_right[pi.item_id].push(Slot { d_transf: pi.d_transf, centroid: pi.shape.centroid(), area: pi.shape.area }));
+
+    // Items of A right of the cut, grouped per item type
+    let mut a_right: Vec<Vec<PItemKey>> = vec![vec![]; sep.instance.items.len()];
+    sep.prob.layout.placed_items.iter()
+        .filter(|(_, pi)| pi.shape.centroid().0 >= cut)
+        .for_each(|(pk, pi)| a_right[pi.item_id].push(pk));
+
+    info!("[XOVR] recombining layouts at x: {:.3} ({:.1}%)", cut, cut / sep.prob.strip_width() * 100.0);
+
+    // Move A's items right of the cut to B's placements of the same item type.
+    // If B has more placements of that type, a random subset of them is used.
+    let mut moves = vec![];
+    let (mut a_leftover, mut b_unused) = (vec![], vec![]);
+    for (a_pks, mut b_slots) in a_right.into_iter().zip(b_right) {
+        b_slots.shuffle(&mut sep.rng);
+        let n = usize::min(a_pks.len(), b_slots.len());
+        moves.extend(a_pks[..n].iter().zip(&b_slots[..n]).map(|(pk, slot)| (*pk, slot.d_transf)));
+        a_leftover.extend_from_slice(&a_pks[n..]);
+        b_unused.extend_from_slice(&b_slots[n..]);
+    }
+    let n_same_type = moves.len();
+
+    // The remaining items of A would end up on top of B's transplanted items,
+    // move them (in their current rotation) to the centroid of an unused placement of B with the most similar area instead
+    let mut n_unmatched = 0;
+    for pk in a_leftover {
+        let pi = &sep.prob.layout.placed_items[pk];
+        let (item_id, d_transf, centroid, area) = (pi.item_id, pi.d_transf, pi.shape.centroid(), pi.shape.area);
+        let closest = b_unused.iter()
+            .position_min_by_key(|slot| OrderedFloat((slot.area - area).abs()))
+            .filter(|&i| f32::min(b_unused[i].area, area) >= f32::max(b_unused[i].area, area) * XOVR_SIMILAR_AREA_RATIO);
+        match closest {
+            Some(i) => {
+                let slot = b_unused.swap_remove(i);
+                let dt = d_transf.compose()
+                    .translate((slot.centroid.0 - centroid.0, slot.centroid.1 - centroid.1))
+                    .decompose();
+                moves.push((pk, clamp_into_container(sep, item_id, dt)));
+            }
+            None => n_unmatched += 1,
+        }
+    }
+
+    info!("[XOVR] moving {} items to placements of B of the same type and {} to placements of a similar size, {} items of A unmatched, {} placements of B unused",
+        n_same_type, moves.len() - n_same_type, n_unmatched, b_unused.len());
+
+    for (pk, dt) in moves {
+        sep.move_item(pk, dt);
+    }
+}
+
+/// A placement of parent B
+#[derive(Clone, Copy)]
+struct Slot {
+    d_transf: DTransformation,
+    centroid: Point,
+    area: f32,
+}
@@ -0,0 +1,181 @@
+use crate::config::{DisruptionStrategy, DisruptorSelection, ExplorationConfig};
+use crate::consts::{ADAPTIVE_DSRP_MIN_WEIGHT, ADAPTIVE_DSRP_REACTION};
+use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::eval::sep_evaluator::SeparationEvaluator;
+use crate::optimizer::disrupt::band::{ReflectPositions, RuinRecreateBand};
+use crate::optimizer::disrupt::cluster::RotateCluster;
+use crate::optimizer::disrupt::crossover::Crossover;
+use crate::optimizer::disrupt::replace::RandomReplace;
+use crate::optimizer::disrupt::swap::LargeItemSwap;
+use crate::optimizer::separator::Separator;
+use crate::sample::uniform_sampler::UniformBBoxSampler;
+use crate::zones::forbidden_zones;
+use itertools::Itertools;
+use jagua_rs::entities::{Instance, PItemKey};
+use jagua_rs::geometry::geo_traits::TransformableFrom;
+use jagua_rs::geometry::primitives::Rect;
+use jagua_rs::geometry::DTransformation;
+use jagua_rs::probs::spp::entities::SPSolution;
+use log::{debug, info, warn};
+use rand::prelude::IndexedRandom;
+use rand::RngExt;
+
+pub mod band;
+pub mod cluster;
+pub mod crossover;
+pub mod replace;
+pub mod swap;
+
+/// Strategy to disrupt a solution during the exploration phase, in order to escape local minima.
+pub trait Disruptor: Send + Sync {
+    /// Checks whether the strategy can be applied to the separator's current solution
+    fn is_applicable(&self, sep: &Separator, _ctx: &DisruptionContext) -> bool {
+        sep.prob.layout.placed_items.len() >= 2
+    }
+
+    /// Disrupts the separator's current solution
+    fn disrupt(&self, sep: &mut Separator, ctx: &DisruptionContext);
+}
+
+/// Information about the exploration phase available to the disruptors
+pub struct DisruptionContext<'a> {
+    /// Pool of infeasible solutions at the current width, sorted by increasing loss
+    pub pool: &'a [(SPSolution, f32)],
+    /// Index of the solution in the pool that is being disrupted
+    pub selected_idx: usize,
+}
+
+pub fn build_disruptor(strategy: DisruptionStrategy, config: &ExplorationConfig) -> Box<dyn Disruptor> {
+    match strategy {
+        DisruptionStrategy::LargeItemSwap => Box::new(LargeItemSwap { ch_area_cutoff_percentile: config.large_item_ch_area_cutoff_percentile }),
+        DisruptionStrategy::Crossover => Box::new(Crossover { pool_distribution_stddev: config.solution_pool_distribution_stddev }),
+        DisruptionStrategy::RuinRecreateBand => Box::new(RuinRecreateBand),
+        DisruptionStrategy::RotateCluster => Box::new(RotateCluster),
+        DisruptionStrategy::ReflectPositions => Box::new(ReflectPositions),
+        DisruptionStrategy::RandomReplace => Box::new(RandomReplace),
+    }
+}
+
+/// Selects which disruption strategy to apply and keeps track of their success.
+pub struct DisruptorSelector {
+    pub strategies: Vec<(DisruptionStrategy, Box<dyn Disruptor>)>,
+    pub selection: DisruptorSelection,
+    /// Selection weights of every strategy (only used for adaptive selection)
+    pub weights: Vec<f32>,
+    /// Index of the last applied strategy, whose outcome has not yet been reported
+    pending: Option<usize>,
+}
+
+impl DisruptorSelector {
+    pub fn new(config: &ExplorationConfig) -> Self {
+        assert!(!config.disruption.strategies.is_empty(), "at least one disruption strategy should be configured");
+        let strategies = config.disruption.strategies.iter()
+            .map(|s| (*s, build_disruptor(*s, config)))
+            .collect_vec();
+        let weights = vec![1.0; strategies.len()];
+
+        Self {
+            strategies,
+            selection: config.disruption.selection,
+            weights,
+            pending: None,
+        }
+    }
+
+    /// Selects an applicable strategy and uses it to disrupt the separator's current solution.
+    pub fn disrupt(&mut self, sep: &mut Separator, ctx: &DisruptionContext) {
+        let applicable = (0..self.strategies.len())
+            .filter(|i| self.strategies[*i].1.is_applicable(sep, ctx))
+            .collect_vec();
+
+        let selected = match (applicable.len(), self.selection) {
+            (0, _) => {
+                warn!("[DSRP] no applicable disruption strategy");
+                return;
+            }
+            (1, _) => applicable[0],
+            (_, DisruptorSelection::Random) => *applicable.choose(&mut sep.rng).unwrap(),
+            (_, DisruptorSelection::Adaptive) => *applicable.choose_weighted(&mut sep.rng, |i| self.weights[*i]).unwrap(),
+        };
+
+        let (strategy, disruptor) = &self.strategies[selected];
+        debug!("[DSRP] applying {:?}", strategy);
+        disruptor.disrupt(sep, ctx);
+        self.pending = Some(selected);
+    }
+
+    /// Reports whether the separation following the last disruption led to a feasible solution.
+    pub fn report_outcome(&mut self, feasible: bool) {
+        if let Some(idx) = self.pending.take() && self.selection == DisruptorSelection::Adaptive {
+            let score = if feasible { 1.0 } else { 0.0 };
+            let w = &mut self.weights[idx];
+            *w = ((1.0 - ADAPTIVE_DSRP_REACTION) * *w + ADAPTIVE_DSRP_REACTION * score).max(ADAPTIVE_DSRP_MIN_WEIGHT);
+            if feasible {
+                info!("[DSRP] {:?} led to a feasible solution, weights: {:?}", self.strategies[idx].0,
+                    self.strategies.iter().zip(&self.weights).map(|((s, _), w)| format!("{s:?}: {w:.2}")).collect_vec());
+            }
+        }
+    }
+
+    /// Strategy applied by the last disruption, if its outcome has not yet been reported
+    pub fn pending_strategy(&self) -> Option<DisruptionStrategy> {
+        self.pending.map(|idx| self.strategies[idx].0)
+    }
+
+    /// Forgets the outcome of the last disruption.
+    pub fn clear_pending(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// Generates a random vertical band spanning the full height of the strip, with a width relative to the strip's width.
+/// Ratios of `1.0` or more result in a band covering the entire strip.
+fn random_band(sep: &mut Separator, width_ratio: f32) -> Rect {
+    let c_bbox = sep.prob.layout.container.outer_cd.bbox;
+    let band_width = c_bbox.width() * width_ratio.min(1.0);
+    let x_min = match band_width < c_bbox.width() {
+        true => sep.rng.random_range(c_bbox.x_min..(c_bbox.x_max - band_width)),
+        false => c_bbox.x_min,
+    };
+    Rect::try_new(x_min, c_bbox.y_min, x_min + band_width, c_bbox.y_max).unwrap()
+}
+
+/// Collects all items whose centroid lies within the horizontal range of the band.
+fn items_in_band(sep: &Separator, band: Rect) -> Vec<PItemKey> {
+    sep.prob.layout.placed_items.iter()
+        .filter(|(_, pi)| (band.x_min..band.x_max).contains(&pi.shape.centroid().0))
+        .map(|(pk, _)| pk)
+        .collect_vec()
+}
+
+/// Evaluates `n_samples` uniform samples within `sample_bbox` for the item and returns the best one.
+fn best_sample_in(sep: &mut Separator, pk: PItemKey, sample_bbox: Rect, n_samples: usize) -> Option<DTransformation> {
+    let item = sep.instance.item(sep.prob.layout.placed_items[pk].item_id);
+    let sampler = UniformBBoxSampler::new(sample_bbox, item, sep.prob.layout.container.outer_cd.bbox)?
+        .with_forbidden_zones(forbidden_zones(&sep.prob.layout.container, item.min_quality));
+    let mut evaluator = SeparationEvaluator::new(&sep.prob.layout, item, pk, &sep.ct);
+
+    let mut best: Option<(DTransformation, SampleEval)> = None;
+    for _ in 0..n_samples {
+        let dt = sampler.sample(&mut sep.rng);
+        let eval = evaluator.evaluate_sample(dt, best.map(|(_, e)| e));
+        if best.is_none_or(|(_, best_eval)| eval < best_eval) {
+            best = Some((dt, eval));
+        }
+    }
+    best.map(|(dt, _)| dt)
+}
+
+/// Translates a transformation of an item such that its bounding box lies within the container (as far as possible).
+fn clamp_into_container(sep: &Separator, item_id: usize, dt: DTransformation) -> DTransformation {
+    let item = sep.instance.item(item_id);
+    let c_bbox = sep.prob.layout.container.outer_cd.bbox;
+    let mut shape = item.shape_cd.as_ref().clone();
+    let bbox = shape.transform_from(&item.shape_cd, &dt.compose()).bbox;
+
+    let dx = f32::max(c_bbox.x_min - bbox.x_min, 0.0) + f32::min(c_bbox.x_max - bbox.x_max, 0.0);
+    let dy = f32::max(c_bbox.y_min - bbox.y_min, 0.0) + f32::min(c_bbox.y_max - bbox.y_max, 0.0);
+
+    let (tx, ty) = dt.translation();
+    DTransformation::new(dt.rotation(), (tx + dx, ty + dy))
+}
@@ -0,0 +1,24 @@
+use crate::consts::DSRP_N_REPLACED_ITEMS;
+use crate::optimizer::disrupt::{best_sample_in, DisruptionContext, Disruptor};
+use crate::optimizer::separator::Separator;
+use log::info;
+use rand::prelude::IteratorRandom;
+
+/// Re-places a number of random items at uniformly random positions in the strip.
+pub struct RandomReplace;
+
+impl Disruptor for RandomReplace {
+    fn disrupt(&self, sep: &mut Separator, _ctx: &DisruptionContext) {
+        let replaced = sep.prob.layout.placed_items.keys()
+            .sample(&mut sep.rng, DSRP_N_REPLACED_ITEMS);
+        let c_bbox = sep.prob.layout.container.outer_cd.bbox;
+
+        info!("[DSRP] randomly re-placing {} items", replaced.len());
+
+        for pk in replaced {
+            if let Some(dt) = best_sample_in(sep, pk, c_bbox, 1) {
+                sep.move_item(pk, dt);
+            }
+        }
+    }
+}
@@ -1,240 +1,181 @@
+use crate::bounds::{min_strip_height, LowerBounds};
 use crate::config::ExplorationConfig;
+use crate::optimizer::disrupt::{DisruptionContext, DisruptorSelector};
+use crate::optimizer::open_dim::shrink_factors;
 use crate::optimizer::separator::{Separator, SeparatorConfig};
-use crate::sample::uniform_sampler::convert_sample_to_closest_feasible;
 use crate::util::listener::{ReportType, SolutionListener};
 use crate::util::terminator::Terminator;
 use crate::FMT;
-use float_cmp::approx_eq;
-use itertools::Itertools;
-use jagua_rs::collision_detection::hazards::HazardEntity;
-use jagua_rs::entities::{Instance, Layout, PItemKey};
-use jagua_rs::geometry::geo_traits::CollidesWith;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
-use log::{debug, info, warn};
-use ordered_float::OrderedFloat;
-use rand::prelude::{Distribution, IteratorRandom};
+use log::{debug, info};
+use rand::prelude::Distribution;
+use rand::Rng;
 use rand_distr::Normal;
-use slotmap::SecondaryMap;
-use std::cmp::Reverse;
 
 /// Algorithm 12 from https://doi.org/10.48550/arXiv.2509.13329
 pub fn exploration_phase(instance: &SPInstance, sep: &mut Separator, sol_listener: &mut impl SolutionListener, term: &impl Terminator, config: &ExplorationConfig) -> Vec<SPSolution> {
-    let mut current_width = sep.prob.strip_width();
-    let mut best_width = current_width;
+    let mut state = ExplorationState::new(instance, sep, sol_listener, config, LowerBounds::new(instance));
 
-    let mut feasible_sols = vec![sep.prob.save()];
+    while !term.kill() && state.step(instance, sep, sol_listener, term, config) {}
 
-    sol_listener.report(ReportType::ExplFeas, &feasible_sols[0], instance);
-    info!("[EXPL] starting optimization with initial width: {:.3} ({:.3}%)",current_width,sep.prob.density() * 100.0);
+    state.finish(instance)
+}
+
+/// State of the exploration phase, which can be advanced one separation attempt at a time.
+/// Allows the phase to be interleaved with other work, such as migrating solutions between islands.
+pub struct ExplorationState {
+    pub current_width: f32,
+    pub best_width: f32,
+    /// Height of the strip, only changes in open-dimension mode
+    pub current_height: f32,
+    pub best_height: f32,
+    /// All feasible solutions found so far, in order of decreasing width
+    pub feasible_sols: Vec<SPSolution>,
+    /// Infeasible solutions at the current width, sorted by increasing loss
+    pub infeas_sol_pool: Vec<(SPSolution, f32)>,
+    pub disruptor: DisruptorSelector,
+    pub lower_bounds: LowerBounds,
+    /// Number of times the strip was shrunk, used to alternate between the dimensions in open-dimension mode
+    n_shrinks: usize,
+    /// Minimum height of the strip in which all items fit
+    min_height: f32,
+}
 
-    let mut infeas_sol_pool: Vec<(SPSolution, f32)> = vec![];
+impl ExplorationState {
+    /// Starts the exploration from the separator's current (feasible) solution.
+    pub fn new(instance: &SPInstance, sep: &Separator, sol_listener: &mut impl SolutionListener, config: &ExplorationConfig, lower_bounds: LowerBounds) -> Self {
+        let current_width = sep.prob.strip_width();
+        let current_height = sep.prob.strip.fixed_height;
+        let feasible_sols = vec![sep.prob.save()];
+
+        sol_listener.report(ReportType::ExplFeas, &feasible_sols[0], instance);
+        info!("[EXPL] starting optimization with initial width: {:.3} ({:.3}%)",current_width,sep.prob.density() * 100.0);
+
+        Self {
+            current_width,
+            best_width: current_width,
+            current_height,
+            best_height: current_height,
+            feasible_sols,
+            infeas_sol_pool: vec![],
+            disruptor: DisruptorSelector::new(config),
+            lower_bounds,
+            n_shrinks: 0,
+            min_height: min_strip_height(instance),
+        }
+    }
 
-    while !term.kill() {
+    /// Performs a single separation attempt at the current width, followed by either a shrink or a disruption.
+    /// Returns `false` if the exploration phase should not be continued.
+    pub fn step(&mut self, instance: &SPInstance, sep: &mut Separator, sol_listener: &mut impl SolutionListener, term: &impl Terminator, config: &ExplorationConfig) -> bool {
         // Attempt to separate the current layout
         let local_best = sep.separate(term, sol_listener);
         let total_loss = local_best.1.get_total_loss();
 
         if total_loss == 0.0 {
             // If successfully separated
-            if current_width < best_width {
-                info!("[EXPL] feasible solution found! (width: {:.3}, dens: {:.3}%)",current_width,sep.prob.density() * 100.0);
-                best_width = current_width;
-                feasible_sols.push(local_best.0.clone());
+            self.disruptor.report_outcome(true);
+            if self.current_width * self.current_height < self.best_area() {
+                info!("[EXPL] feasible solution found! (width: {:.3}, dens: {:.3}%, gap: {:.3}%)",self.current_width,sep.prob.density() * 100.0, self.lower_bounds.gap(self.current_width) * 100.0);
+                self.best_width = self.current_width;
+                self.best_height = self.current_height;
+                self.feasible_sols.push(local_best.0.clone());
                 sol_listener.report(ReportType::ExplFeas, &local_best.0, instance);
             }
+            if config.open_dimension.is_none() && self.lower_bounds.is_reached(self.best_width) {
+                info!("[EXPL] lower bound reached ({:.3}), terminating", self.lower_bounds.best());
+                return false;
+            }
             // Shrink the strip width and clear the infeasible solution pool
-            let next_width = current_width * (1.0 - config.shrink_step);
-            info!("[EXPL] shrinking strip by {}%: {:.3} -> {:.3}", config.shrink_step * 100.0, current_width, next_width);
-            sep.change_strip_width(next_width, None);
-            current_width = next_width;
-            infeas_sol_pool.clear();
+            self.shrink(sep, config);
+        } else if term.kill() {
+            // The separation attempt was interrupted, leave the separator as is so the attempt can be resumed
+            debug!("[EXPL] separation interrupted (width: {:.3}, min loss: {})", self.current_width, FMT().fmt2(total_loss));
         } else {
-            info!("[EXPL] unable to reach feasibility (width: {:.3}, dens: {:.3}%, min loss: {:.3})", current_width, sep.prob.density() * 100.0, FMT().fmt2(total_loss));
+            self.disruptor.report_outcome(false);
+            info!("[EXPL] unable to reach feasibility (width: {:.3}, dens: {:.3}%, min loss: {:.3})", self.current_width, sep.prob.density() * 100.0, FMT().fmt2(total_loss));
             sol_listener.report(ReportType::ExplInfeas, &local_best.0, instance);
 
             // Separation was not successful add it to the pool of infeasible solutions
-            match infeas_sol_pool.binary_search_by(|(_, o)| o.partial_cmp(&total_loss).unwrap()) {
-                Ok(idx) | Err(idx) => infeas_sol_pool.insert(idx, (local_best.0.clone(), total_loss)),
+            match self.infeas_sol_pool.binary_search_by(|(_, o)| o.partial_cmp(&total_loss).unwrap()) {
+                Ok(idx) | Err(idx) => self.infeas_sol_pool.insert(idx, (local_best.0.clone(), total_loss)),
             }
 
-            if infeas_sol_pool.len() >= config.max_conseq_failed_attempts.unwrap_or(usize::MAX) {
-                info!("[EXPL] max consecutive failed attempts ({}), terminating", infeas_sol_pool.len());
-                break;
+            if self.infeas_sol_pool.len() >= config.max_conseq_failed_attempts.unwrap_or(usize::MAX) {
+                info!("[EXPL] max consecutive failed attempts ({}), terminating", self.infeas_sol_pool.len());
+                return false;
             }
 
             // Restore to a random solution from the pool, with better solutions having more chance to be selected
-            let selected_sol = {
-                // Sample a value in range [0.0, 1.0[ from a normal distribution
-                let distribution = Normal::new(0.0, config.solution_pool_distribution_stddev).unwrap();
-                let sample = distribution.sample(&mut sep.rng).abs().min(0.999);
-                // Map it to an index in the infeasible solution pool (better solutions are at the start of the pool)
-                let selected_idx = (sample * infeas_sol_pool.len() as f32) as usize;
-
-                let (selected_sol, loss) = &infeas_sol_pool[selected_idx];
-                info!("[EXPL] starting solution {}/{} selected from solution pool (l: {}) to disrupt", selected_idx, infeas_sol_pool.len(), FMT().fmt2(*loss));
-                selected_sol
-            };
+            let selected_idx = select_from_pool(self.infeas_sol_pool.len(), config.solution_pool_distribution_stddev, &mut sep.rng);
+            let (selected_sol, loss) = &self.infeas_sol_pool[selected_idx];
+            info!("[EXPL] starting solution {}/{} selected from solution pool (l: {}) to disrupt", selected_idx, self.infeas_sol_pool.len(), FMT().fmt2(*loss));
 
             // Rollback to this solution and disrupt it.
             sep.rollback(selected_sol, None);
-            disrupt_solution(sep, config);
+            let ctx = DisruptionContext { pool: &self.infeas_sol_pool, selected_idx };
+            self.disruptor.disrupt(sep, &ctx);
         }
+        true
     }
 
-    info!("[EXPL] finished, best feasible solution: width: {:.3} ({:.3}%)",best_width,feasible_sols.last().unwrap().density(instance) * 100.0);
-
-    feasible_sols
-}
+    /// Adopts a feasible solution that is narrower than the best one found so far, and continues exploring from it.
+    pub fn adopt(&mut self, instance: &SPInstance, sep: &mut Separator, sol: &SPSolution, sol_listener: &mut impl SolutionListener, config: &ExplorationConfig) {
+        debug_assert!(sol.strip_width() * sol.strip.fixed_height < self.best_area());
+        info!("[EXPL] adopting feasible solution (width: {:.3} -> {:.3}, dens: {:.3}%)", self.best_width, sol.strip_width(), sol.density(instance) * 100.0);
+
+        sep.change_strip_width(sol.strip_width(), None);
+        sep.change_strip_height(sol.strip.fixed_height, None);
+        sep.rollback(sol, None);
+        self.current_width = sol.strip_width();
+        self.best_width = sol.strip_width();
+        self.current_height = sol.strip.fixed_height;
+        self.best_height = sol.strip.fixed_height;
+        self.feasible_sols.push(sol.clone());
+        sol_listener.report(ReportType::ExplFeas, sol, instance);
+
+        self.shrink(sep, config);
+    }
 
-fn disrupt_solution(sep: &mut Separator, config: &ExplorationConfig) {
-    if sep.prob.layout.placed_items.len() < 2 {
-        warn!("[DSRP] cannot disrupt solution with less than 2 items");
-        return;
+    /// Area of the strip of the best feasible solution
+    pub fn best_area(&self) -> f32 {
+        self.best_width * self.best_height
     }
 
-    // The general idea is to disrupt a solution by swapping two 'large' items in the layout.
-    // 'Large' items are those whose convex hull area falls within a certain top percentile
-    // of the total convex hull area of all items in the layout.
-
-    // Step 1: Define what constitutes a 'large' item.
-
-    // Calculate the total convex hull area of all items, considering quantities.
-    let total_convex_hull_area: f32 = sep
-        .prob
-        .instance
-        .items
-        .iter()
-        .map(|(item, quantity)| item.shape_cd.surrogate().convex_hull_area * (*quantity as f32))
-        .sum();
-
-    let cutoff_threshold_area = total_convex_hull_area * config.large_item_ch_area_cutoff_percentile;
-
-    // Sort items by convex hull area in descending order.
-    let sorted_items_by_ch_area = sep
-        .prob
-        .instance
-        .items
-        .iter()
-        .sorted_by_key(|(item, _)| Reverse(OrderedFloat(item.shape_cd.surrogate().convex_hull_area)))
-        .peekable();
-
-    let mut cumulative_ch_area = 0.0;
-    let mut ch_area_cutoff = 0.0;
-
-    // Iterate through items, accumulating their convex hull areas until the cumulative sum
-    // exceeds the cutoff_threshold_area. The convex hull area of the item that causes
-    // this excess becomes the ch_area_cutoff.
-    for (item, quantity) in sorted_items_by_ch_area {
-        let item_ch_area = item.shape_cd.surrogate().convex_hull_area;
-        cumulative_ch_area += item_ch_area * (*quantity as f32);
-        if cumulative_ch_area > cutoff_threshold_area {
-            ch_area_cutoff = item_ch_area;
-            debug!("[DSRP] cutoff ch area: {}, for item id: {}, bbox: {:?}",ch_area_cutoff, item.id, item.shape_cd.bbox);
-            break;
-        }
+    pub fn best_feasible(&self) -> &SPSolution {
+        self.feasible_sols.last().unwrap()
     }
 
-    // Step 2: Select two 'large' items and 'swap' them.
-
-    let large_items = sep.prob.layout.placed_items.iter()
-        .filter(|(_, pi)| pi.shape.surrogate().convex_hull_area >= ch_area_cutoff);
-
-    //Choose a first item with a large enough convex hull
-    let (pk1, pi1) = large_items.clone().choose(&mut sep.rng).expect("[DSRP] failed to choose first item");
-
-    //Choose a second item with a large enough convex hull and different enough from the first.
-    //If no such item is found, choose a random one.
-    let (pk2, pi2) = large_items.clone()
-        .filter(|(_, pi)|
-            // Ensure the second item is different from the first
-            !approx_eq!(f32, pi.shape.area,pi1.shape.area, epsilon = pi1.shape.area * 0.01) &&
-                !approx_eq!(f32, pi.shape.diameter, pi1.shape.diameter, epsilon = pi1.shape.diameter * 0.01)
-        )
-        .choose(&mut sep.rng)
-        .or_else(|| {
-            sep.prob.layout.placed_items.iter()
-                .filter(|(pk, _)| *pk != pk1) // Ensure the second item is not the same as the first
-                .choose(&mut sep.rng)
-        }) // As a fallback, choose any item
-        .expect("[EXPL] failed to choose second item for disruption");
-
-    // Step 3: Swap the two items' positions in the layout.
-
-    let dt1_old = pi1.d_transf;
-    let dt2_old = pi2.d_transf;
-
-    // Make sure the swaps do not violate feasibility (rotation).
-    let dt1_new = convert_sample_to_closest_feasible(dt2_old, sep.prob.instance.item(pi1.item_id));
-    let dt2_new = convert_sample_to_closest_feasible(dt1_old, sep.prob.instance.item(pi2.item_id));
-
-    info!("[EXPL] disrupting by swapping two large items (id: {} <-> {})", pi1.item_id, pi2.item_id);
-
-    let pk1 = sep.move_item(pk1, dt1_new);
-    let pk2 = sep.move_item(pk2, dt2_new);
-
-
-    // Step 4: Move all items that are practically contained by one of the swapped items to the "empty space" created by the moved item.
-    //         This is particularly important when huge items are swapped with smaller items. 
-    //         The huge item will create a large empty space and many of the items which previously 
-    //         surrounded the smaller one will be contained by the huge one.
-    {
-        // transformation to convert the contained items' position (relative to the old and new positions of the swapped items)
-        let converting_transformation = dt1_new.compose().inverse()
-            .transform(&dt1_old.compose());
-
-        for c1_pk in practically_contained_items(&sep.prob.layout, pk1).into_iter().filter(|c1_pk| *c1_pk != pk2) {
-            let c1_pi = &sep.prob.layout.placed_items[c1_pk];
-
-            let new_dt = c1_pi.d_transf
-                .compose()
-                .transform(&converting_transformation)
-                .decompose();
-
-            //Ensure the sure the new position is feasible
-            let new_feasible_dt = convert_sample_to_closest_feasible(new_dt, sep.prob.instance.item(c1_pi.item_id));
-            sep.move_item(c1_pk, new_feasible_dt);
-        }
+    /// Concludes the exploration phase, returning all feasible solutions found.
+    pub fn finish(self, instance: &SPInstance) -> Vec<SPSolution> {
+        info!("[EXPL] finished, best feasible solution: width: {:.3} ({:.3}%)",self.best_width,self.best_feasible().density(instance) * 100.0);
+        self.feasible_sols
     }
 
-    // Do the same for the second item, but using the second transformation
-    {
-        let converting_transformation = dt2_new.compose().inverse()
-            .transform(&dt2_old.compose());
-
-        for c2_pk in practically_contained_items(&sep.prob.layout, pk2).into_iter().filter(|c2_pk| *c2_pk != pk1) {
-            let c2_pi = &sep.prob.layout.placed_items[c2_pk];
-            let new_dt = c2_pi.d_transf
-                .compose()
-                .transform(&converting_transformation)
-                .decompose();
-
-            //make sure the new position is feasible
-            let new_feasible_dt = convert_sample_to_closest_feasible(new_dt, sep.prob.instance.item(c2_pi.item_id));
-            sep.move_item(c2_pk, new_feasible_dt);
+    fn shrink(&mut self, sep: &mut Separator, config: &ExplorationConfig) {
+        let (width_factor, height_factor) = shrink_factors(config.open_dimension, self.n_shrinks, config.shrink_step, self.current_height, self.min_height);
+        let (next_width, next_height) = (self.current_width * width_factor, self.current_height * height_factor);
+        match config.open_dimension {
+            None => info!("[EXPL] shrinking strip by {}%: {:.3} -> {:.3}", config.shrink_step * 100.0, self.current_width, next_width),
+            Some(_) => info!("[EXPL] shrinking strip area by {}%: {:.3} x {:.3} -> {:.3} x {:.3}", config.shrink_step * 100.0, self.current_width, self.current_height, next_width, next_height),
         }
+        if width_factor < 1.0 {
+            sep.change_strip_width(next_width, None);
+        }
+        sep.change_strip_height(next_height, None);
+        self.current_width = next_width;
+        self.current_height = next_height;
+        self.n_shrinks += 1;
+        self.infeas_sol_pool.clear();
+        self.disruptor.clear_pending();
     }
 }
 
-/// Collects all items which point of inaccessibility (POI) is contained by pk_c's shape.
-fn practically_contained_items(layout: &Layout, pk_c: PItemKey) -> Vec<PItemKey> {
-    let pi_c = &layout.placed_items[pk_c];
-    // Detect all collisions with the item pk_c's shape.
-    let mut collector = SecondaryMap::new();
-    layout.cde().collect_poly_collisions(&pi_c.shape, &mut collector);
-
-    // Filter out the items that have their POI contained by pk_c's shape.
-    collector.iter()
-        .filter_map(|(_,he)| {
-            match he {
-                HazardEntity::PlacedItem { pk, .. } => Some(*pk),
-                _ => None
-            }
-        })
-        .filter(|pk| *pk != pk_c) // Ensure we don't include the item itself
-        .filter(|pk| {
-            // Check if the POI of the item is contained by pk_c's shape
-            let poi = layout.placed_items[*pk].shape.poi;
-            pi_c.shape.collides_with(&poi.center)
-        })
-        .collect_vec()
-}
\ No newline at end of file
+/// Samples an index from a solution pool of length `pool_len`, with better solutions (at the start of the pool) having more chance to be selected
+pub fn select_from_pool(pool_len: usize, stddev: f32, rng: &mut impl Rng) -> usize {
+    // Sample a value in range [0.0, 1.0[ from a normal distribution
+    let distribution = Normal::new(0.0, stddev).unwrap();
+    let sample = distribution.sample(rng).abs().min(0.999);
+    // Map it to an index in the pool
+    (sample * pool_len as f32) as usize
+}
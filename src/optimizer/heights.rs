@@ -0,0 +1,269 @@
+use crate::bounds::{items_fit_strip, LowerBounds};
+use crate::config::{CompressionConfig, ExplorationConfig, SparrowConfig};
+use crate::consts::{HEIGHT_DOMINANCE_MARGIN, HEIGHT_SCREENING_RATIO};
+use crate::groups::ItemGroups;
+use crate::optimizer::compact::remnant_area;
+use crate::optimizer::state::{OptimizerState, Phase};
+use crate::optimizer::OptimizeReport;
+use crate::util::io::{ExtSPOutput, ExtStripHeightReport};
+use crate::util::listener::{DummySolListener, ReportType, SolutionListener};
+use crate::util::terminator::Terminator;
+use crate::EPOCH;
+use anyhow::{anyhow, bail, ensure, Result};
+use itertools::Itertools;
+use jagua_rs::io::import::Importer;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::probs::spp::io::ext_repr::ExtSPInstance;
+use log::info;
+use ordered_float::OrderedFloat;
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// A candidate strip (roll or sheet) height, with the cost per unit of area of the strip
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StripHeightOption {
+    pub height: f32,
+    pub cost_per_area: f32,
+}
+
+/// Parses `height:cost_per_area`, or just `height` (with a cost of 1 per unit of area)
+impl FromStr for StripHeightOption {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (height, cost_per_area) = match s.split_once(':') {
+            Some((h, c)) => (h.trim().parse::<f32>()?, c.trim().parse::<f32>()?),
+            None => (s.trim().parse::<f32>()?, 1.0),
+        };
+        ensure!(height > 0.0 && cost_per_area >= 0.0, "invalid strip height option: {s}");
+        Ok(Self { height, cost_per_area })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StripHeightStatus {
+    /// Cheapest layout of all heights
+    Selected,
+    /// Fully optimized, but more expensive than the selected height
+    Optimized,
+    /// Dropped after screening, as it cannot (or is unlikely to) beat the cheapest height
+    Dominated,
+    /// Not all items fit within the height
+    Infeasible,
+}
+
+/// Outcome of a single candidate strip height
+#[derive(Clone)]
+pub struct StripHeightCandidate {
+    pub option: StripHeightOption,
+    pub status: StripHeightStatus,
+    pub instance: SPInstance,
+    /// Best layout found at this height, `None` if infeasible
+    pub solution: Option<SPSolution>,
+    pub lower_bounds: LowerBounds,
+    /// Intermediate results and timings, `None` if the height was not fully optimized
+    pub report: Option<OptimizeReport>,
+}
+
+impl StripHeightCandidate {
+    /// Cost of the used part of the strip
+    pub fn cost(&self) -> Option<f32> {
+        self.solution.as_ref().map(|sol| self.cost_of_width(sol.strip_width()))
+    }
+
+    /// Cost of the narrowest strip any layout at this height could possibly fit in
+    pub fn lower_bound_cost(&self) -> f32 {
+        self.cost_of_width(self.lower_bounds.best())
+    }
+
+    fn cost_of_width(&self, width: f32) -> f32 {
+        width * self.option.height * self.option.cost_per_area
+    }
+}
+
+/// Outcome of [`optimize_strip_heights`]: all candidate heights, one of which is selected
+#[derive(Clone)]
+pub struct StripHeightsResult {
+    pub candidates: Vec<StripHeightCandidate>,
+    /// Index of the selected (cheapest) candidate
+    pub selected: usize,
+}
+
+impl StripHeightsResult {
+    pub fn best(&self) -> &StripHeightCandidate {
+        &self.candidates[self.selected]
+    }
+
+    /// Comparison of all candidate heights, in the order in which they were provided
+    pub fn comparison(&self) -> Vec<ExtStripHeightReport> {
+        self.candidates.iter()
+            .map(|c| ExtStripHeightReport {
+                strip_height: c.option.height,
+                cost_per_area: c.option.cost_per_area,
+                status: c.status,
+                strip_width: c.solution.as_ref().map(|sol| sol.strip_width()),
+                cost: c.cost(),
+                lower_bound_cost: c.lower_bound_cost(),
+            })
+            .collect()
+    }
+
+    /// Converts the selected layout to the JSON output format, including the comparison of all heights
+    pub fn to_ext_output(&self, ext_instance: &ExtSPInstance) -> ExtSPOutput {
+        let best = self.best();
+        let solution = best.solution.as_ref().unwrap();
+        ExtSPOutput {
+            instance: ExtSPInstance { strip_height: best.option.height, ..ext_instance.clone() },
+            solution: jagua_rs::probs::spp::io::export(&best.instance, solution, *EPOCH),
+            remnant_area: Some(remnant_area(solution)),
+            lower_bound: Some(best.lower_bounds.best()),
+            gap: Some(best.lower_bounds.gap(solution.strip_width())),
+            strip_heights: Some(self.comparison()),
+            item_quantities: None,
+            item_groups: None,
+            quality_zones: None,
+            cut_tree: None,
+            guillotine_feasible: None,
+        }
+    }
+
+    fn log_comparison(&self) {
+        info!("[HGT] {:>10} | {:>10} | {:>10} | {:>12} | {:>12} | status", "height", "cost/area", "width", "cost", "lb cost");
+        for c in &self.candidates {
+            let width = c.solution.as_ref().map_or("-".to_string(), |sol| format!("{:.3}", sol.strip_width()));
+            let cost = c.cost().map_or("-".to_string(), |cost| format!("{cost:.3}"));
+            info!("[HGT] {:>10.3} | {:>10.3} | {:>10} | {:>12} | {:>12.3} | {:?}", c.option.height, c.option.cost_per_area, width, cost, c.lower_bound_cost(), c.status);
+        }
+    }
+}
+
+/// Optimizes the instance for multiple candidate strip heights and selects the cheapest layout.
+///
+/// The time limit (or work budget) of the configuration is split over the heights:
+/// first all heights are explored for a short while ([`HEIGHT_SCREENING_RATIO`]), after which the heights that cannot
+/// (or are unlikely to, see [`HEIGHT_DOMINANCE_MARGIN`]) beat the cheapest one are dropped.
+/// The remainder is divided equally over the surviving heights, which are fully optimized one after the other.
+pub fn optimize_strip_heights(
+    ext_instance: &ExtSPInstance,
+    options: &[StripHeightOption],
+    config: &SparrowConfig,
+    mut rng: Xoshiro256PlusPlus,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut (impl Terminator + Sync),
+) -> Result<StripHeightsResult> {
+    ensure!(!options.is_empty(), "no strip heights provided");
+    let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+
+    let mut candidates = options.iter()
+        .map(|&option| {
+            let ext_instance = ExtSPInstance { strip_height: option.height, ..ext_instance.clone() };
+            let instance = jagua_rs::probs::spp::io::import_instance(&importer, &ext_instance)?;
+            let status = match items_fit_strip(&instance) {
+                true => StripHeightStatus::Optimized,
+                false => {
+                    info!("[HGT] not all items fit within height {:.3}", option.height);
+                    StripHeightStatus::Infeasible
+                }
+            };
+            let lower_bounds = LowerBounds::new(&instance);
+            Ok(StripHeightCandidate { option, status, instance, solution: None, lower_bounds, report: None })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let feasible = candidates.iter().positions(|c| c.status != StripHeightStatus::Infeasible).collect_vec();
+    if feasible.is_empty() {
+        bail!("none of the strip heights fit all items");
+    }
+
+    // Screen all heights by exploring each of them for a share of the screening budget
+    let screening_ratio = HEIGHT_SCREENING_RATIO / feasible.len() as f32;
+    info!("[HGT] screening {} strip heights", feasible.len());
+    let mut states = feasible.iter()
+        .map(|&i| {
+            let c = &mut candidates[i];
+            let rng = Xoshiro256PlusPlus::seed_from_u64(rng.next_u64());
+            let mut state = OptimizerState::with_constraints(c.instance.clone(), c.lower_bounds, ItemGroups::default(), &[], rng, &mut DummySolListener, terminator,
+                &scale_expl_config(&config.expl_cfg, screening_ratio), &config.cmpr_cfg, None);
+            run_phase(&mut state, terminator, Phase::Exploration);
+            c.solution = Some(state.best_solution().clone());
+            info!("[HGT] height {:.3} screened, cost: {:.3} (width: {:.3})", c.option.height, c.cost().unwrap(), state.best_solution().strip_width());
+            (i, state)
+        })
+        .collect_vec();
+
+    // Drop the heights that cannot, or are unlikely to, beat the cheapest one
+    let cheapest = *feasible.iter().min_by_key(|&&i| OrderedFloat(candidates[i].cost().unwrap())).unwrap();
+    let cheapest_cost = candidates[cheapest].cost().unwrap();
+    states.retain(|(i, _)| {
+        let c = &mut candidates[*i];
+        let dominated = *i != cheapest && (c.lower_bound_cost() >= cheapest_cost || c.cost().unwrap() > cheapest_cost * (1.0 + HEIGHT_DOMINANCE_MARGIN));
+        if dominated {
+            info!("[HGT] height {:.3} dominated (cost: {:.3}, lower bound: {:.3}, cheapest: {:.3})", c.option.height, c.cost().unwrap(), c.lower_bound_cost(), cheapest_cost);
+            c.status = StripHeightStatus::Dominated;
+        }
+        !dominated
+    });
+
+    // Fully optimize the remaining heights with an equal share of the remaining budget
+    let share = 1.0 / states.len() as f32;
+    for (i, mut state) in states {
+        let c = &mut candidates[i];
+        info!("[HGT] optimizing height {:.3}", c.option.height);
+        state.cmpr_config = scale_cmpr_config(&config.cmpr_cfg, share);
+        if state.phase() == Phase::Exploration {
+            let expl_config = scale_expl_config(&config.expl_cfg, (1.0 - HEIGHT_SCREENING_RATIO) * share);
+            match expl_config.work_budget {
+                Some(budget) => terminator.new_work_budget(budget),
+                None => terminator.new_timeout(expl_config.time_limit),
+            }
+            run_phase(&mut state, terminator, Phase::Exploration);
+            if state.phase() == Phase::Exploration {
+                state.next_phase(&mut DummySolListener, terminator);
+            }
+        }
+        run_phase(&mut state, terminator, Phase::Compression);
+        let (solution, report) = state.finish(&mut DummySolListener, terminator);
+        c.solution = Some(solution);
+        c.report = Some(report);
+    }
+
+    let selected = candidates.iter()
+        .positions(|c| c.status == StripHeightStatus::Optimized)
+        .min_by_key(|&i| OrderedFloat(candidates[i].cost().unwrap()))
+        .ok_or_else(|| anyhow!("no strip height optimized"))?;
+    candidates[selected].status = StripHeightStatus::Selected;
+
+    let result = StripHeightsResult { candidates, selected };
+    result.log_comparison();
+    let best = result.best();
+    info!("[HGT] selected height {:.3} (cost: {:.3})", best.option.height, best.cost().unwrap());
+    sol_listener.report(ReportType::Final, best.solution.as_ref().unwrap(), &best.instance);
+
+    Ok(result)
+}
+
+/// Steps the optimizer until the terminator fires or the phase concludes
+fn run_phase(state: &mut OptimizerState, terminator: &mut (impl Terminator + Sync), phase: Phase) {
+    while !terminator.kill() && state.phase() == phase {
+        state.step(&mut DummySolListener, terminator);
+    }
+}
+
+fn scale_expl_config(config: &ExplorationConfig, ratio: f32) -> ExplorationConfig {
+    ExplorationConfig {
+        time_limit: config.time_limit.mul_f32(ratio),
+        work_budget: config.work_budget.map(|b| b.scale(ratio)),
+        ..*config
+    }
+}
+
+fn scale_cmpr_config(config: &CompressionConfig, ratio: f32) -> CompressionConfig {
+    CompressionConfig {
+        time_limit: config.time_limit.mul_f32(ratio),
+        work_budget: config.work_budget.map(|b| b.scale(ratio)),
+        ..*config
+    }
+}
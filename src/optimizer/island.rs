@@ -0,0 +1,180 @@
+use crate::bounds::LowerBounds;
+use crate::config::{ExplorationConfig, IslandConfig, MigrationTopology, WorkBudget};
+use crate::consts::WORK_BUDGET_EPOCH_RATIO;
+use crate::optimizer::explore::ExplorationState;
+use crate::optimizer::separator::Separator;
+use crate::util::listener::{DummySolListener, ReportType, SolutionListener};
+use crate::util::terminator::{Terminator, WorkCounter};
+use itertools::Itertools;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::Instant;
+use log::info;
+use ordered_float::OrderedFloat;
+use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use std::time::Duration;
+
+/// Independent exploration running on its own separator, at its own strip width.
+pub struct Island {
+    pub sep: Separator,
+    pub state: ExplorationState,
+    /// Whether the exploration of this island is still running
+    pub active: bool,
+}
+
+/// Island-model variant of [`exploration_phase`](crate::optimizer::explore::exploration_phase).
+/// Every separator runs a full exploration in parallel. Every `migration_interval`, the islands are paused
+/// and islands adopt the narrowest feasible solution of their neighbours (as defined by the topology).
+pub fn island_exploration_phase(
+    instance: &SPInstance,
+    seps: Vec<Separator>,
+    sol_listener: &mut impl SolutionListener,
+    term: &(impl Terminator + Sync),
+    config: &ExplorationConfig,
+    island_config: &IslandConfig,
+) -> Vec<SPSolution> {
+    let mut state = IslandExplorationState::new(instance, seps, sol_listener, config, island_config, LowerBounds::new(instance));
+
+    while !term.kill() && state.step(instance, sol_listener, term, config, island_config) {}
+
+    state.finish(instance)
+}
+
+/// State of the island exploration, which can be advanced one epoch (followed by a migration) at a time.
+pub struct IslandExplorationState {
+    pub islands: Vec<Island>,
+    /// Best feasible solutions across all islands, in order of decreasing width
+    pub feasible_sols: Vec<SPSolution>,
+    pub n_epochs: usize,
+}
+
+impl IslandExplorationState {
+    pub fn new(instance: &SPInstance, seps: Vec<Separator>, sol_listener: &mut impl SolutionListener, config: &ExplorationConfig, island_config: &IslandConfig, lower_bounds: LowerBounds) -> Self {
+        let islands = seps.into_iter()
+            .map(|sep| {
+                let state = ExplorationState::new(instance, &sep, &mut DummySolListener, config, lower_bounds);
+                Island { sep, state, active: true }
+            })
+            .collect_vec();
+
+        let feasible_sols = vec![islands[0].state.best_feasible().clone()];
+        sol_listener.report(ReportType::ExplFeas, &feasible_sols[0], instance);
+        info!("[ISL] starting {} islands ({:?} topology, migration every {:?})", islands.len(), island_config.topology, island_config.migration_interval);
+
+        Self { islands, feasible_sols, n_epochs: 0 }
+    }
+
+    /// Lets all islands explore independently until the next migration, and then migrates solutions between them.
+    /// Returns `false` if none of the islands are still active.
+    pub fn step(&mut self, instance: &SPInstance, sol_listener: &mut impl SolutionListener, term: &(impl Terminator + Sync), config: &ExplorationConfig, island_config: &IslandConfig) -> bool {
+        if !self.islands.iter().any(|isl| isl.active) {
+            return false;
+        }
+        let deadline = Instant::now() + island_config.migration_interval;
+        let epoch_budget = config.work_budget.map(|b| b.scale(WORK_BUDGET_EPOCH_RATIO / self.islands.len() as f32));
+        let epoch_work = self.islands.par_iter_mut()
+            .map(|isl| {
+                let epoch_term = EpochTerminator { inner: term, deadline, work: WorkCounter::new(epoch_budget) };
+                while isl.active && !epoch_term.kill() {
+                    isl.active = isl.state.step(instance, &mut isl.sep, &mut DummySolListener, &epoch_term, config);
+                }
+                (epoch_term.work.n_iterations(), epoch_term.work.n_evals())
+            })
+            .collect::<Vec<_>>();
+        // Only count the work against the global budget after the epoch, so it does not depend on the timing of the islands
+        epoch_work.iter().for_each(|&(n_iterations, n_evals)| term.register_work(n_iterations, n_evals));
+        self.n_epochs += 1;
+
+        let best_widths = self.islands.iter().map(|isl| isl.state.best_width).collect_vec();
+        info!("[ISL] epoch {} finished, best widths: [{}]", self.n_epochs, best_widths.iter().map(|w| format!("{w:.3}")).join(", "));
+
+        // Report the best feasible solution across all islands, if it improved
+        let best_island = self.islands.iter()
+            .min_by_key(|isl| OrderedFloat(isl.state.best_area()))
+            .unwrap();
+        let best_sol = self.best_feasible();
+        if best_island.state.best_area() < best_sol.strip_width() * best_sol.strip.fixed_height {
+            let best_sol = best_island.state.best_feasible().clone();
+            info!("[ISL] new best feasible solution (width: {:.3}, dens: {:.3}%)", best_sol.strip_width(), best_sol.density(instance) * 100.0);
+            sol_listener.report(ReportType::ExplFeas, &best_sol, instance);
+            self.feasible_sols.push(best_sol);
+        }
+
+        if !term.kill() {
+            migrate(instance, &mut self.islands, config, island_config.topology);
+        }
+        true
+    }
+
+    pub fn best_feasible(&self) -> &SPSolution {
+        self.feasible_sols.last().unwrap()
+    }
+
+    /// Concludes the island exploration, returning the best feasible solutions found.
+    pub fn finish(self, instance: &SPInstance) -> Vec<SPSolution> {
+        info!("[ISL] finished after {} epochs, best feasible solution: width: {:.3} ({:.3}%)", self.n_epochs, self.best_feasible().strip_width(), self.best_feasible().density(instance) * 100.0);
+        self.feasible_sols
+    }
+}
+
+/// Every island adopts the best feasible solution among its sources, if it is narrower than its own.
+fn migrate(instance: &SPInstance, islands: &mut [Island], config: &ExplorationConfig, topology: MigrationTopology) {
+    let n = islands.len();
+    let emigrants = islands.iter()
+        .map(|isl| isl.state.best_feasible().clone())
+        .collect_vec();
+
+    for (i, isl) in islands.iter_mut().enumerate() {
+        let immigrant = match topology {
+            MigrationTopology::Ring => &emigrants[(i + n - 1) % n],
+            MigrationTopology::FullyConnected => emigrants.iter()
+                .min_by_key(|s| OrderedFloat(s.strip_width() * s.strip.fixed_height))
+                .unwrap(),
+        };
+        if immigrant.strip_width() * immigrant.strip.fixed_height < isl.state.best_area() {
+            info!("[ISL] island {i} adopting solution of width {:.3} (own: {:.3})", immigrant.strip_width(), isl.state.best_width);
+            isl.state.adopt(instance, &mut isl.sep, immigrant, &mut DummySolListener, config);
+            isl.active = true;
+        }
+    }
+}
+
+/// Terminates when either the wrapped terminator fires or the end of the current epoch is reached.
+/// The epoch ends at a deadline, or after a fixed amount of work if the exploration is limited by a work budget.
+struct EpochTerminator<'a, T: Terminator> {
+    inner: &'a T,
+    deadline: Instant,
+    work: WorkCounter,
+}
+
+impl<T: Terminator> Terminator for EpochTerminator<'_, T> {
+    fn kill(&self) -> bool {
+        let epoch_ended = match self.work.budget {
+            Some(_) => self.work.spent(),
+            None => Instant::now() > self.deadline,
+        };
+        epoch_ended || self.inner.kill()
+    }
+
+    fn new_timeout(&mut self, timeout: Duration) {
+        self.deadline = Instant::now() + timeout;
+    }
+
+    fn new_work_budget(&mut self, budget: WorkBudget) {
+        self.work = WorkCounter::new(Some(budget));
+    }
+
+    fn register_work(&self, n_iterations: usize, n_evals: usize) {
+        self.work.register(n_iterations, n_evals);
+    }
+
+    fn work_progress(&self) -> Option<f32> {
+        self.inner.work_progress()
+    }
+
+    fn timeout_at(&self) -> Option<Instant> {
+        match self.inner.timeout_at() {
+            Some(t) => Some(t.min(self.deadline)),
+            None => Some(self.deadline),
+        }
+    }
+}
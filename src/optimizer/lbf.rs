@@ -1,9 +1,12 @@
 use crate::eval::lbf_evaluator::LBFEvaluator;
 use crate::eval::sample_eval::SampleEval;
+use crate::groups::ItemGroups;
+use crate::optimizer::separator::{change_strip_width, fit_strip};
 use crate::sample::search::{search_placement, SampleConfig};
 use crate::util::assertions;
+use crate::zones::set_quality_zones;
 use itertools::Itertools;
-use jagua_rs::entities::Instance;
+use jagua_rs::entities::{InferiorQualityZone, Instance};
 use jagua_rs::probs::spp::entities::{SPInstance, SPPlacement, SPProblem};
 use jagua_rs::Instant;
 use log::debug;
@@ -17,6 +20,8 @@ pub struct LBFBuilder {
     pub prob: SPProblem,
     pub rng: Xoshiro256PlusPlus,
     pub sample_config: SampleConfig,
+    /// Groups of items whose span is limited, respected by every placement
+    pub item_groups: ItemGroups,
 }
 
 impl LBFBuilder {
@@ -32,18 +37,32 @@ impl LBFBuilder {
             prob,
             rng,
             sample_config,
+            item_groups: ItemGroups::default(),
         }
     }
 
+    pub fn with_item_groups(mut self, item_groups: ItemGroups) -> Self {
+        self.item_groups = item_groups;
+        self
+    }
+
+    /// Places the items in a strip with these quality zones
+    pub fn with_quality_zones(mut self, quality_zones: &[InferiorQualityZone]) -> Self {
+        set_quality_zones(&mut self.prob, quality_zones);
+        self
+    }
+
     pub fn construct(mut self) -> Self {
         let start = Instant::now();
         let n_items = self.instance.items.len();
+        // Items of a group are placed consecutively (groups first), otherwise from large to small
         let sorted_item_indices = (0..n_items)
             .sorted_by_cached_key(|id| {
                 let item_shape = self.instance.item(*id).shape_cd.as_ref();
                 let convex_hull_area = item_shape.surrogate().convex_hull_area;
                 let diameter = item_shape.diameter;
-                Reverse(OrderedFloat(convex_hull_area * diameter))
+                let group = self.item_groups.group_of(*id);
+                (group.is_none(), group, Reverse(OrderedFloat(convex_hull_area * diameter)))
             })
             .flat_map(|id| {
                 let missing_qty = self.prob.item_demand_qtys[id];
@@ -53,36 +72,61 @@ impl LBFBuilder {
 
         debug!("[CONSTR] placing items in order: {:?}",sorted_item_indices);
 
-        for item_id in sorted_item_indices {
-            self.place_item(item_id);
+        let item_groups = self.item_groups.clone();
+        for (group, item_ids) in &sorted_item_indices.into_iter().chunk_by(|id| item_groups.group_of(*id)) {
+            match group {
+                None => item_ids.for_each(|item_id| self.place_item(item_id, None)),
+                Some(_) => self.place_group(item_ids.collect()),
+            }
         }
 
-        self.prob.fit_strip();
+        fit_strip(&mut self.prob);
         debug!("[CONSTR] placed all items in width: {:.3} (in {:?})",self.prob.strip_width(), start.elapsed());
         self
     }
 
-    fn place_item(&mut self, item_id: usize) {
-        match self.find_placement(item_id) {
+    /// Places all items of a group, within the group's maximum span.
+    /// If the group does not fit among the items placed so far, it is placed again to the right of them.
+    fn place_group(&mut self, item_ids: Vec<usize>) {
+        let placed = item_ids.iter()
+            .map_while(|&item_id| self.find_placement(item_id, None).map(|p| self.prob.place_item(p)))
+            .collect_vec();
+        if placed.len() < item_ids.len() {
+            let frontier = self.prob.layout.placed_items.values()
+                .map(|pi| pi.shape.bbox.x_max)
+                .fold(0.0, f32::max);
+            debug!("[CONSTR] group does not fit in between the placed items, placing it right of {frontier:.3}");
+            placed.into_iter().for_each(|pk| _ = self.prob.remove_item(pk));
+            // Once the first item is right of the frontier, the group's span keeps the others close to it
+            self.place_item(item_ids[0], Some(frontier));
+            item_ids[1..].iter().for_each(|&item_id| self.place_item(item_id, None));
+        }
+    }
+
+    fn place_item(&mut self, item_id: usize, min_x: Option<f32>) {
+        match self.find_placement(item_id, min_x) {
             Some(p_opt) => {
                 self.prob.place_item(p_opt);
                 debug!("[CONSTR] placing item {}/{} with id {} at [{}]",self.prob.layout.placed_items.len(),self.instance.total_item_qty(),p_opt.item_id,p_opt.d_transf);
             }
             None => {
                 debug!("[CONSTR] failed to place item with id {}, expanding strip width",item_id);
-                self.prob.change_strip_width(self.prob.strip_width() * 1.2);
+                let new_width = self.prob.strip_width() * 1.2;
+                change_strip_width(&mut self.prob, new_width);
                 assert!(assertions::strip_width_is_in_check(&self.prob), "strip-width is running away (>{:.3}), item {item_id} does not seem to fit into the strip", self.prob.strip_width());          
-                self.place_item(item_id);
+                self.place_item(item_id, min_x);
             }
         }
     }
 
-    fn find_placement(&mut self, item_id: usize) -> Option<SPPlacement> {
+    fn find_placement(&mut self, item_id: usize, min_x: Option<f32>) -> Option<SPPlacement> {
         let layout = &self.prob.layout;
         let item = self.instance.item(item_id);
-        let evaluator = LBFEvaluator::new(layout, item);
+        let evaluator = LBFEvaluator::new(layout, item)
+            .with_group_window(self.item_groups.window(layout, item_id, None))
+            .with_min_x(min_x.unwrap_or(f32::NEG_INFINITY));
 
-        let (best_sample, _) = search_placement(layout, item, None, evaluator, self.sample_config, &mut self.rng);
+        let (best_sample, _) = search_placement(layout, item, None, None, evaluator, self.sample_config, &mut self.rng);
 
         match best_sample {
             Some((d_transf, SampleEval::Clear { .. })) => {
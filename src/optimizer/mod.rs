@@ -1,14 +1,8 @@
 use crate::config::*;
-use crate::consts::LBF_SAMPLE_CONFIG;
-use crate::optimizer::compress::compression_phase;
-use crate::optimizer::explore::exploration_phase;
-use crate::optimizer::lbf::LBFBuilder;
-use crate::optimizer::separator::Separator;
-use crate::util::listener::{ReportType, SolutionListener};
+use crate::optimizer::state::OptimizerState;
+use crate::util::listener::SolutionListener;
 use crate::util::terminator::Terminator;
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
-use log::info;
-use rand::{Rng, SeedableRng};
 use std::time::Duration;
 use rand::rngs::Xoshiro256PlusPlus;
 
@@ -17,59 +11,47 @@ pub mod separator;
 mod worker;
 pub mod explore;
 pub mod compress;
+pub mod compact;
+pub mod demand;
+pub mod disrupt;
+pub mod island;
+pub mod heights;
+pub mod open_dim;
+pub mod state;
 
 ///Algorithm 11 from https://doi.org/10.48550/arXiv.2509.13329
 pub fn optimize(
     instance: SPInstance,
-    mut rng: Xoshiro256PlusPlus,
+    rng: Xoshiro256PlusPlus,
     sol_listener: &mut impl SolutionListener,
-    terminator: &mut impl Terminator,
+    terminator: &mut (impl Terminator + Sync),
     expl_config: &ExplorationConfig,
     cmpr_config: &CompressionConfig,
     initial_solution: Option<&SPSolution>
 ) -> SPSolution {
-    let mut next_rng = || Xoshiro256PlusPlus::seed_from_u64(rng.next_u64());
-    
-    // First build an initial solution if none is provided
-    let start_prob = match initial_solution {
-        None => {
-            let builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG).construct();
-            builder.prob
-        }
-        Some(init_sol) => {
-            info!("[OPT] warm starting from provided initial solution");
-            let mut prob = jagua_rs::probs::spp::entities::SPProblem::new(instance.clone());
-            prob.restore(init_sol);
-            prob
-        }
-    };
+    optimize_with_report(instance, rng, sol_listener, terminator, expl_config, cmpr_config, initial_solution).0
+}
 
-    // Begin by executing the exploration phase
-    terminator.new_timeout(expl_config.time_limit);
-    let mut expl_separator = Separator::new(instance.clone(), start_prob, next_rng(), expl_config.separator_config);
-    let solutions = exploration_phase(
-        &instance,
-        &mut expl_separator,
-        sol_listener,
-        terminator,
-        expl_config,
-    );
-    let final_explore_sol = solutions.last().unwrap().clone();
+/// Intermediate results and timings of an optimization run
+#[derive(Debug, Clone)]
+pub struct OptimizeReport {
+    /// Final (feasible) solution of the exploration phase
+    pub exploration_solution: SPSolution,
+    pub exploration_time: Duration,
+    pub compression_time: Duration,
+    pub compaction_time: Duration,
+}
 
-    // Start the compression phase from the final solution from the exploration phase
-    terminator.new_timeout(cmpr_config.time_limit);
-    let mut cmpr_separator = Separator::new(expl_separator.instance, expl_separator.prob, next_rng(), cmpr_config.separator_config);
-    let cmpr_sol = compression_phase(
-        &instance,
-        &mut cmpr_separator,
-        &final_explore_sol,
-        sol_listener,
-        terminator,
-        cmpr_config,
-    );
-
-    sol_listener.report(ReportType::Final, &cmpr_sol, &instance);
-
-    // Return the final compressed solution
-    cmpr_sol
-}
\ No newline at end of file
+/// Same as [`optimize`], but also returns an [`OptimizeReport`]
+pub fn optimize_with_report(
+    instance: SPInstance,
+    rng: Xoshiro256PlusPlus,
+    sol_listener: &mut impl SolutionListener,
+    terminator: &mut (impl Terminator + Sync),
+    expl_config: &ExplorationConfig,
+    cmpr_config: &CompressionConfig,
+    initial_solution: Option<&SPSolution>
+) -> (SPSolution, OptimizeReport) {
+    OptimizerState::new(instance, rng, sol_listener, terminator, expl_config, cmpr_config, initial_solution)
+        .run(sol_listener, terminator)
+}
@@ -0,0 +1,43 @@
+use crate::bounds::min_strip_height;
+use crate::config::OpenDimension;
+use crate::consts::OPEN_DIM_INITIAL_DENSITY;
+use crate::optimizer::separator::{change_strip_height, change_strip_width};
+use jagua_rs::probs::spp::entities::{SPInstance, SPProblem};
+
+/// Factors by which the width and height of the strip are scaled to shrink its area by `ratio`.
+/// `n` is the number of times the strip was shrunk before, used to alternate between the dimensions.
+/// In alternating mode, the width is shrunk instead of the height if the items would no longer fit.
+pub fn shrink_factors(open_dim: Option<OpenDimension>, n: usize, ratio: f32, height: f32, min_height: f32) -> (f32, f32) {
+    match open_dim {
+        None => (1.0 - ratio, 1.0),
+        Some(OpenDimension::Alternating) => match n % 2 == 1 && height * (1.0 - ratio) >= min_height {
+            true => (1.0, 1.0 - ratio),
+            false => (1.0 - ratio, 1.0),
+        },
+        Some(OpenDimension::AspectRatio(_)) => {
+            let f = (1.0 - ratio).sqrt();
+            (f, f)
+        }
+    }
+}
+
+/// Height of the strip in which the initial solution is constructed.
+/// For a fixed aspect ratio, chosen such that a layout of typical density roughly has this ratio.
+pub fn initial_height(instance: &SPInstance, open_dim: Option<OpenDimension>) -> f32 {
+    match open_dim {
+        None | Some(OpenDimension::Alternating) => instance.base_strip.fixed_height,
+        Some(OpenDimension::AspectRatio(ratio)) => {
+            let height = (instance.item_area() / (ratio * OPEN_DIM_INITIAL_DENSITY)).sqrt();
+            height.max(min_strip_height(instance) * 1.001)
+        }
+    }
+}
+
+/// Expands the width or the height of the strip (at the right or the top, so the layout remains unchanged) until it has the given aspect ratio
+pub fn fit_aspect_ratio(prob: &mut SPProblem, ratio: f32) {
+    let (width, height) = (prob.strip_width(), prob.strip.fixed_height);
+    match width / height > ratio {
+        true => change_strip_height(prob, width / ratio),
+        false => change_strip_width(prob, height * ratio),
+    }
+}
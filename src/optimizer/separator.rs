@@ -1,9 +1,13 @@
+use crate::groups::ItemGroups;
 use crate::optimizer::worker::{SepStats, SeparatorWorker};
 use crate::optimizer::Terminator;
 use crate::quantify::tracker::{CTSnapshot, CollisionTracker};
-use crate::sample::search::SampleConfig;
+use crate::sample::adaptive::AdaptiveBudget;
+use crate::sample::gap_sampler::OccupancyGrid;
+use crate::sample::search::{ContainerSampler, SampleConfig};
 use crate::util::assertions::tracker_matches_layout;
 use crate::util::listener::{ReportType, SolutionListener};
+use crate::zones::swap_strip_container;
 use crate::FMT;
 use itertools::Itertools;
 use jagua_rs::entities::PItemKey;
@@ -25,6 +29,8 @@ pub struct SeparatorConfig {
     pub n_workers: usize,
     pub log_level: Level,
     pub sample_config: SampleConfig,
+    /// Number of items above which the collision tracker stores the pairs of items sparsely
+    pub sparse_tracker_threshold: usize,
 }
 
 pub struct Separator {
@@ -35,11 +41,17 @@ pub struct Separator {
     pub workers: Vec<SeparatorWorker>,
     pub config: SeparatorConfig,
     pub thread_pool: Option<ThreadPool>,
+    /// Groups of items whose span is limited, enforced as soft constraints by the collision trackers
+    pub item_groups: ItemGroups,
+    /// Occupancy of the layout, maintained if the gap-aware container sampler is used
+    pub grid: Option<OccupancyGrid>,
 }
 
 impl Separator {
     pub fn new(instance: SPInstance, prob: SPProblem, mut rng: Xoshiro256PlusPlus, config: SeparatorConfig) -> Self {
-        let ct = CollisionTracker::new(&prob.layout);
+        let item_groups = ItemGroups::default();
+        let ct = CollisionTracker::new(&prob.layout, &instance, &item_groups, config.sparse_tracker_threshold);
+        let grid = occupancy_grid(&prob, config.sample_config);
         let workers = (0..config.n_workers).map(|_|
             SeparatorWorker {
                 instance: instance.clone(),
@@ -47,6 +59,8 @@ impl Separator {
                 ct: ct.clone(),
                 rng: Xoshiro256PlusPlus::seed_from_u64(rng.random()),
                 sample_config: config.sample_config,
+                budget: config.sample_config.adaptive.then(|| AdaptiveBudget::new(&instance)),
+                grid: grid.clone(),
             }).collect();
 
         let pool = if cfg!(target_arch = "wasm32") {
@@ -65,9 +79,19 @@ impl Separator {
             workers,
             config,
             thread_pool: pool,
+            item_groups,
+            grid,
         }
     }
 
+    /// Enforces the maximum x-span of the item groups from now on
+    pub fn with_item_groups(mut self, item_groups: ItemGroups) -> Self {
+        self.ct = CollisionTracker::new(&self.prob.layout, &self.instance, &item_groups, self.config.sparse_tracker_threshold);
+        self.workers.iter_mut().for_each(|w| w.ct = self.ct.clone());
+        self.item_groups = item_groups;
+        self
+    }
+
     /// Algorithm 9 from https://doi.org/10.48550/arXiv.2509.13329
     pub fn separate(&mut self, term: &impl Terminator, sol_listener: &mut impl SolutionListener) -> (SPSolution, CTSnapshot) {
         let mut min_loss_sol = (self.prob.save(), self.ct.save());
@@ -76,7 +100,7 @@ impl Separator {
 
         let mut n_strikes = 0;
         let mut n_iter = 0;
-        let mut sep_stats = SepStats { total_moves: 0, total_evals: 0 };
+        let mut sep_stats = SepStats::default();
         let start = Instant::now();
 
         // As long as the strike limit is not reached, and the solution is not yet separated.
@@ -86,9 +110,11 @@ impl Separator {
             let initial_strike_loss = self.ct.get_total_loss();
             debug!("[SEP] [s:{n_strikes},i:{n_iter}]     init_l: {}",FMT().fmt2(initial_strike_loss));
 
-            while n_iter_no_improvement < self.config.iter_no_imprv_limit {
+            while n_iter_no_improvement < self.config.iter_no_imprv_limit && !term.kill() {
                 let (loss_before, w_loss_before) = (self.ct.get_total_loss(), self.ct.get_total_weighted_loss(),);
-                sep_stats += self.move_items_multi();
+                let iter_stats = self.move_items_multi();
+                term.register_work(1, iter_stats.total_evals);
+                sep_stats += iter_stats;
                 let (loss, w_loss) = (self.ct.get_total_loss(), self.ct.get_total_weighted_loss(),);
 
                 debug!("[SEP] [s:{n_strikes},i:{n_iter}] ( ) l: {} -> {}, wl: {} -> {}, (min l: {})", FMT().fmt2(loss_before), FMT().fmt2(loss), FMT().fmt2(w_loss_before), FMT().fmt2(w_loss), FMT().fmt2(min_loss));
@@ -129,14 +155,22 @@ impl Separator {
             self.rollback(&min_loss_sol.0, Some(&min_loss_sol.1));
         }
         let secs = start.elapsed().as_secs_f32();
-        log!(self.config.log_level, "[SEP] finished, evals/s: {} K, evals/move: {}, moves/s: {}, iter/s: {}, #workers: {}, total {:.3}s",
+        log!(self.config.log_level, "[SEP] finished, evals/s: {} K, evals/move: {} ({}% refine), moves/s: {}, iter/s: {}, #workers: {}, total {:.3}s",
             (sep_stats.total_evals as f32/ (1000.0 * secs)) as usize,
             FMT().fmt2(sep_stats.total_evals as f32 / sep_stats.total_moves as f32),
+            FMT().fmt2(100.0 * sep_stats.refine_evals as f32 / sep_stats.total_evals as f32),
             FMT().fmt2(sep_stats.total_moves as f32 / secs),
             FMT().fmt2(n_iter as f32 / secs),
             self.workers.len(),
             FMT().fmt2(secs),
         );
+        if let Some((min, max)) = sep_stats.item_evals.iter().filter(|&&n| n > 0).minmax().into_option() {
+            log!(self.config.log_level, "[SEP] evals/item: min {}, avg {}, max {}",
+                FMT().fmt2(*min as f32),
+                FMT().fmt2(sep_stats.total_evals as f32 / sep_stats.item_evals.iter().filter(|&&n| n > 0).count() as f32),
+                FMT().fmt2(*max as f32),
+            );
+        }
 
         // Return the best solution found: a feasible one if separation was successful, otherwise the 'least' infeasible one
         (min_loss_sol.0, min_loss_sol.1)
@@ -150,7 +184,7 @@ impl Separator {
         let mut separate_multi = || -> SepStats {
             self.workers.par_iter_mut().map(|worker| {
                 // Sync the workers with the master
-                worker.load(&master_sol, &self.ct);
+                worker.load(&master_sol, &self.ct, self.grid.as_ref());
                 // Let all of them run `move_items` with unique random orderings in which the items are moved
                 worker.move_items()
             }).sum()
@@ -165,14 +199,15 @@ impl Separator {
         debug!("[MOD] optimizers w_o's: {:?}",self.workers.iter().map(|opt| opt.ct.get_total_weighted_loss()).collect_vec());
 
         // Check what run yielded the best solution (lowest collision quantification)
-        let (best_sol, best_ct) = self.workers.iter_mut()
+        let (best_sol, best_ct, best_grid) = self.workers.iter_mut()
             .min_by_key(|opt| OrderedFloat(opt.ct.get_total_weighted_loss()))
-            .map(|opt| (opt.prob.save(), &opt.ct))
+            .map(|opt| (opt.prob.save(), &opt.ct, &opt.grid))
             .unwrap();
 
         // Load this 'best' solution into the master, effectively throwing away all other work.
         self.prob.restore(&best_sol);
         self.ct = best_ct.clone();
+        self.grid.clone_from(best_grid);
 
         sep_report
     }
@@ -180,6 +215,7 @@ impl Separator {
     pub fn rollback(&mut self, sol: &SPSolution, ots: Option<&CTSnapshot>) {
         debug_assert!(sol.strip_width() == self.prob.strip_width());
         self.prob.restore(sol);
+        self.grid = occupancy_grid(&self.prob, self.config.sample_config);
 
         match ots {
             Some(ots) => {
@@ -188,7 +224,7 @@ impl Separator {
             }
             None => {
                 //otherwise, rebuild it
-                self.ct = CollisionTracker::new(&self.prob.layout);
+                self.ct = CollisionTracker::new(&self.prob.layout, &self.instance, &self.item_groups, self.config.sparse_tracker_threshold);
             }
         }
     }
@@ -202,10 +238,16 @@ impl Separator {
         let old_weighted_loss = self.ct.get_weighted_loss(pk);
 
         //Remove the item from the problem
+        if let Some(grid) = &mut self.grid {
+            grid.register_removal(&self.prob.layout.placed_items[pk]);
+        }
         self.prob.remove_item(pk);
 
         //Place the item again but with a new transformation
         let new_pk = self.prob.place_item(SPPlacement{d_transf,item_id});
+        if let Some(grid) = &mut self.grid {
+            grid.register_placement(&self.prob.layout.placed_items[new_pk]);
+        }
 
         self.ct.register_item_move(&self.prob.layout, pk, new_pk);
 
@@ -237,12 +279,43 @@ impl Separator {
             self.move_item(pik, new_transf.decompose());
         }
 
-        self.prob.change_strip_width(new_width);
+        change_strip_width(&mut self.prob, new_width);
+        self.rebuild_trackers();
+        debug!("[SEP] changed strip width to {:.3}", new_width);
+    }
+
+    /// Changes the height of the strip, shifting all items above `split_position` (by default the middle of the strip) up or down.
+    /// Does nothing if the height remains the same.
+    pub fn change_strip_height(&mut self, new_height: f32, split_position: Option<f32>) {
+        let height = self.prob.strip.fixed_height;
+        if new_height == height {
+            return;
+        }
+        let split_position = split_position.unwrap_or(height / 2.0);
+        let delta = new_height - height;
+
+        //shift all items above the split position
+        let items_to_shift = self.prob.layout.placed_items.iter()
+            .filter(|(_, pi)| pi.shape.centroid().1 > split_position)
+            .map(|(k, pi)| (k, pi.d_transf))
+            .collect_vec();
+
+        for (pik, dtransf) in items_to_shift {
+            let existing_transf = dtransf.compose();
+            let new_transf = existing_transf.translate((0.0, delta));
+            self.move_item(pik, new_transf.decompose());
+        }
+
+        change_strip_height(&mut self.prob, new_height);
+        self.rebuild_trackers();
+        debug!("[SEP] changed strip height to {:.3}", new_height);
+    }
 
-        //rebuild the collision tracker
-        self.ct = CollisionTracker::new(&self.prob.layout);
+    /// Rebuilds the collision tracker and the workers after the strip has changed
+    fn rebuild_trackers(&mut self) {
+        self.ct = CollisionTracker::new(&self.prob.layout, &self.instance, &self.item_groups, self.config.sparse_tracker_threshold);
+        self.grid = occupancy_grid(&self.prob, self.config.sample_config);
 
-        //rebuild the workers
         self.workers.iter_mut().for_each(|opt| {
             *opt = SeparatorWorker {
                 instance: self.instance.clone(),
@@ -250,8 +323,39 @@ impl Separator {
                 ct: self.ct.clone(),
                 rng: Xoshiro256PlusPlus::seed_from_u64(self.rng.random()),
                 sample_config: self.config.sample_config,
+                budget: opt.budget.take(),
+                grid: self.grid.clone(),
             };
         });
-        debug!("[SEP] changed strip width to {:.3}", new_width);
     }
 }
+
+/// Occupancy grid of the layout, only if it is used by the container sampler
+fn occupancy_grid(prob: &SPProblem, sample_config: SampleConfig) -> Option<OccupancyGrid> {
+    (sample_config.container_sampler == ContainerSampler::GapAware).then(|| OccupancyGrid::new(&prob.layout))
+}
+
+/// Modifies the height of the strip of a problem at the top, keeping the bottom fixed
+pub fn change_strip_height(prob: &mut SPProblem, new_height: f32) {
+    assert!(new_height > 0.0, "strip height must be positive");
+    if new_height == prob.strip.fixed_height {
+        return;
+    }
+    prob.strip.fixed_height = new_height;
+    swap_strip_container(prob);
+}
+
+/// Equivalent to [`SPProblem::change_strip_width`], but keeps the quality zones of the container
+pub fn change_strip_width(prob: &mut SPProblem, new_width: f32) {
+    prob.strip.set_width(new_width);
+    swap_strip_container(prob);
+}
+
+/// Equivalent to [`SPProblem::fit_strip`], but keeps the quality zones of the container
+pub fn fit_strip(prob: &mut SPProblem) {
+    // Some tolerance avoids false collision positives, the strip needs to be at least `offset` wider than the items
+    let item_x_max = prob.layout.placed_items.values()
+        .map(|pi| pi.shape.bbox.x_max)
+        .fold(f32::NEG_INFINITY, f32::max) * 1.00001;
+    change_strip_width(prob, item_x_max + prob.strip.shape_modify_config.offset.unwrap_or(0.0));
+}
@@ -0,0 +1,339 @@
+use crate::bounds::LowerBounds;
+use crate::config::{CompressionConfig, ExplorationConfig, OpenDimension, WorkBudget};
+use crate::consts::LBF_SAMPLE_CONFIG;
+use crate::groups::ItemGroups;
+use crate::guillotine::guillotine_repair;
+use crate::optimizer::compact::compaction_phase;
+use crate::optimizer::compress::CompressionState;
+use crate::optimizer::demand::{insert_optional_items, DemandRange};
+use crate::optimizer::explore::ExplorationState;
+use crate::optimizer::island::IslandExplorationState;
+use crate::optimizer::lbf::LBFBuilder;
+use crate::optimizer::open_dim::{fit_aspect_ratio, initial_height};
+use crate::optimizer::separator::{change_strip_height, Separator};
+use crate::optimizer::OptimizeReport;
+use crate::util::listener::{ReportType, SolutionListener};
+use crate::util::terminator::Terminator;
+use crate::zones;
+use crate::zones::{restore_problem, set_quality_zones};
+use jagua_rs::entities::InferiorQualityZone;
+use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
+use jagua_rs::Instant;
+use log::{info, warn};
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::{Rng, SeedableRng};
+use std::time::Duration;
+
+/// Phases of the optimization, in the order in which they are executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Exploration,
+    Compression,
+    Done,
+}
+
+/// Explicit state of an [`optimize`](crate::optimizer::optimize) run, which is advanced one step at a time.
+///
+/// Allows a host to interleave other work with the optimization, inspect intermediate solutions,
+/// change time limits mid-run or end phases early.
+/// The terminator passed to every call should be the same: it is (re)configured with the time limit or work budget of each phase.
+/// ```no_run
+/// # use sparrow::optimizer::state::OptimizerState;
+/// # use sparrow::util::terminator::{BasicTerminator, Terminator};
+/// # use sparrow::util::listener::DummySolListener;
+/// # fn run(mut state: OptimizerState, term: &mut BasicTerminator) {
+/// while !state.is_done() {
+///     if term.kill() {
+///         state.next_phase(&mut DummySolListener, term);
+///     } else {
+///         state.step(&mut DummySolListener, term);
+///     }
+///     println!("best width so far: {}", state.best_solution().strip_width());
+/// }
+/// # }
+/// ```
+pub struct OptimizerState {
+    pub instance: SPInstance,
+    /// Configuration of the exploration phase, changes take effect at the next step
+    pub expl_config: ExplorationConfig,
+    /// Configuration of the compression phase, changes take effect at the next step
+    pub cmpr_config: CompressionConfig,
+    /// Demand ranges of the items (indexed by id), optional copies are inserted into the final solution if provided.
+    /// The quantities of `instance` are the required (minimum) ones.
+    pub demand_ranges: Option<Vec<DemandRange>>,
+    pub lower_bounds: LowerBounds,
+    item_groups: ItemGroups,
+    rng: Xoshiro256PlusPlus,
+    stage: Stage,
+    phase_start: Instant,
+    report: OptimizeReport,
+}
+
+enum Stage {
+    Exploring(Separator, Box<ExplorationState>),
+    ExploringIslands(Box<IslandExplorationState>),
+    Compressing(Separator, Box<CompressionState>),
+    Done(SPSolution),
+}
+
+impl OptimizerState {
+    /// Constructs (or restores) the initial solution and starts the exploration phase
+    pub fn new(
+        instance: SPInstance,
+        rng: Xoshiro256PlusPlus,
+        sol_listener: &mut impl SolutionListener,
+        terminator: &mut impl Terminator,
+        expl_config: &ExplorationConfig,
+        cmpr_config: &CompressionConfig,
+        initial_solution: Option<&SPSolution>,
+    ) -> Self {
+        let lower_bounds = LowerBounds::new(&instance);
+        Self::with_constraints(instance, lower_bounds, ItemGroups::default(), &[], rng, sol_listener, terminator, expl_config, cmpr_config, initial_solution)
+    }
+
+    /// Same as [`OptimizerState::new`], but every solution keeps the items of each group within the group's maximum x-span,
+    /// and the strip contains quality zones which items can only overlap if they allow the zone's quality.
+    /// The lower bounds of the instance are provided by the caller, which can reuse them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_constraints(
+        instance: SPInstance,
+        lower_bounds: LowerBounds,
+        item_groups: ItemGroups,
+        quality_zones: &[InferiorQualityZone],
+        mut rng: Xoshiro256PlusPlus,
+        sol_listener: &mut impl SolutionListener,
+        terminator: &mut impl Terminator,
+        expl_config: &ExplorationConfig,
+        cmpr_config: &CompressionConfig,
+        initial_solution: Option<&SPSolution>,
+    ) -> Self {
+        let mut next_rng = || Xoshiro256PlusPlus::seed_from_u64(rng.next_u64());
+        info!("[OPT] lower bound on strip width: {lower_bounds}");
+
+        // First build an initial solution if none is provided
+        let start_prob = match initial_solution {
+            None => {
+                let mut builder = LBFBuilder::new(instance.clone(), next_rng(), LBF_SAMPLE_CONFIG)
+                    .with_item_groups(item_groups.clone())
+                    .with_quality_zones(quality_zones);
+                change_strip_height(&mut builder.prob, initial_height(&instance, expl_config.open_dimension));
+                let mut prob = builder.construct().prob;
+                if let Some(OpenDimension::AspectRatio(ratio)) = expl_config.open_dimension {
+                    fit_aspect_ratio(&mut prob, ratio);
+                    info!("[OPT] initial strip for aspect ratio {ratio}: {:.3} x {:.3}", prob.strip_width(), prob.strip.fixed_height);
+                }
+                prob
+            }
+            Some(init_sol) => {
+                info!("[OPT] warm starting from provided initial solution");
+                let mut prob = restore_problem(&instance, init_sol);
+                set_quality_zones(&mut prob, quality_zones);
+                if !quality_zones.is_empty() && !zones::is_feasible(&prob.layout, &instance) {
+                    warn!("[OPT] initial solution is not feasible within the quality zones");
+                }
+                let spans = item_groups.spans(prob.layout.placed_items.values());
+                if item_groups.groups().iter().zip(spans).any(|(group, span)| span > group.max_x_span) {
+                    warn!("[OPT] initial solution exceeds the maximum x-span of an item group");
+                }
+                prob
+            }
+        };
+
+        let init_sol = start_prob.save();
+
+        // Begin by executing the exploration phase
+        start_phase(terminator, expl_config.time_limit, expl_config.work_budget);
+        let stage = match expl_config.island_config {
+            None => {
+                let sep = Separator::new(instance.clone(), start_prob, next_rng(), expl_config.separator_config)
+                    .with_item_groups(item_groups.clone());
+                let state = ExplorationState::new(&instance, &sep, sol_listener, expl_config, lower_bounds);
+                Stage::Exploring(sep, Box::new(state))
+            }
+            Some(island_config) => {
+                let seps = (0..island_config.n_islands)
+                    .map(|_| Separator::new(instance.clone(), start_prob.clone(), next_rng(), expl_config.separator_config).with_item_groups(item_groups.clone()))
+                    .collect();
+                let state = IslandExplorationState::new(&instance, seps, sol_listener, expl_config, &island_config, lower_bounds);
+                Stage::ExploringIslands(Box::new(state))
+            }
+        };
+
+        let report = OptimizeReport {
+            exploration_solution: init_sol,
+            exploration_time: Duration::ZERO,
+            compression_time: Duration::ZERO,
+            compaction_time: Duration::ZERO,
+        };
+
+        Self {
+            instance,
+            expl_config: *expl_config,
+            cmpr_config: *cmpr_config,
+            demand_ranges: None,
+            lower_bounds,
+            item_groups,
+            rng,
+            stage,
+            phase_start: Instant::now(),
+            report,
+        }
+    }
+
+    /// Performs a single step of the current phase: a separation attempt during exploration (an epoch with islands),
+    /// or a compression attempt during compression. Steps are interrupted when the terminator fires, and resumed by the next step.
+    /// Moves on to the next phase if the current one concludes by itself (e.g. the lower bound is reached),
+    /// but not when the terminator fires, that is left to the caller (see [`OptimizerState::next_phase`]).
+    /// Returns `false` once the optimization is done.
+    pub fn step(&mut self, sol_listener: &mut impl SolutionListener, terminator: &mut (impl Terminator + Sync)) -> bool {
+        let proceed = match &mut self.stage {
+            Stage::Exploring(sep, state) => state.step(&self.instance, sep, sol_listener, terminator, &self.expl_config),
+            Stage::ExploringIslands(state) => {
+                let island_config = self.expl_config.island_config.expect("island configuration removed during exploration");
+                state.step(&self.instance, sol_listener, terminator, &self.expl_config, &island_config)
+            }
+            Stage::Compressing(sep, state) => state.step(&self.instance, sep, sol_listener, terminator, &self.cmpr_config),
+            Stage::Done(_) => return false,
+        };
+        if !proceed {
+            self.next_phase(sol_listener, terminator);
+        }
+        !self.is_done()
+    }
+
+    /// Concludes the current phase and starts the next one.
+    /// After compression, the final solution is compacted (if enabled) and reported.
+    pub fn next_phase(&mut self, sol_listener: &mut impl SolutionListener, terminator: &mut impl Terminator) {
+        let phase_time = self.phase_start.elapsed();
+        self.stage = match std::mem::replace(&mut self.stage, Stage::Done(self.report.exploration_solution.clone())) {
+            Stage::Exploring(_, state) => {
+                self.report.exploration_time = phase_time;
+                self.start_compression(state.finish(&self.instance), terminator)
+            }
+            Stage::ExploringIslands(state) => {
+                self.report.exploration_time = phase_time;
+                self.start_compression(state.finish(&self.instance), terminator)
+            }
+            Stage::Compressing(_, state) => {
+                let mut cmpr_sol = state.finish(&self.instance);
+                self.report.compression_time = phase_time;
+
+                // Slide all items left and down to maximize the remnant at the end of the strip
+                let cmpt_start = Instant::now();
+                if self.cmpr_config.post_compaction {
+                    cmpr_sol = compaction_phase(&self.instance, &cmpr_sol, &self.item_groups);
+                }
+                self.report.compaction_time = cmpt_start.elapsed();
+
+                // Fill the free space of the final strip with optional copies of items
+                if let Some(ranges) = &self.demand_ranges {
+                    cmpr_sol = insert_optional_items(&self.instance, ranges, &self.item_groups, &cmpr_sol, &mut self.rng);
+                }
+
+                // Shift items apart until the layout can be cut with guillotine cuts
+                if self.cmpr_config.guillotine {
+                    match guillotine_repair(&self.instance, &cmpr_sol) {
+                        Ok(repaired) => cmpr_sol = repaired,
+                        Err(e) => warn!("[GLT] {e}, the final layout is not separable by guillotine cuts"),
+                    }
+                }
+
+                match self.cmpr_config.open_dimension {
+                    None => info!("[OPT] final width: {:.3}, gap to lower bound: {:.3}%", cmpr_sol.strip_width(), self.lower_bounds.gap(cmpr_sol.strip_width()) * 100.0),
+                    Some(_) => info!("[OPT] final strip: {:.3} x {:.3} (area: {:.3}, dens: {:.3}%)", cmpr_sol.strip_width(), cmpr_sol.strip.fixed_height, cmpr_sol.strip_width() * cmpr_sol.strip.fixed_height, cmpr_sol.density(&self.instance) * 100.0),
+                }
+                sol_listener.report(ReportType::Final, &cmpr_sol, &self.instance);
+                Stage::Done(cmpr_sol)
+            }
+            done @ Stage::Done(_) => done,
+        };
+        self.phase_start = Instant::now();
+    }
+
+    /// Starts the compression phase from the final solution from the exploration phase
+    fn start_compression(&mut self, expl_solutions: Vec<SPSolution>, terminator: &mut impl Terminator) -> Stage {
+        let final_explore_sol = expl_solutions.last().unwrap().clone();
+        start_phase(terminator, self.cmpr_config.time_limit, self.cmpr_config.work_budget);
+        let cmpr_prob = restore_problem(&self.instance, &final_explore_sol);
+        let sep = Separator::new(self.instance.clone(), cmpr_prob, Xoshiro256PlusPlus::seed_from_u64(self.rng.next_u64()), self.cmpr_config.separator_config)
+            .with_item_groups(self.item_groups.clone());
+        let state = CompressionState::new(&self.instance, &final_explore_sol, self.lower_bounds);
+        self.report.exploration_solution = final_explore_sol;
+        Stage::Compressing(sep, Box::new(state))
+    }
+
+    /// Changes the time limit of the current phase (counted from its start), and of the phase's configuration.
+    /// Has no effect on the terminator if the phase is limited by a work budget.
+    pub fn set_time_limit(&mut self, terminator: &mut impl Terminator, time_limit: Duration) {
+        let work_budget = match self.phase() {
+            Phase::Exploration => {
+                self.expl_config.time_limit = time_limit;
+                self.expl_config.work_budget
+            }
+            Phase::Compression => {
+                self.cmpr_config.time_limit = time_limit;
+                self.cmpr_config.work_budget
+            }
+            Phase::Done => return,
+        };
+        if work_budget.is_none() {
+            terminator.new_timeout(time_limit.saturating_sub(self.phase_start.elapsed()));
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        match self.stage {
+            Stage::Exploring(..) | Stage::ExploringIslands(_) => Phase::Exploration,
+            Stage::Compressing(..) => Phase::Compression,
+            Stage::Done(_) => Phase::Done,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.phase() == Phase::Done
+    }
+
+    /// Best feasible solution found so far (the final solution once done)
+    pub fn best_solution(&self) -> &SPSolution {
+        match &self.stage {
+            Stage::Exploring(_, state) => state.best_feasible(),
+            Stage::ExploringIslands(state) => state.best_feasible(),
+            Stage::Compressing(_, state) => &state.best_sol,
+            Stage::Done(sol) => sol,
+        }
+    }
+
+    /// Time spent in the current phase so far
+    pub fn phase_elapsed(&self) -> Duration {
+        self.phase_start.elapsed()
+    }
+
+    /// Runs all remaining phases, each until its terminator fires or it concludes by itself, and returns the final solution
+    pub fn run(mut self, sol_listener: &mut impl SolutionListener, terminator: &mut (impl Terminator + Sync)) -> (SPSolution, OptimizeReport) {
+        while !self.is_done() {
+            match terminator.kill() {
+                true => self.next_phase(sol_listener, terminator),
+                false => _ = self.step(sol_listener, terminator),
+            }
+        }
+        self.finish(sol_listener, terminator)
+    }
+
+    /// Concludes all remaining phases immediately and returns the final solution
+    pub fn finish(mut self, sol_listener: &mut impl SolutionListener, terminator: &mut impl Terminator) -> (SPSolution, OptimizeReport) {
+        while !self.is_done() {
+            self.next_phase(sol_listener, terminator);
+        }
+        match self.stage {
+            Stage::Done(sol) => (sol, self.report),
+            _ => unreachable!(),
+        }
+    }
+}
+
+fn start_phase(terminator: &mut impl Terminator, time_limit: Duration, work_budget: Option<WorkBudget>) {
+    match work_budget {
+        Some(budget) => terminator.new_work_budget(budget),
+        None => terminator.new_timeout(time_limit),
+    }
+}
@@ -1,5 +1,7 @@
 use crate::eval::sep_evaluator::SeparationEvaluator;
 use crate::quantify::tracker::CollisionTracker;
+use crate::sample::adaptive::AdaptiveBudget;
+use crate::sample::gap_sampler::OccupancyGrid;
 use crate::sample::search;
 use crate::sample::search::SampleConfig;
 use crate::util::assertions::tracker_matches_layout;
@@ -21,14 +23,19 @@ pub struct SeparatorWorker {
     pub ct: CollisionTracker,
     pub rng: Xoshiro256PlusPlus,
     pub sample_config: SampleConfig,
+    /// Adapts the sample configuration to every item, if enabled in the sample configuration
+    pub budget: Option<AdaptiveBudget>,
+    /// Occupancy of the layout, maintained if the gap-aware container sampler is used
+    pub grid: Option<OccupancyGrid>,
 }
 
 impl SeparatorWorker {
-    pub fn load(&mut self, sol: &SPSolution, ct: &CollisionTracker) {
-        // restores the state of the worker to the given solution and accompanying tracker
+    pub fn load(&mut self, sol: &SPSolution, ct: &CollisionTracker, grid: Option<&OccupancyGrid>) {
+        // restores the state of the worker to the given solution and accompanying tracker and occupancy grid
         debug_assert!(sol.strip_width() == self.prob.strip_width());
         self.prob.restore(sol);
         self.ct = ct.clone();
+        self.grid = grid.cloned();
     }
 
     /// Algorithm 5 from https://doi.org/10.48550/arXiv.2509.13329
@@ -39,8 +46,7 @@ impl SeparatorWorker {
             .collect_vec()
             .tap_mut(|v| v.shuffle(&mut self.rng));
 
-        let mut total_moves = 0;
-        let mut total_evals = 0;
+        let mut stats = SepStats::default();
 
         // Give each colliding item the opportunity to move to a better (eval) position
         for &pk in candidates.iter() {
@@ -52,19 +58,29 @@ impl SeparatorWorker {
                 // Create an 'evaluator' to perform collision detection and collision quantification of the samples during the search
                 let evaluator = SeparationEvaluator::new(&self.prob.layout, item, pk, &self.ct);
 
+                let sample_config = match &self.budget {
+                    Some(budget) => budget.sample_config(item_id, self.sample_config),
+                    None => self.sample_config,
+                };
+
                 // Perform the search for a better position for the item
-                let (best_sample, n_evals) =
-                    search::search_placement(&self.prob.layout, item, Some(pk), evaluator, self.sample_config, &mut self.rng);
+                let (best_sample, search_stats) =
+                    search::search_placement(&self.prob.layout, item, Some(pk), self.grid.as_ref(), evaluator, sample_config, &mut self.rng);
 
                 let (new_dt, _eval) = best_sample.expect("search_placement should always return a sample");
 
                 // Move the item to the new position
-                self.move_item(pk, new_dt);
-                total_moves += 1;
-                total_evals += n_evals;
+                let new_pk = self.move_item(pk, new_dt);
+                if let Some(budget) = &mut self.budget {
+                    budget.report_move(item_id, self.ct.get_loss(new_pk) == 0.0);
+                }
+                stats.total_moves += 1;
+                stats.total_evals += search_stats.n_evals;
+                stats.refine_evals += search_stats.n_refine_evals;
+                stats.register_item_evals(item_id, search_stats.n_evals);
             }
         }
-        SepStats { total_moves, total_evals }
+        stats
     }
 
     pub fn move_item(&mut self, pk: PItemKey, d_transf: DTransformation) -> PItemKey {
@@ -78,9 +94,15 @@ impl SeparatorWorker {
         debug_assert!(old_w_l > 0.0, "Item with key {:?} should be colliding, but has no weighted loss: {}", pk, FMT().fmt2(old_w_l));
 
         // First removing the item and subsequently place it in its new position
+        if let Some(grid) = &mut self.grid {
+            grid.register_removal(&self.prob.layout.placed_items[pk]);
+        }
         let old_placement = self.prob.remove_item(pk);
         let new_placement = SPPlacement { d_transf, item_id: item.id };
         let new_pk = self.prob.place_item(new_placement);
+        if let Some(grid) = &mut self.grid {
+            grid.register_placement(&self.prob.layout.placed_items[new_pk]);
+        }
 
         // Update the collision tracker to reflect the changes
         self.ct.register_item_move(&self.prob.layout, pk, new_pk);
@@ -95,22 +117,32 @@ impl SeparatorWorker {
     }
 }
 
+#[derive(Debug, Clone, Default)]
 pub struct SepStats {
     pub total_moves: usize,
     pub total_evals: usize,
+    /// Number of evaluations spent refining samples
+    pub refine_evals: usize,
+    /// Number of evaluations spent on every item (indexed by item id)
+    pub item_evals: Vec<usize>,
+}
+
+impl SepStats {
+    fn register_item_evals(&mut self, item_id: usize, n_evals: usize) {
+        if self.item_evals.len() <= item_id {
+            self.item_evals.resize(item_id + 1, 0);
+        }
+        self.item_evals[item_id] += n_evals;
+    }
 }
 
 impl Sum for SepStats {
     fn sum<I: Iterator<Item=SepStats>>(iter: I) -> Self {
-        let mut total_moves = 0;
-        let mut total_evals = 0;
-
+        let mut total = SepStats::default();
         for report in iter {
-            total_moves += report.total_moves;
-            total_evals += report.total_evals;
+            total += report;
         }
-
-        SepStats { total_moves, total_evals }
+        total
     }
 }
 
@@ -118,5 +150,9 @@ impl AddAssign for SepStats {
     fn add_assign(&mut self, other: Self) {
         self.total_moves += other.total_moves;
         self.total_evals += other.total_evals;
+        self.refine_evals += other.refine_evals;
+        for (item_id, n_evals) in other.item_evals.into_iter().enumerate() {
+            self.register_item_evals(item_id, n_evals);
+        }
     }
 }
@@ -5,8 +5,8 @@ use jagua_rs::geometry::primitives::{Rect, SPolygon};
 
 pub mod overlap_proxy;
 mod pair_matrix;
+mod sparse_pair_map;
 pub mod tracker;
-#[cfg(feature = "simd")]
 pub mod simd;
 
 /// Quantifies a collision between two simple polygons.
@@ -50,4 +50,27 @@ pub fn quantify_collision_poly_container(s: &SPolygon, c_bbox: Rect) -> f32 {
     let penalty = calc_shape_penalty(s, s);
 
     2.0 * overlap.sqrt() * penalty
-}
\ No newline at end of file
+}
+
+/// Quantifies a collision between a simple polygon and a zone of the container it should not overlap.
+/// Same as a collision between two items, but penalized only by the shape of the item, as zones can be arbitrarily large.
+#[inline(always)]
+pub fn quantify_collision_poly_zone(s: &SPolygon, zone: &SPolygon) -> f32 {
+    let epsilon = s.diameter * OVERLAP_PROXY_EPSILON_DIAM_RATIO;
+
+    let overlap_proxy = overlap_area_proxy(s.surrogate(), zone.surrogate(), epsilon) + epsilon.powi(2);
+
+    debug_assert!(overlap_proxy.is_normal());
+
+    let penalty = calc_shape_penalty(s, s);
+
+    overlap_proxy.sqrt() * penalty
+}
+
+/// Quantifies how far an item (of height `height` and shape penalty `penalty`) makes its group exceed the group's maximum x-span.
+/// Analogous to a collision with the container, with the part of the item sticking out of the allowed span as the overlap.
+#[inline(always)]
+pub fn quantify_group_span_violation(excess: f32, height: f32, penalty: f32) -> f32 {
+    debug_assert!(excess > 0.0);
+    2.0 * (excess * height).sqrt() * penalty
+}
@@ -1,5 +1,6 @@
 use jagua_rs::geometry::fail_fast::SPSurrogate;
 use jagua_rs::geometry::geo_traits::DistanceTo;
+use jagua_rs::geometry::primitives::Point;
 use std::f32::consts::PI;
 
 /// Calculates a proxy for the overlap area between two simple polygons (using poles).
@@ -24,4 +25,24 @@ pub fn overlap_area_proxy(sp1: &SPSurrogate, sp2: &SPSurrogate, epsilon: f32) ->
     debug_assert!(total_overlap.is_normal());
     
     total_overlap
-}
\ No newline at end of file
+}
+/// Sum of the vectors with which the overlapping poles of `sp2` push those of `sp1` apart, weighted like [`overlap_area_proxy`],
+/// together with the torque this push exerts on `sp1` around `pivot`: `[x, y, torque]`.
+pub fn overlap_push(sp1: &SPSurrogate, sp2: &SPSurrogate, pivot: Point) -> [f32; 3] {
+    let mut push = [0.0; 3];
+    for p1 in &sp1.poles {
+        for p2 in &sp2.poles {
+            let (dx, dy) = (p1.center.x() - p2.center.x(), p1.center.y() - p2.center.y());
+            let dist = dx.hypot(dy);
+            let pd = (p1.radius + p2.radius) - dist;
+            if pd > 0.0 && dist > 0.0 {
+                let magnitude = pd * f32::min(p1.radius, p2.radius) / dist;
+                let (fx, fy) = (dx * magnitude, dy * magnitude);
+                push[0] += fx;
+                push[1] += fy;
+                push[2] += (p1.center.x() - pivot.x()) * fy - (p1.center.y() - pivot.y()) * fx;
+            }
+        }
+    }
+    push
+}
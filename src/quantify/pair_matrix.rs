@@ -17,6 +17,16 @@ impl PairMatrix {
             data: vec![CTEntry { weight: 1.0, loss: 0.0 }; len],
         }
     }
+
+    pub fn row(&self, idx: usize) -> impl Iterator<Item = (usize, &CTEntry)> {
+        (0..self.size).map(move |i| (i, &self[(idx, i)]))
+    }
+
+    pub fn reset_losses(&mut self, idx: usize) {
+        for i in 0..self.size {
+            self[(idx, i)].loss = 0.0;
+        }
+    }
 }
 
 impl Index<(usize, usize)> for PairMatrix {
@@ -3,7 +3,7 @@ use jagua_rs::geometry::primitives::Circle;
 
 /// Collection of circles, but with a memory layout that's more suitable for SIMD operations:
 /// SoA (Structure of Arrays) instead of AoS (Array of Structures).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 #[repr(align(32))]
 pub struct CirclesSoA {
     pub x: Vec<f32>,
@@ -13,11 +13,7 @@ pub struct CirclesSoA {
 
 impl CirclesSoA {
     pub fn new() -> Self {
-        Self {
-            x: Vec::new(),
-            y: Vec::new(),
-            r: Vec::new(),
-        }
+        Self::default()
     }
     pub fn load(&mut self, circles: &[Circle]) -> &mut Self {
         self.x.resize(circles.len(), 0.0);
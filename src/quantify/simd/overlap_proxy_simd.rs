@@ -2,85 +2,80 @@ use crate::quantify::overlap_proxy::overlap_area_proxy;
 use crate::quantify::simd::circles_soa::CirclesSoA;
 use float_cmp::approx_eq;
 use jagua_rs::geometry::fail_fast::SPSurrogate;
-use jagua_rs::geometry::geo_traits::DistanceTo;
-use jagua_rs::geometry::primitives::{Circle, Point};
+use jagua_rs::geometry::primitives::Circle;
 use std::f32::consts::PI;
-use std::simd::Select;
-use std::simd::Simd;
+use std::sync::LazyLock;
+
+/// Instruction set used to vectorize the overlap proxy, detected once at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdLevel {
+    Scalar,
+    /// SSE4.1, 4 lanes
+    Sse,
+    /// AVX2, 8 lanes
+    Avx2,
+    /// AVX-512F, 16 lanes
+    Avx512,
+}
+
+impl SimdLevel {
+    /// Widest instruction set supported by the CPU this process is running on
+    pub fn detect() -> Self {
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("avx512f") {
+                return SimdLevel::Avx512;
+            }
+            if is_x86_feature_detected!("avx2") {
+                return SimdLevel::Avx2;
+            }
+            if is_x86_feature_detected!("sse4.1") {
+                return SimdLevel::Sse;
+            }
+        }
+        SimdLevel::Scalar
+    }
 
-/// Width of the SIMD vector
-const SIMD_WIDTH: usize = 4;
+    pub fn lanes(&self) -> usize {
+        match self {
+            SimdLevel::Scalar => 1,
+            SimdLevel::Sse => 4,
+            SimdLevel::Avx2 => 8,
+            SimdLevel::Avx512 => 16,
+        }
+    }
+}
 
-#[allow(non_camel_case_types)]
-type f32xN = Simd<f32,SIMD_WIDTH>;
+pub static SIMD_LEVEL: LazyLock<SimdLevel> = LazyLock::new(SimdLevel::detect);
 
-/// SIMD version of [`poles_overlap_area_proxy`] with configurable vector width.
+/// Vectorized version of [`overlap_area_proxy`], dispatched at runtime to the widest instruction set available.
 /// `p2` should match the poles of `sp2`.
+///
+/// Every lane performs exactly the same operations as the scalar version and the lanes are summed in the original order,
+/// so the result is bit-identical regardless of the instruction set (and thus machine) used.
 #[inline(always)]
 pub fn poles_overlap_area_proxy_simd(sp1: &SPSurrogate, sp2: &SPSurrogate, epsilon: f32, p2: &CirclesSoA) -> f32 {
-    use std::simd::prelude::{SimdFloat, SimdPartialOrd};
-    use std::simd::StdFloat;
-
-    let e_n = f32xN::splat(epsilon);
-    let e_sq_n = f32xN::splat(epsilon * epsilon);
-    let two_e_n = f32xN::splat(2.0 * epsilon);
-
     let mut total_overlap = 0.0;
     for p1 in sp1.poles.iter() {
-        //common values for all chunks
-        let r1 = p1.radius;
-        let x1_n = f32xN::splat(p1.center.x());
-        let y1_n = f32xN::splat(p1.center.y());
-        let r1_n = f32xN::splat(r1);
-
-        //process complete chunks with SIMD
-        let chunks = p2.x.len() / SIMD_WIDTH;
-
-        for chunk in 0..chunks {
-            let idx = chunk * SIMD_WIDTH;
-
-            // load the next N elements from p2
-            let x2 = f32xN::from_slice(&p2.x[idx..idx + SIMD_WIDTH]);
-            let y2 = f32xN::from_slice(&p2.y[idx..idx + SIMD_WIDTH]);
-            let r2 = f32xN::from_slice(&p2.r[idx..idx + SIMD_WIDTH]);
-
-            // calculate pd
-            let dx = x1_n - x2;
-            let dy = y1_n - y2;
-
-            let pd = r1_n + r2 - (dx * dx + dy * dy).sqrt();
-
-            // calculate pd_decay
-            let pd_mask = pd.simd_ge(e_n);
-            let decay_values = e_sq_n / (-pd + two_e_n);
-            let pd_decay = pd_mask.select(pd, decay_values);
-
-            // calculate min radius
-            let min_r = r1_n.simd_min(r2);
-
-            total_overlap += (pd_decay * min_r).reduce_sum();
-        }
+        // SAFETY: the kernels are only called when the CPU supports their target features
+        let processed = unsafe {
+            match *SIMD_LEVEL {
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                SimdLevel::Avx512 => x86::pole_overlap_avx512(p1, p2, epsilon, &mut total_overlap),
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                SimdLevel::Avx2 => x86::pole_overlap_avx2(p1, p2, epsilon, &mut total_overlap),
+                #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+                SimdLevel::Sse => x86::pole_overlap_sse(p1, p2, epsilon, &mut total_overlap),
+                _ => 0,
+            }
+        };
 
         //process remaining elements with scalar operations
-        let remaining_idx = chunks * SIMD_WIDTH;
-        for j in remaining_idx..p2.x.len() {
-            let p2 = Circle { 
-                center : Point(p2.x[j], p2.y[j]), 
-                radius: p2.r[j]
-            };
-
-            //penetration depth between the two poles (circles)
-            let pd = (p1.radius + p2.radius) - p1.center.distance_to(&p2.center);
-
-            let pd_decay = match pd >= epsilon {
-                true => pd,
-                false => epsilon.powi(2) / (-pd + 2.0 * epsilon),
-            };
-
-            total_overlap += pd_decay * f32::min(p1.radius, p2.radius);
+        for j in processed..p2.x.len() {
+            total_overlap += pole_overlap(p1, p2.x[j], p2.y[j], p2.r[j], epsilon);
         }
     }
-    
+
     total_overlap *= PI;
 
     debug_assert!(
@@ -93,3 +88,84 @@ pub fn poles_overlap_area_proxy_simd(sp1: &SPSurrogate, sp2: &SPSurrogate, epsil
     debug_assert!(total_overlap.is_normal());
     total_overlap
 }
+
+/// Decayed penetration depth between two poles, weighted by the smallest radius
+#[inline(always)]
+fn pole_overlap(p1: &Circle, x2: f32, y2: f32, r2: f32, epsilon: f32) -> f32 {
+    let (dx, dy) = (p1.center.x() - x2, p1.center.y() - y2);
+    let pd = (p1.radius + r2) - (dx * dx + dy * dy).sqrt();
+
+    let pd_decay = match pd >= epsilon {
+        true => pd,
+        false => (epsilon * epsilon) / (-pd + 2.0 * epsilon),
+    };
+
+    pd_decay * f32::min(p1.radius, r2)
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    use crate::quantify::simd::circles_soa::CirclesSoA;
+    use jagua_rs::geometry::primitives::Circle;
+
+    /// Generates a kernel which computes the overlap of pole `p1` with all complete chunks of `p2`.
+    /// The values of the lanes are added to `total` one by one, in order.
+    /// Returns the number of poles of `p2` processed.
+    macro_rules! pole_overlap_kernel {
+        ($name:ident, $feature:literal, $lanes:literal, $vec:ty, $set1:ident, $loadu:ident, $storeu:ident,
+         $add:ident, $sub:ident, $mul:ident, $div:ident, $sqrt:ident, $min:ident, $blend_ge:expr) => {
+            #[target_feature(enable = $feature)]
+            pub unsafe fn $name(p1: &Circle, p2: &CirclesSoA, epsilon: f32, total: &mut f32) -> usize {
+                let chunks = p2.x.len() / $lanes;
+                let x1 = $set1(p1.center.x());
+                let y1 = $set1(p1.center.y());
+                let r1 = $set1(p1.radius);
+                let e = $set1(epsilon);
+                let e_sq = $set1(epsilon * epsilon);
+                let two_e = $set1(2.0 * epsilon);
+                let mut out = [0.0f32; $lanes];
+
+                for chunk in 0..chunks {
+                    let idx = chunk * $lanes;
+                    unsafe {
+                        let x2: $vec = $loadu(p2.x.as_ptr().add(idx));
+                        let y2: $vec = $loadu(p2.y.as_ptr().add(idx));
+                        let r2: $vec = $loadu(p2.r.as_ptr().add(idx));
+
+                        // penetration depth
+                        let dx = $sub(x1, x2);
+                        let dy = $sub(y1, y2);
+                        let pd = $sub($add(r1, r2), $sqrt($add($mul(dx, dx), $mul(dy, dy))));
+
+                        // decayed penetration depth: pd if pd >= e, e² / (2e - pd) otherwise
+                        let decay = $div(e_sq, $add($sub($set1(0.0), pd), two_e));
+                        let pd_decay = $blend_ge(pd, e, decay);
+
+                        $storeu(out.as_mut_ptr(), $mul(pd_decay, $min(r1, r2)));
+                    }
+                    for v in out {
+                        *total += v;
+                    }
+                }
+                chunks * $lanes
+            }
+        };
+    }
+
+    pole_overlap_kernel!(pole_overlap_sse, "sse4.1", 4, __m128,
+        _mm_set1_ps, _mm_loadu_ps, _mm_storeu_ps, _mm_add_ps, _mm_sub_ps, _mm_mul_ps, _mm_div_ps, _mm_sqrt_ps, _mm_min_ps,
+        |pd, e, decay| _mm_blendv_ps(decay, pd, _mm_cmpge_ps(pd, e)));
+
+    pole_overlap_kernel!(pole_overlap_avx2, "avx2", 8, __m256,
+        _mm256_set1_ps, _mm256_loadu_ps, _mm256_storeu_ps, _mm256_add_ps, _mm256_sub_ps, _mm256_mul_ps, _mm256_div_ps, _mm256_sqrt_ps, _mm256_min_ps,
+        |pd, e, decay| _mm256_blendv_ps(decay, pd, _mm256_cmp_ps::<_CMP_GE_OQ>(pd, e)));
+
+    pole_overlap_kernel!(pole_overlap_avx512, "avx512f", 16, __m512,
+        _mm512_set1_ps, _mm512_loadu_ps, _mm512_storeu_ps, _mm512_add_ps, _mm512_sub_ps, _mm512_mul_ps, _mm512_div_ps, _mm512_sqrt_ps, _mm512_min_ps,
+        |pd, e, decay| _mm512_mask_blend_ps(_mm512_cmp_ps_mask::<_CMP_GE_OQ>(pd, e), decay, pd));
+}
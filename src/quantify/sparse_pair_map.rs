@@ -0,0 +1,90 @@
+use crate::quantify::tracker::CTEntry;
+use std::collections::BTreeMap;
+
+const NEUTRAL_ENTRY: CTEntry = CTEntry { weight: 1.0, loss: 0.0 };
+
+// sparse alternative to the `PairMatrix` for instances with many items.
+// every item has an adjacency map containing only the pairs that are currently colliding or have a non-unit weight,
+// all other pairs are implicitly neutral (no loss, weight of 1.0).
+// entries are stored symmetrically: in the adjacency maps of both items of the pair.
+// ordered maps are used to keep the iteration order (and thus floating point sums) deterministic.
+#[derive(Debug, Clone)]
+pub struct SparsePairMap {
+    pub rows: Vec<BTreeMap<usize, CTEntry>>,
+}
+
+impl SparsePairMap {
+    pub fn new(size: usize) -> Self {
+        Self {
+            rows: vec![BTreeMap::new(); size],
+        }
+    }
+
+    pub fn get(&self, idx1: usize, idx2: usize) -> CTEntry {
+        self.rows[idx1].get(&idx2).copied().unwrap_or(NEUTRAL_ENTRY)
+    }
+
+    pub fn set_loss(&mut self, idx1: usize, idx2: usize, loss: f32) {
+        debug_assert!(idx1 != idx2);
+        for (a, b) in [(idx1, idx2), (idx2, idx1)] {
+            let entry = self.rows[a].entry(b).or_insert(NEUTRAL_ENTRY);
+            entry.loss = loss;
+            if is_neutral(entry) {
+                self.rows[a].remove(&b);
+            }
+        }
+    }
+
+    pub fn row(&self, idx: usize) -> impl Iterator<Item = (usize, &CTEntry)> {
+        self.rows[idx].iter().map(|(&k, e)| (k, e))
+    }
+
+    pub fn reset_losses(&mut self, idx: usize) {
+        let others = self.rows[idx].keys().copied().collect::<Vec<_>>();
+        for other in others {
+            self.set_loss(idx, other, 0.0);
+        }
+    }
+
+    /// Iterates over every stored pair once
+    pub fn entries(&self) -> impl Iterator<Item = &CTEntry> {
+        self.rows.iter().enumerate()
+            .flat_map(|(idx, row)| row.range(idx + 1..).map(|(_, e)| e))
+    }
+
+    /// Applies `f` to every stored pair (both of its copies), and drops the pairs that became neutral
+    pub fn modify_entries(&mut self, f: impl Fn(&mut CTEntry)) {
+        for row in self.rows.iter_mut() {
+            row.values_mut().for_each(&f);
+            row.retain(|_, e| !is_neutral(e));
+        }
+    }
+
+    pub fn copy_losses_from(&mut self, other: &SparsePairMap) {
+        for idx in 0..self.rows.len() {
+            let stale = self.rows[idx].keys()
+                .filter(|k| !other.rows[idx].contains_key(k))
+                .copied()
+                .collect::<Vec<_>>();
+            for k in stale {
+                self.set_loss(idx, k, 0.0);
+            }
+            for (&k, e) in other.rows[idx].range(idx + 1..) {
+                self.set_loss(idx, k, e.loss);
+            }
+        }
+    }
+
+    /// Checks whether all pairs are stored symmetrically and no neutral pairs are stored
+    pub fn is_consistent(&self) -> bool {
+        self.rows.iter().enumerate().all(|(idx, row)| {
+            row.iter().all(|(&k, e)| {
+                k != idx && !is_neutral(e) && self.rows[k].get(&idx).is_some_and(|o| o.loss == e.loss && o.weight == e.weight)
+            })
+        })
+    }
+}
+
+fn is_neutral(e: &CTEntry) -> bool {
+    e.loss == 0.0 && e.weight == 1.0
+}
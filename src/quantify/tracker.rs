@@ -1,37 +1,158 @@
 use crate::consts::{GLS_WEIGHT_DECAY, GLS_WEIGHT_MAX_INC_RATIO, GLS_WEIGHT_MIN_INC_RATIO};
 use crate::quantify::pair_matrix::PairMatrix;
-use crate::quantify::{quantify_collision_poly_container, quantify_collision_poly_poly};
+use crate::quantify::sparse_pair_map::SparsePairMap;
+use crate::groups::{GroupWindow, ItemGroups};
+use crate::quantify::{calc_shape_penalty, quantify_collision_poly_container, quantify_collision_poly_poly, quantify_collision_poly_zone, quantify_group_span_violation};
 use crate::util::assertions::tracker_matches_layout;
+use crate::zones::is_forbidden_zone;
+use itertools::{Either, Itertools};
 use jagua_rs::collision_detection::hazards::collector::{BasicHazardCollector, HazardCollector};
 use jagua_rs::collision_detection::hazards::HazardEntity;
-use jagua_rs::entities::{Layout, PItemKey};
+use jagua_rs::entities::{Instance, Layout, PItemKey};
+use jagua_rs::geometry::primitives::Rect;
+use jagua_rs::probs::spp::entities::SPInstance;
 use ordered_float::Float;
 use slotmap::SecondaryMap;
+use std::sync::Arc;
 
 /// Tracker of both collisions between pair of items and collisions with the container.
+/// Violations of the maximum x-span of item groups are tracked as (soft) collisions as well.
+/// Overlap with zones of the container of insufficient quality counts as a collision with the container.
 /// It also stores the weights for every pair of hazards and is used as a cache for collisions.
 #[derive(Debug, Clone)]
 pub struct CollisionTracker {
     pub size: usize,
     pub pk_idx_map: SecondaryMap<PItemKey, usize>,
-    pub pair_collisions: PairMatrix,
+    pub pair_collisions: PairCollisions,
     pub container_collisions: Vec<CTEntry>,
+    /// Violations of the maximum x-span of the item's group, zero for items without a group
+    pub group_collisions: Vec<CTEntry>,
+    pub item_groups: ItemGroups,
+    group_index: Arc<GroupIndex>,
+    /// Minimum quality required by every item, see [`Item::min_quality`](jagua_rs::entities::Item::min_quality)
+    min_qualities: Arc<Vec<Option<usize>>>,
+    /// Bounding box of every item, to compute the span of the groups
+    bboxes: Vec<Rect>,
+    /// Running totals of all collisions involving each item
+    pub item_totals: Vec<LossTotal>,
+    /// Running total of all collisions in the layout
+    pub total: LossTotal,
+}
+
+/// Storage of the collisions and weights between pairs of items.
+/// Dense for regular instances, sparse for instances with more items than the threshold (see [`SPARSE_TRACKER_THRESHOLD`](crate::consts::SPARSE_TRACKER_THRESHOLD)).
+#[derive(Debug, Clone)]
+pub enum PairCollisions {
+    Dense(PairMatrix),
+    Sparse(SparsePairMap),
+}
+
+impl PairCollisions {
+    pub fn new(size: usize, sparse_threshold: usize) -> Self {
+        match size > sparse_threshold {
+            false => PairCollisions::Dense(PairMatrix::new(size)),
+            true => PairCollisions::Sparse(SparsePairMap::new(size)),
+        }
+    }
+
+    pub fn get(&self, idx1: usize, idx2: usize) -> CTEntry {
+        match self {
+            PairCollisions::Dense(pm) => pm[(idx1, idx2)],
+            PairCollisions::Sparse(sm) => sm.get(idx1, idx2),
+        }
+    }
+
+    pub fn set_loss(&mut self, idx1: usize, idx2: usize, loss: f32) {
+        match self {
+            PairCollisions::Dense(pm) => pm[(idx1, idx2)].loss = loss,
+            PairCollisions::Sparse(sm) => sm.set_loss(idx1, idx2, loss),
+        }
+    }
+
+    /// Sets the loss of all pairs involving the item to zero
+    pub fn reset_losses(&mut self, idx: usize) {
+        match self {
+            PairCollisions::Dense(pm) => pm.reset_losses(idx),
+            PairCollisions::Sparse(sm) => sm.reset_losses(idx),
+        }
+    }
+
+    /// All (non-neutral) pairs involving the item, with the index of the other item
+    pub fn row(&self, idx: usize) -> impl Iterator<Item = (usize, &CTEntry)> {
+        match self {
+            PairCollisions::Dense(pm) => Either::Left(pm.row(idx)),
+            PairCollisions::Sparse(sm) => Either::Right(sm.row(idx)),
+        }
+    }
+
+    /// All (non-neutral) pairs, each one once
+    pub fn entries(&self) -> impl Iterator<Item = &CTEntry> {
+        match self {
+            PairCollisions::Dense(pm) => Either::Left(pm.data.iter()),
+            PairCollisions::Sparse(sm) => Either::Right(sm.entries()),
+        }
+    }
+
+    pub fn modify_entries(&mut self, f: impl Fn(&mut CTEntry)) {
+        match self {
+            PairCollisions::Dense(pm) => pm.data.iter_mut().for_each(f),
+            PairCollisions::Sparse(sm) => sm.modify_entries(f),
+        }
+    }
+
+    pub fn copy_losses_from(&mut self, other: &PairCollisions) {
+        match (self, other) {
+            (PairCollisions::Dense(pm), PairCollisions::Dense(other)) => {
+                pm.data.iter_mut()
+                    .zip(other.data.iter())
+                    .for_each(|(a, b)| a.loss = b.loss);
+            }
+            (PairCollisions::Sparse(sm), PairCollisions::Sparse(other)) => sm.copy_losses_from(other),
+            _ => unreachable!("trackers of the same layout should use the same pair storage"),
+        }
+    }
+}
+
+/// Items (by index in the tracker) of every item group, shared by all clones of a tracker
+#[derive(Debug, Default)]
+struct GroupIndex {
+    idx_group: Vec<Option<usize>>,
+    members: Vec<Vec<usize>>,
+    /// Shape penalty of every item, see [`calc_shape_penalty`]
+    penalties: Vec<f32>,
 }
 
 pub type CTSnapshot = CollisionTracker;
 
 impl CollisionTracker {
-    pub fn new(l: &Layout) -> Self {
+    pub fn new(l: &Layout, instance: &SPInstance, item_groups: &ItemGroups, sparse_threshold: usize) -> Self {
         let size = l.placed_items.len();
 
+        let group_index = {
+            let idx_group = l.placed_items.values().map(|pi| item_groups.group_of(pi.item_id)).collect_vec();
+            let mut members = vec![vec![]; item_groups.groups().len()];
+            idx_group.iter().enumerate()
+                .filter_map(|(idx, g)| g.map(|g| (idx, g)))
+                .for_each(|(idx, g)| members[g].push(idx));
+            let penalties = l.placed_items.values().map(|pi| calc_shape_penalty(&pi.shape, &pi.shape)).collect();
+            GroupIndex { idx_group, members, penalties }
+        };
+
         // Create the tracker
         let mut ot = Self {
             size,
             pk_idx_map: l.placed_items.keys().enumerate()
                 .map(|(i, pk)| (pk, i))
                 .collect(),
-            pair_collisions: PairMatrix::new(size),
+            pair_collisions: PairCollisions::new(size, sparse_threshold),
             container_collisions: vec![CTEntry { weight: 1.0, loss: 0.0 }; size],
+            group_collisions: vec![CTEntry { weight: 1.0, loss: 0.0 }; size],
+            item_groups: item_groups.clone(),
+            group_index: Arc::new(group_index),
+            min_qualities: Arc::new(l.placed_items.values().map(|pi| instance.item(pi.item_id).min_quality).collect()),
+            bboxes: l.placed_items.values().map(|pi| pi.shape.bbox).collect(),
+            item_totals: vec![LossTotal::default(); size],
+            total: LossTotal::default(),
         };
 
         // Recompute the loss for all items
@@ -49,11 +170,19 @@ impl CollisionTracker {
         let pi = &l.placed_items[pk];
         let shape = &pi.shape;
 
-        // Reset all current loss values for the item
-        for i in 0..self.size {
-            self.pair_collisions[(idx, i)].loss = 0.0;
+        // Remove all current collisions of the item from the running totals
+        for (idx_other, e) in self.pair_collisions.row(idx).filter(|(_, e)| e.loss > 0.0) {
+            self.item_totals[idx_other].remove(e);
+            self.total.remove(e);
         }
+        self.total.remove(&self.container_collisions[idx]);
+        self.total.remove(&self.group_collisions[idx]);
+        self.item_totals[idx] = LossTotal::default();
+
+        // Reset all current loss values for the item
+        self.pair_collisions.reset_losses(idx);
         self.container_collisions[idx].loss = 0.0;
+        self.group_collisions[idx].loss = 0.0;
 
         // Compute which hazards are currently colliding with the item
         let mut collector = BasicHazardCollector::with_capacity(l.placed_items.len() + 1);
@@ -62,7 +191,8 @@ impl CollisionTracker {
         collector.remove_by_entity(&HazardEntity::from((pk, pi)));
 
         // For each colliding hazard, quantify the collision and store it in the tracker
-        for (_, haz) in collector.iter() {
+        let mut container_loss = 0.0;
+        for (hkey, haz) in collector.iter() {
             match haz {
                 HazardEntity::PlacedItem { pk: other_pk, .. } => {
                     let shape_other = &l.placed_items[*other_pk].shape;
@@ -70,27 +200,121 @@ impl CollisionTracker {
 
                     let loss = quantify_collision_poly_poly(shape, shape_other);
                     assert!(loss > 0.0, "loss for a collision should be > 0.0");
-                    self.pair_collisions[(idx, idx_other)].loss = loss;
+                    self.pair_collisions.set_loss(idx, idx_other, loss);
+
+                    let e = self.pair_collisions.get(idx, idx_other);
+                    self.item_totals[idx].add(&e);
+                    self.item_totals[idx_other].add(&e);
+                    self.total.add(&e);
                 }
                 HazardEntity::Exterior => {
                     let loss = quantify_collision_poly_container(shape, l.container.outer_cd.bbox);
                     assert!(loss > 0.0, "loss for a collision should be > 0.0");
-                    self.container_collisions[idx].loss = loss;
+                    container_loss += loss;
+                }
+                HazardEntity::InferiorQualityZone { .. } | HazardEntity::Hole { .. } => {
+                    if is_forbidden_zone(haz, self.min_qualities[idx]) {
+                        let loss = quantify_collision_poly_zone(shape, &l.cde().hazards_map[hkey].shape);
+                        assert!(loss > 0.0, "loss for a collision should be > 0.0");
+                        container_loss += loss;
+                    }
                 }
-                _ => unimplemented!("unsupported hazard entity"),
             }
         }
+
+        if container_loss > 0.0 {
+            self.container_collisions[idx].loss = container_loss;
+
+            let e = self.container_collisions[idx];
+            self.item_totals[idx].add(&e);
+            self.total.add(&e);
+        }
+
+        // The span of the item's group might have changed
+        self.bboxes[idx] = shape.bbox;
+        if let Some(g) = self.group_index.idx_group[idx] {
+            self.recompute_group_losses(g);
+        }
+
+        // Only the item's own total is checked here, the full check is done whenever the totals are recomputed
+        debug_assert!(self.item_totals[idx].approx_eq(&self.item_total_from_entries(idx)));
+    }
+
+    /// Every item of a group exceeds the maximum span by the same distance: the span of the entire group (which includes the item) minus the maximum
+    fn recompute_group_losses(&mut self, g: usize) {
+        let index = self.group_index.clone();
+        let max_x_span = self.item_groups.groups()[g].max_x_span;
+        let window = index.members[g].iter()
+            .fold(GroupWindow::empty(max_x_span), |w, &idx| w.extend(self.bboxes[idx].x_min, self.bboxes[idx].x_max));
+        let excess = (window.span() - max_x_span).max(0.0);
+
+        for &idx in &index.members[g] {
+            let e = self.group_collisions[idx];
+            self.item_totals[idx].remove(&e);
+            self.total.remove(&e);
+
+            self.group_collisions[idx].loss = match excess > 0.0 {
+                true => quantify_group_span_violation(excess, self.bboxes[idx].height(), index.penalties[idx]),
+                false => 0.0,
+            };
+
+            let e = self.group_collisions[idx];
+            self.item_totals[idx].add(&e);
+            self.total.add(&e);
+        }
+    }
+
+    /// Sums all entries involving the item from scratch
+    fn item_total_from_entries(&self, idx: usize) -> LossTotal {
+        let mut t = LossTotal::default();
+        self.pair_collisions.row(idx).for_each(|(_, e)| t.add(e));
+        t.add(&self.container_collisions[idx]);
+        t.add(&self.group_collisions[idx]);
+        t
+    }
+
+    /// Sums all entries from scratch, per item and in total
+    fn compute_totals(&self) -> (Vec<LossTotal>, LossTotal) {
+        let item_totals = (0..self.size)
+            .map(|idx| self.item_total_from_entries(idx))
+            .collect();
+        let mut total = LossTotal::default();
+        self.pair_collisions.entries()
+            .chain(self.container_collisions.iter())
+            .chain(self.group_collisions.iter())
+            .for_each(|e| total.add(e));
+        (item_totals, total)
+    }
+
+    /// Replaces the running totals with a full recomputation, needed whenever weights change
+    fn recompute_totals(&mut self) {
+        (self.item_totals, self.total) = self.compute_totals();
+    }
+
+    /// Checks whether the running totals match a full recomputation
+    pub fn totals_match_entries(&self) -> bool {
+        let (item_totals, total) = self.compute_totals();
+        self.item_totals.iter().zip(item_totals.iter())
+            .chain(std::iter::once((&self.total, &total)))
+            .all(|(a, b)| a.approx_eq(b))
     }
 
     pub fn restore_but_keep_weights(&mut self, cts: &CTSnapshot, layout: &Layout) {
+        // The running totals are about to be replaced, verify the ones accumulated so far
+        debug_assert!(self.totals_match_entries());
         //Copy the loss and keys, but keep the weights
         self.pk_idx_map = cts.pk_idx_map.clone();
-        self.pair_collisions.data.iter_mut()
-            .zip(cts.pair_collisions.data.iter())
-            .for_each(|(a, b)| a.loss = b.loss);
+        self.pair_collisions.copy_losses_from(&cts.pair_collisions);
         self.container_collisions.iter_mut()
             .zip(cts.container_collisions.iter())
             .for_each(|(a, b)| a.loss = b.loss);
+        self.group_collisions.iter_mut()
+            .zip(cts.group_collisions.iter())
+            .for_each(|(a, b)| a.loss = b.loss);
+        self.group_index = cts.group_index.clone();
+        self.min_qualities = cts.min_qualities.clone();
+        self.bboxes.clone_from(&cts.bboxes);
+        self.recompute_totals();
         debug_assert!(tracker_matches_layout(self, layout));
     }
 
@@ -111,15 +335,17 @@ impl CollisionTracker {
 
     /// Algorithm 8 from https://doi.org/10.48550/arXiv.2509.13329
     pub fn update_weights(&mut self) {
+        // The running totals are about to be replaced, verify the ones accumulated so far
+        debug_assert!(self.totals_match_entries());
         // Find the maximum loss across all entries
-        let max_loss = self.pair_collisions.data.iter()
+        let max_loss = self.pair_collisions.entries()
             .chain(self.container_collisions.iter())
+            .chain(self.group_collisions.iter())
             .map(|e| e.loss)
             .fold(0.0, |a, b| a.max(b));
 
         // Go over all entries (pairs) and modify their weights.
-        for e in self.pair_collisions.data.iter_mut()
-            .chain(self.container_collisions.iter_mut()) {
+        let update_weight = |e: &mut CTEntry| {
             let multiplier = match e.loss == 0.0 {
                 true => {
                     // No collision at the moment, slowly decay the weight back to 1.0
@@ -131,12 +357,16 @@ impl CollisionTracker {
                 },
             };
             e.weight = (e.weight * multiplier).max(1.0);
-        }
+        };
+        self.pair_collisions.modify_entries(update_weight);
+        self.container_collisions.iter_mut().for_each(update_weight);
+        self.group_collisions.iter_mut().for_each(update_weight);
+        self.recompute_totals();
     }
 
     pub fn get_pair_weight(&self, pk1: PItemKey, pk2: PItemKey) -> f32 {
         let (idx1, idx2) = (self.pk_idx_map[pk1], self.pk_idx_map[pk2]);
-        self.pair_collisions[(idx1, idx2)].weight
+        self.pair_collisions.get(idx1, idx2).weight
     }
 
     pub fn get_container_weight(&self, pk: PItemKey) -> f32 {
@@ -144,11 +374,36 @@ impl CollisionTracker {
         self.container_collisions[idx].weight
     }
 
+    pub fn get_group_weight(&self, pk: PItemKey) -> f32 {
+        let idx = self.pk_idx_map[pk];
+        self.group_collisions[idx].weight
+    }
+
+    /// Horizontal extent of the other items of the item's group, `None` if the item is not part of a group
+    pub fn get_group_window(&self, pk: PItemKey) -> Option<GroupWindow> {
+        let idx = self.pk_idx_map[pk];
+        let g = self.group_index.idx_group[idx]?;
+        let window = self.group_index.members[g].iter()
+            .filter(|&&other| other != idx)
+            .fold(GroupWindow::empty(self.item_groups.groups()[g].max_x_span), |w, &other| w.extend(self.bboxes[other].x_min, self.bboxes[other].x_max));
+        Some(window)
+    }
+
+    /// Minimum quality of the zones the item is allowed to overlap, `None` if it requires full quality
+    pub fn get_min_quality(&self, pk: PItemKey) -> Option<usize> {
+        self.min_qualities[self.pk_idx_map[pk]]
+    }
+
+    /// Shape penalty of the item, used to quantify group span violations
+    pub fn get_group_penalty(&self, pk: PItemKey) -> f32 {
+        self.group_index.penalties[self.pk_idx_map[pk]]
+    }
+
     /// Algorithm 1 from https://doi.org/10.48550/arXiv.2509.13329
     /// Evaluations between item pairs are stored in this data-structure for quick and easy retrieval.
     pub fn get_pair_loss(&self, pk1: PItemKey, pk2: PItemKey) -> f32 {
         let (idx1, idx2) = (self.pk_idx_map[pk1], self.pk_idx_map[pk2]);
-        self.pair_collisions[(idx1, idx2)].loss
+        self.pair_collisions.get(idx1, idx2).loss
     }
 
     pub fn get_container_loss(&self, pk: PItemKey) -> f32 {
@@ -156,46 +411,25 @@ impl CollisionTracker {
         self.container_collisions[idx].loss
     }
 
-    pub fn get_loss(&self, pk: PItemKey) -> f32 {
+    pub fn get_group_loss(&self, pk: PItemKey) -> f32 {
         let idx = self.pk_idx_map[pk];
+        self.group_collisions[idx].loss
+    }
 
-        let pair_loss = (0..self.size)
-            .map(|i| self.pair_collisions[(idx, i)].loss)
-            .sum::<f32>();
-
-        self.container_collisions[idx].loss + pair_loss
+    pub fn get_loss(&self, pk: PItemKey) -> f32 {
+        self.item_totals[self.pk_idx_map[pk]].loss
     }
 
     pub fn get_weighted_loss(&self, pk: PItemKey) -> f32 {
-        let idx = self.pk_idx_map[pk];
-
-        let w_pair_loss = (0..self.size)
-            .map(|i| self.pair_collisions[(idx, i)].weighted_loss())
-            .sum::<f32>();
-
-        self.container_collisions[idx].weighted_loss() + w_pair_loss
+        self.item_totals[self.pk_idx_map[pk]].weighted_loss
     }
 
     pub fn get_total_loss(&self) -> f32 {
-        let cont_o = self.container_collisions.iter().map(|e| e.loss).sum::<f32>();
-
-        let pair_o = self.pair_collisions.data.iter()
-            .map(|e| e.loss)
-            .sum::<f32>();
-
-        cont_o + pair_o
+        self.total.loss
     }
 
     pub fn get_total_weighted_loss(&self) -> f32 {
-        let cont_w_o = self.container_collisions.iter()
-            .map(|e| e.weighted_loss())
-            .sum::<f32>();
-
-        let pair_w_o = self.pair_collisions.data.iter()
-            .map(|e| e.weighted_loss())
-            .sum::<f32>();
-
-        cont_w_o + pair_w_o
+        self.total.weighted_loss
     }
 }
 
@@ -209,4 +443,41 @@ impl CTEntry {
     pub fn weighted_loss(&self) -> f32 {
         self.weight * self.loss
     }
-}
\ No newline at end of file
+}
+/// Running sum of the (weighted) loss of a set of collisions.
+/// The number of collisions is tracked as well, so that the sums are exactly zero when there are no collisions left,
+/// regardless of any floating point errors accumulated by the incremental updates.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LossTotal {
+    pub loss: f32,
+    pub weighted_loss: f32,
+    pub n_collisions: usize,
+}
+
+impl LossTotal {
+    fn add(&mut self, e: &CTEntry) {
+        if e.loss > 0.0 {
+            self.loss += e.loss;
+            self.weighted_loss += e.weighted_loss();
+            self.n_collisions += 1;
+        }
+    }
+
+    fn remove(&mut self, e: &CTEntry) {
+        if e.loss > 0.0 {
+            self.n_collisions -= 1;
+            match self.n_collisions {
+                0 => *self = LossTotal::default(),
+                _ => {
+                    self.loss -= e.loss;
+                    self.weighted_loss -= e.weighted_loss();
+                }
+            }
+        }
+    }
+
+    fn approx_eq(&self, other: &LossTotal) -> bool {
+        let close = |a: f32, b: f32| (a - b).abs() <= 1e-3 * f32::max(1.0, f32::max(a.abs(), b.abs()));
+        self.n_collisions == other.n_collisions && close(self.loss, other.loss) && close(self.weighted_loss, other.weighted_loss)
+    }
+}
@@ -0,0 +1,61 @@
+use crate::consts::{ADAPTIVE_SAMPLE_FACTOR_RANGE, ADAPTIVE_SUCCESS_EMA_ALPHA};
+use crate::sample::search::SampleConfig;
+use jagua_rs::entities::Instance;
+use jagua_rs::probs::spp::entities::SPInstance;
+
+/// Adapts the sampling budget of [`search_placement`](crate::sample::search::search_placement) to every item, based on:
+/// - its size relative to the container, compared to the average item:
+///   small items receive more container-wide samples (to find holes), large items more focussed samples.
+/// - its recent move success: items that keep colliding after being moved receive more samples, items that are easily
+///   separated receive less.
+#[derive(Debug, Clone)]
+pub struct AdaptiveBudget {
+    /// Relative size of every item (indexed by item id): the square root of its convex hull area divided by the average one
+    rel_sizes: Vec<f32>,
+    /// Exponential moving average of the fraction of moves of every item (indexed by item id) that resolved all its collisions
+    success_rates: Vec<f32>,
+}
+
+impl AdaptiveBudget {
+    pub fn new(instance: &SPInstance) -> Self {
+        let ch_area = |id: usize| instance.item(id).shape_cd.surrogate().convex_hull_area;
+        let total_qty = instance.items.iter().map(|(_, qty)| *qty).sum::<usize>();
+        let avg_area = instance.items.iter()
+            .map(|(item, qty)| ch_area(item.id) * *qty as f32)
+            .sum::<f32>() / total_qty as f32;
+
+        Self {
+            rel_sizes: (0..instance.items.len()).map(|id| (ch_area(id) / avg_area).sqrt()).collect(),
+            success_rates: vec![0.5; instance.items.len()],
+        }
+    }
+
+    /// Sample configuration for a specific item, derived from the base configuration
+    pub fn sample_config(&self, item_id: usize, base: SampleConfig) -> SampleConfig {
+        let (min_f, max_f) = ADAPTIVE_SAMPLE_FACTOR_RANGE;
+        let rel_size = self.rel_sizes[item_id];
+        // Between 0.5 (always successful) and 1.5 (never successful)
+        let success_factor = 1.5 - self.success_rates[item_id];
+
+        let container_factor = (success_factor / rel_size).clamp(min_f, max_f);
+        let focussed_factor = (success_factor * rel_size).clamp(min_f, max_f);
+
+        let scale = |n: usize, f: f32| match n {
+            0 => 0,
+            n => usize::max(1, (n as f32 * f).round() as usize),
+        };
+
+        SampleConfig {
+            n_container_samples: scale(base.n_container_samples, container_factor),
+            n_focussed_samples: scale(base.n_focussed_samples, focussed_factor),
+            ..base
+        }
+    }
+
+    /// Registers whether a move of an item resolved all of its collisions
+    pub fn report_move(&mut self, item_id: usize, success: bool) {
+        let rate = &mut self.success_rates[item_id];
+        let outcome = if success { 1.0 } else { 0.0 };
+        *rate += ADAPTIVE_SUCCESS_EMA_ALPHA * (outcome - *rate);
+    }
+}
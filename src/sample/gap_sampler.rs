@@ -0,0 +1,134 @@
+use crate::consts::{GAP_GRID_RESOLUTION, GAP_SAMPLER_N_CANDIDATES};
+use crate::sample::uniform_sampler::UniformBBoxSampler;
+use jagua_rs::entities::{Layout, PlacedItem};
+use jagua_rs::geometry::primitives::Rect;
+use jagua_rs::geometry::DTransformation;
+use rand::Rng;
+use std::borrow::Cow;
+use tap::Tap;
+
+/// A sampler that biases container-wide samples toward empty regions of the layout.
+/// Draws a number of uniform candidates and keeps the one whose bounding box overlaps the least with occupied space,
+/// according to an [`OccupancyGrid`].
+#[derive(Clone, Debug)]
+pub struct GapAwareSampler<'a> {
+    uniform: UniformBBoxSampler,
+    grid: Cow<'a, OccupancyGrid>,
+    /// Sorted cells covered by the item being moved (once per covering pole), these are considered free
+    ignored_cells: Vec<usize>,
+}
+
+impl<'a> GapAwareSampler<'a> {
+    /// `ignored` is the item being moved, the space it occupies is considered free.
+    pub fn new(uniform: UniformBBoxSampler, grid: Cow<'a, OccupancyGrid>, ignored: Option<&PlacedItem>) -> Self {
+        let ignored_cells = ignored
+            .map(|pi| grid.covered_cells(pi).tap_mut(|cells| cells.sort_unstable()))
+            .unwrap_or_default();
+        Self { uniform, grid, ignored_cells }
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> DTransformation {
+        let mut best: Option<(DTransformation, f32)> = None;
+        for _ in 0..GAP_SAMPLER_N_CANDIDATES {
+            let (dt, bbox) = self.uniform.sample_with_bbox(rng);
+            let occupancy = self.grid.occupancy(bbox, &self.ignored_cells);
+            if occupancy == 0.0 {
+                return dt;
+            }
+            if best.is_none_or(|(_, best_occ)| occupancy < best_occ) {
+                best = Some((dt, occupancy));
+            }
+        }
+        best.map(|(dt, _)| dt).unwrap()
+    }
+}
+
+/// Coarse grid over the container which approximates which regions are occupied by placed items.
+/// A cell is occupied if its center lies within any pole of a placed item.
+/// Kept up to date incrementally by registering every placed and removed item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OccupancyGrid {
+    bbox: Rect,
+    cell_size: f32,
+    n_cols: usize,
+    n_rows: usize,
+    /// Number of poles covering the center of every cell
+    counts: Vec<u32>,
+}
+
+impl OccupancyGrid {
+    /// Creates a grid containing all items placed in the layout
+    pub fn new(l: &Layout) -> Self {
+        let bbox = l.container.outer_cd.bbox;
+        let n_rows = GAP_GRID_RESOLUTION;
+        let cell_size = bbox.height() / n_rows as f32;
+        let n_cols = usize::max(1, (bbox.width() / cell_size).ceil() as usize);
+
+        let mut grid = Self { bbox, cell_size, n_cols, n_rows, counts: vec![0; n_rows * n_cols] };
+        l.placed_items.values().for_each(|pi| grid.register_placement(pi));
+        grid
+    }
+
+    /// Marks the space covered by a newly placed item as occupied
+    pub fn register_placement(&mut self, pi: &PlacedItem) {
+        for idx in self.covered_cells(pi) {
+            self.counts[idx] += 1;
+        }
+    }
+
+    /// Releases the space covered by a removed item
+    pub fn register_removal(&mut self, pi: &PlacedItem) {
+        for idx in self.covered_cells(pi) {
+            self.counts[idx] -= 1;
+        }
+    }
+
+    /// Cells with their center inside a pole of the item, once per covering pole
+    fn covered_cells(&self, pi: &PlacedItem) -> Vec<usize> {
+        let bbox = self.bbox;
+        let mut cells = vec![];
+        for pole in pi.shape.surrogate().poles.iter() {
+            let (cx, cy, r) = (pole.center.x(), pole.center.y(), pole.radius);
+            let (cols, rows) = (
+                cell_range(cx - r - bbox.x_min, cx + r - bbox.x_min, self.cell_size, self.n_cols),
+                cell_range(cy - r - bbox.y_min, cy + r - bbox.y_min, self.cell_size, self.n_rows),
+            );
+            for row in rows {
+                for col in cols.clone() {
+                    let (x, y) = (bbox.x_min + (col as f32 + 0.5) * self.cell_size, bbox.y_min + (row as f32 + 0.5) * self.cell_size);
+                    if (x - cx).powi(2) + (y - cy).powi(2) <= r * r {
+                        cells.push(row * self.n_cols + col);
+                    }
+                }
+            }
+        }
+        cells
+    }
+
+    /// Fraction of the cells with their center inside `rect` that are occupied, not counting the poles in `ignored_cells` (sorted).
+    /// If `rect` does not contain any cell center, the cell containing its centroid is used.
+    pub fn occupancy(&self, rect: Rect, ignored_cells: &[usize]) -> f32 {
+        let mut cols = cell_range(rect.x_min - self.bbox.x_min, rect.x_max - self.bbox.x_min, self.cell_size, self.n_cols);
+        let mut rows = cell_range(rect.y_min - self.bbox.y_min, rect.y_max - self.bbox.y_min, self.cell_size, self.n_rows);
+        if cols.is_empty() || rows.is_empty() {
+            let centroid = rect.centroid();
+            let col = (((centroid.x() - self.bbox.x_min) / self.cell_size) as usize).min(self.n_cols - 1);
+            let row = (((centroid.y() - self.bbox.y_min) / self.cell_size) as usize).min(self.n_rows - 1);
+            (cols, rows) = (col..col + 1, row..row + 1);
+        }
+        let n_ignored = |idx: usize| ignored_cells.partition_point(|&i| i <= idx) - ignored_cells.partition_point(|&i| i < idx);
+        let n_occupied = rows.clone()
+            .flat_map(|row| cols.clone().map(move |col| row * self.n_cols + col))
+            .filter(|&idx| self.counts[idx] as usize > n_ignored(idx))
+            .count();
+
+        n_occupied as f32 / (cols.len() * rows.len()) as f32
+    }
+}
+
+/// Range of cells (along one axis) with their center within `[min, max]`, relative to the start of the grid
+fn cell_range(min: f32, max: f32, cell_size: f32, n_cells: usize) -> std::ops::Range<usize> {
+    let start = (min / cell_size - 0.5).ceil().max(0.0) as usize;
+    let end = ((max / cell_size - 0.5).floor() + 1.0).clamp(0.0, n_cells as f32) as usize;
+    start.min(end)..end
+}
@@ -1,4 +1,7 @@
+pub mod adaptive;
 mod best_samples;
-mod coord_descent;
+pub mod gap_sampler;
+pub mod refine;
+pub mod rotations;
 pub mod search;
 pub mod uniform_sampler;
\ No newline at end of file
@@ -1,30 +1,26 @@
 use crate::consts::{CD_STEP_FAIL, CD_STEP_SUCCESS};
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::sample::refine::{RefineConfig, Refiner};
 use jagua_rs::geometry::DTransformation;
 use log::trace;
 use rand::{Rng, RngExt};
 use std::cmp::Ordering;
 use std::fmt::Debug;
 
-#[derive(Clone, Debug, Copy)]
-pub struct CDConfig {
-    /// Initial step size for the coordinate descent
-    pub t_step_init: f32,
-    /// Limit for the step size, below which no more candidates are generated
-    pub t_step_limit: f32,
-    /// Initial step size for the rotation wiggle axis
-    pub r_step_init: f32,
-    /// Limit for the rotation wiggle step size, below which no more candidates are generated
-    pub r_step_limit: f32,
-    /// Defines whether the wiggle axis (rotation) is enabled
-    pub wiggle: bool,
+/// Refines samples with [`refine_coord_desc`]
+pub struct CoordDescentRefiner;
+
+impl Refiner for CoordDescentRefiner {
+    fn refine(&self, start: (DTransformation, SampleEval), evaluator: &mut impl SampleEvaluator, config: RefineConfig, rng: &mut impl Rng) -> (DTransformation, SampleEval) {
+        refine_coord_desc(start, evaluator, config, rng)
+    }
 }
 
 /// Refines an initial 'sample' (transformation and evaluation) into a local minimum using a coordinate descent inspired algorithm.
 pub fn refine_coord_desc(
     (init_dt, init_eval): (DTransformation, SampleEval),
     evaluator: &mut impl SampleEvaluator,
-    cd_config: CDConfig,
+    cd_config: RefineConfig,
     rng: &mut impl Rng,
 ) -> (DTransformation, SampleEval) {
     let n_evals_init = evaluator.n_evals();
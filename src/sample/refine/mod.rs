@@ -0,0 +1,53 @@
+use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::sample::refine::coord_descent::CoordDescentRefiner;
+use crate::sample::refine::nelder_mead::NelderMeadRefiner;
+use crate::sample::refine::pattern_search::PatternSearchRefiner;
+use crate::sample::refine::pole_gradient::PoleGradientRefiner;
+use jagua_rs::geometry::DTransformation;
+use rand::Rng;
+
+pub mod coord_descent;
+pub mod nelder_mead;
+pub mod pattern_search;
+pub mod pole_gradient;
+
+#[derive(Clone, Debug, Copy)]
+pub struct RefineConfig {
+    /// Initial step size for translations
+    pub t_step_init: f32,
+    /// Limit for the translation step size, below which no more candidates are generated
+    pub t_step_limit: f32,
+    /// Initial step size for rotations
+    pub r_step_init: f32,
+    /// Limit for the rotation step size, below which no more candidates are generated
+    pub r_step_limit: f32,
+    /// Defines whether the rotation may be refined as well (the wiggle axis)
+    pub wiggle: bool,
+}
+
+/// Local search which refines a sample (transformation and evaluation) into a local minimum.
+/// The returned sample is never worse than the initial one.
+pub trait Refiner {
+    fn refine(&self, start: (DTransformation, SampleEval), evaluator: &mut impl SampleEvaluator, config: RefineConfig, rng: &mut impl Rng) -> (DTransformation, SampleEval);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RefinerType {
+    /// Two candidates along one random axis at a time (see [`CoordDescentRefiner`])
+    CoordDescent,
+    /// Candidates along all axes at every step (see [`PatternSearchRefiner`])
+    PatternSearch,
+    /// Simplex over (x, y, θ) (see [`NelderMeadRefiner`])
+    NelderMead,
+    /// Follows the direction in which the overlapping poles push the item (see [`PoleGradientRefiner`])
+    PoleGradient,
+}
+
+pub fn refine(refiner: RefinerType, start: (DTransformation, SampleEval), evaluator: &mut impl SampleEvaluator, config: RefineConfig, rng: &mut impl Rng) -> (DTransformation, SampleEval) {
+    match refiner {
+        RefinerType::CoordDescent => CoordDescentRefiner.refine(start, evaluator, config, rng),
+        RefinerType::PatternSearch => PatternSearchRefiner.refine(start, evaluator, config, rng),
+        RefinerType::NelderMead => NelderMeadRefiner.refine(start, evaluator, config, rng),
+        RefinerType::PoleGradient => PoleGradientRefiner.refine(start, evaluator, config, rng),
+    }
+}
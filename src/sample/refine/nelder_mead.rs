@@ -0,0 +1,91 @@
+use crate::consts::NM_MAX_ITERATIONS;
+use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::sample::refine::{RefineConfig, Refiner};
+use jagua_rs::geometry::DTransformation;
+use rand::Rng;
+
+/// Nelder-Mead simplex search over (x, y) or (x, y, θ) if rotation is allowed.
+/// The initial simplex spans the initial step sizes, the search stops when it has collapsed below the step limits.
+pub struct NelderMeadRefiner;
+
+type Vertex = ([f32; 3], SampleEval);
+
+impl Refiner for NelderMeadRefiner {
+    fn refine(&self, (init_dt, init_eval): (DTransformation, SampleEval), evaluator: &mut impl SampleEvaluator, config: RefineConfig, _rng: &mut impl Rng) -> (DTransformation, SampleEval) {
+        let n_dims = if config.wiggle { 3 } else { 2 };
+        let to_dt = |v: [f32; 3]| DTransformation::new(v[2], (v[0], v[1]));
+
+        let (tx, ty) = init_dt.translation();
+        let origin = [tx, ty, init_dt.rotation()];
+        let init_steps = [config.t_step_init, config.t_step_init, config.r_step_init];
+
+        let mut simplex: Vec<Vertex> = vec![(origin, init_eval)];
+        for d in 0..n_dims {
+            let mut v = origin;
+            v[d] += init_steps[d];
+            simplex.push((v, evaluator.evaluate_sample(to_dt(v), None)));
+        }
+
+        for _ in 0..NM_MAX_ITERATIONS {
+            simplex.sort_by_key(|(_, eval)| *eval);
+            let (best, worst) = (simplex[0], simplex[n_dims]);
+
+            // Stop when the simplex has collapsed below the step limits
+            let spread = |d: usize| simplex.iter().map(|(v, _)| (v[d] - best.0[d]).abs()).fold(0.0, f32::max);
+            let t_converged = spread(0) < config.t_step_limit && spread(1) < config.t_step_limit;
+            let r_converged = !config.wiggle || spread(2) < config.r_step_limit;
+            if t_converged && r_converged {
+                break;
+            }
+
+            // Centroid of all vertices except the worst one
+            let mut centroid = [0.0; 3];
+            for (v, _) in &simplex[..n_dims] {
+                for d in 0..3 {
+                    centroid[d] += v[d] / n_dims as f32;
+                }
+            }
+            // Point on the line through the worst vertex and the centroid
+            let point = |coef: f32| -> [f32; 3] {
+                let mut p = worst.0;
+                for d in 0..n_dims {
+                    p[d] = centroid[d] + coef * (centroid[d] - worst.0[d]);
+                }
+                p
+            };
+            let mut eval_point = |coef: f32| -> Vertex {
+                let p = point(coef);
+                (p, evaluator.evaluate_sample(to_dt(p), Some(worst.1)))
+            };
+
+            let reflected = eval_point(1.0);
+            if reflected.1 < best.1 {
+                let expanded = eval_point(2.0);
+                simplex[n_dims] = if expanded.1 < reflected.1 { expanded } else { reflected };
+            } else if reflected.1 < simplex[n_dims - 1].1 {
+                simplex[n_dims] = reflected;
+            } else {
+                let contracted = match reflected.1 < worst.1 {
+                    true => eval_point(0.5),
+                    false => eval_point(-0.5),
+                };
+                if contracted.1 < SampleEval::min(reflected.1, worst.1) {
+                    simplex[n_dims] = contracted;
+                } else {
+                    // Shrink all vertices toward the best one
+                    for vertex in simplex.iter_mut().skip(1) {
+                        for d in 0..n_dims {
+                            vertex.0[d] = best.0[d] + 0.5 * (vertex.0[d] - best.0[d]);
+                        }
+                        vertex.1 = evaluator.evaluate_sample(to_dt(vertex.0), None);
+                    }
+                }
+            }
+        }
+
+        simplex.iter()
+            .min_by_key(|(_, eval)| *eval)
+            .map(|(v, eval)| (to_dt(*v), *eval))
+            .unwrap()
+    }
+}
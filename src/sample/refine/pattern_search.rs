@@ -0,0 +1,52 @@
+use crate::consts::{CD_STEP_FAIL, CD_STEP_SUCCESS};
+use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::sample::refine::{RefineConfig, Refiner};
+use jagua_rs::geometry::DTransformation;
+use rand::Rng;
+
+/// Compass search: evaluates the candidates on either side of the current position along all axes (x, y and rotation)
+/// and moves to the best one if it improves. All step sizes grow on success and shrink on failure.
+pub struct PatternSearchRefiner;
+
+impl Refiner for PatternSearchRefiner {
+    fn refine(&self, (init_dt, init_eval): (DTransformation, SampleEval), evaluator: &mut impl SampleEvaluator, config: RefineConfig, _rng: &mut impl Rng) -> (DTransformation, SampleEval) {
+        let (mut pos, mut eval) = (init_dt, init_eval);
+        let (mut t_step, mut r_step) = (config.t_step_init, config.r_step_init);
+
+        loop {
+            let translate = t_step >= config.t_step_limit;
+            let rotate = config.wiggle && r_step >= config.r_step_limit;
+            if !translate && !rotate {
+                break;
+            }
+
+            let (tx, ty) = pos.translation();
+            let r = pos.rotation();
+            let translations = [(tx + t_step, ty), (tx - t_step, ty), (tx, ty + t_step), (tx, ty - t_step)]
+                .map(|t| DTransformation::new(r, t));
+            let rotations = [r + r_step, r - r_step]
+                .map(|r| DTransformation::new(r, (tx, ty)));
+
+            let candidates = translations.iter().filter(|_| translate)
+                .chain(rotations.iter().filter(|_| rotate));
+
+            // Poll all candidates, tightening the upper bound as better ones are found
+            let mut best = (pos, eval);
+            for &c in candidates {
+                let c_eval = evaluator.evaluate_sample(c, Some(best.1));
+                if c_eval < best.1 {
+                    best = (c, c_eval);
+                }
+            }
+
+            let m = match best.1 < eval {
+                true => CD_STEP_SUCCESS,
+                false => CD_STEP_FAIL,
+            };
+            (pos, eval) = best;
+            t_step *= m;
+            r_step *= m;
+        }
+        (pos, eval)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::consts::{CD_STEP_FAIL, CD_STEP_SUCCESS};
+use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
+use crate::sample::refine::pattern_search::PatternSearchRefiner;
+use crate::sample::refine::{RefineConfig, Refiner};
+use jagua_rs::geometry::DTransformation;
+use rand::Rng;
+
+/// Uses the overlap between the poles of the item and those of the hazards it collides with as a pseudo-gradient:
+/// translates the item in the direction it is pushed in and rotates it along the torque this push exerts.
+/// Falls back to [`PatternSearchRefiner`] for evaluators which cannot provide an overlap direction.
+pub struct PoleGradientRefiner;
+
+impl Refiner for PoleGradientRefiner {
+    fn refine(&self, (init_dt, init_eval): (DTransformation, SampleEval), evaluator: &mut impl SampleEvaluator, config: RefineConfig, rng: &mut impl Rng) -> (DTransformation, SampleEval) {
+        let (mut pos, mut eval) = (init_dt, init_eval);
+        let (mut t_step, mut r_step) = (config.t_step_init, config.r_step_init);
+
+        let mut first = true;
+        loop {
+            let translate = t_step >= config.t_step_limit;
+            let rotate = config.wiggle && r_step >= config.r_step_limit;
+            if !translate && !rotate {
+                break;
+            }
+
+            let Some([fx, fy, torque]) = evaluator.overlap_direction(pos) else {
+                if first && matches!(eval, SampleEval::Collision { .. }) {
+                    // The evaluator does not support overlap directions
+                    return PatternSearchRefiner.refine((pos, eval), evaluator, config, rng);
+                }
+                // No more collisions
+                break;
+            };
+            first = false;
+
+            let (tx, ty) = pos.translation();
+            let r = pos.rotation();
+            let norm = fx.hypot(fy);
+
+            let translated = (translate && norm > 0.0)
+                .then(|| DTransformation::new(r, (tx + fx / norm * t_step, ty + fy / norm * t_step)));
+            let rotated = (rotate && torque != 0.0)
+                .then(|| DTransformation::new(r + torque.signum() * r_step, (tx, ty)));
+
+            if translated.is_none() && rotated.is_none() {
+                break;
+            }
+
+            let mut best = (pos, eval);
+            if let Some(c) = translated {
+                let c_eval = evaluator.evaluate_sample(c, Some(eval));
+                t_step *= if c_eval < eval { CD_STEP_SUCCESS } else { CD_STEP_FAIL };
+                if c_eval < best.1 {
+                    best = (c, c_eval);
+                }
+            } else {
+                t_step *= CD_STEP_FAIL;
+            }
+            if let Some(c) = rotated {
+                let c_eval = evaluator.evaluate_sample(c, Some(eval));
+                r_step *= if c_eval < eval { CD_STEP_SUCCESS } else { CD_STEP_FAIL };
+                if c_eval < best.1 {
+                    best = (c, c_eval);
+                }
+            } else {
+                r_step *= CD_STEP_FAIL;
+            }
+            (pos, eval) = best;
+        }
+        (pos, eval)
+    }
+}
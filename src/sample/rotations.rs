@@ -0,0 +1,45 @@
+use crate::consts::{ROT_N_HULL_EDGES, ROT_N_NEIGHBOURS};
+use itertools::Itertools;
+use jagua_rs::entities::{Item, Layout, PItemKey};
+use jagua_rs::geometry::geo_traits::DistanceTo;
+use jagua_rs::geometry::normalize_rotation;
+use jagua_rs::geometry::primitives::{Point, Rect, SPolygon};
+use ordered_float::OrderedFloat;
+use std::cmp::Reverse;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Rotations which align one of the item's longest convex hull edges with the axes of the strip
+pub fn hull_aligned_rotations(item: &Item) -> Vec<f32> {
+    longest_hull_edge_angles(item.shape_cd.as_ref(), ROT_N_HULL_EDGES).into_iter()
+        .flat_map(|angle| (0..4).map(move |k| normalize_rotation(-angle + k as f32 * FRAC_PI_2)))
+        .collect()
+}
+
+/// Rotations which align the item's longest convex hull edge with the longest convex hull edge of
+/// the items surrounding the current placement (`ref_pk`) of the item.
+pub fn neighbour_aligned_rotations(l: &Layout, item: &Item, ref_pk: PItemKey) -> Vec<f32> {
+    let Some(&item_angle) = longest_hull_edge_angles(item.shape_cd.as_ref(), 1).first() else {
+        return vec![];
+    };
+    let ref_bbox = l.placed_items[ref_pk].shape.bbox;
+    let ref_centroid = ref_bbox.centroid();
+
+    l.placed_items.iter()
+        .filter(|(pk, pi)| *pk != ref_pk && Rect::intersection(pi.shape.bbox, ref_bbox).is_some())
+        .sorted_by_key(|(_, pi)| OrderedFloat(pi.shape.bbox.centroid().sq_distance_to(&ref_centroid)))
+        .take(ROT_N_NEIGHBOURS)
+        .filter_map(|(_, pi)| longest_hull_edge_angles(&pi.shape, 1).first().copied())
+        .flat_map(|neighbour_angle| [0.0, PI].map(|k| normalize_rotation(neighbour_angle - item_angle + k)))
+        .collect()
+}
+
+/// Angles of the `n` longest edges of the convex hull of the shape
+fn longest_hull_edge_angles(shape: &SPolygon, n: usize) -> Vec<f32> {
+    let hull = &shape.surrogate().convex_hull_indices;
+    (0..hull.len())
+        .map(|i| (shape.vertex(hull[i]), shape.vertex(hull[(i + 1) % hull.len()])))
+        .sorted_by_key(|(Point(x1, y1), Point(x2, y2))| Reverse(OrderedFloat((x2 - x1).powi(2) + (y2 - y1).powi(2))))
+        .take(n)
+        .map(|(Point(x1, y1), Point(x2, y2))| (y2 - y1).atan2(x2 - x1))
+        .collect()
+}
@@ -1,23 +1,52 @@
 use crate::consts::{PRE_REFINE_CD_R_STEPS, PRE_REFINE_CD_TL_RATIOS, SND_REFINE_CD_R_STEPS, SND_REFINE_CD_TL_RATIOS, UNIQUE_SAMPLE_THRESHOLD};
 use crate::eval::sample_eval::{SampleEval, SampleEvaluator};
 use crate::sample::best_samples::BestSamples;
-use crate::sample::coord_descent::{refine_coord_desc, CDConfig};
+use crate::sample::refine::{refine, RefineConfig, RefinerType};
+use crate::sample::gap_sampler::{GapAwareSampler, OccupancyGrid};
+use crate::sample::rotations::neighbour_aligned_rotations;
 use crate::sample::uniform_sampler::UniformBBoxSampler;
+use crate::zones::forbidden_zones;
+use itertools::Either;
 use jagua_rs::entities::{Item, Layout, PItemKey};
 use jagua_rs::geometry::geo_enums::RotationRange;
 use jagua_rs::geometry::DTransformation;
 use log::debug;
 use rand::Rng;
+use std::borrow::Cow;
 
 #[derive(Debug, Clone, Copy)]
 pub struct SampleConfig {
     pub n_container_samples: usize,
     pub n_focussed_samples: usize,
     pub n_coord_descents: usize,
+    /// Adapt the number of samples to every item (see [`AdaptiveBudget`](crate::sample::adaptive::AdaptiveBudget))
+    /// and stop sampling as soon as a sample without any collision is found
+    pub adaptive: bool,
+    /// Sampler used for the container-wide samples
+    pub container_sampler: ContainerSampler,
+    /// Local search used to refine the best samples
+    pub refiner: RefinerType,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerSampler {
+    /// Uniformly within the container
+    Uniform,
+    /// Biased toward empty regions of the layout (see [`GapAwareSampler`])
+    GapAware,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SearchStats {
+    /// Total number of evaluations
+    pub n_evals: usize,
+    /// Number of evaluations spent refining samples
+    pub n_refine_evals: usize,
 }
 
 /// Algorithm 6 and Figure 7 from https://doi.org/10.48550/arXiv.2509.13329
-pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut evaluator: impl SampleEvaluator, sample_config: SampleConfig, rng: &mut impl Rng) -> (Option<(DTransformation, SampleEval)>, usize) {
+/// `grid` is an up-to-date [`OccupancyGrid`] of the layout, used by the gap-aware sampler if provided.
+pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, grid: Option<&OccupancyGrid>, mut evaluator: impl SampleEvaluator, sample_config: SampleConfig, rng: &mut impl Rng) -> (Option<(DTransformation, SampleEval)>, SearchStats) {
     let item_min_dim = f32::min(item.shape_cd.bbox.width(), item.shape_cd.bbox.height());
 
     let mut best_samples = BestSamples::new(sample_config.n_coord_descents, item_min_dim * UNIQUE_SAMPLE_THRESHOLD);
@@ -33,12 +62,27 @@ pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut e
             best_samples.report(dt, eval);
 
             //Create a uniform sampler focussed around the current placement
+            //(items with continuous rotation also sample rotations aligned with their neighbours and around the current one)
             let pi_bbox = l.placed_items[ref_pk].shape.bbox;
             UniformBBoxSampler::new(pi_bbox, item, l.container.outer_cd.bbox)
+                .map(|s| s.with_forbidden_zones(forbidden_zones(&l.container, item.min_quality)))
+                .map(|s| match item.allowed_rotation {
+                    RotationRange::Continuous => s.with_rotation_hints(&neighbour_aligned_rotations(l, item, ref_pk), Some(dt.rotation())),
+                    _ => s,
+                })
         }
         None => None,
     };
-    let container_sampler = UniformBBoxSampler::new(l.container.outer_cd.bbox, item, l.container.outer_cd.bbox);
+    let container_sampler = UniformBBoxSampler::new(l.container.outer_cd.bbox, item, l.container.outer_cd.bbox)
+        .map(|s| s.with_forbidden_zones(forbidden_zones(&l.container, item.min_quality)))
+        .map(|uniform| match sample_config.container_sampler {
+            ContainerSampler::Uniform => Either::Left(uniform),
+            ContainerSampler::GapAware => {
+                //Without a maintained grid, rasterize the layout from scratch
+                let grid = grid.map_or_else(|| Cow::Owned(OccupancyGrid::new(l)), Cow::Borrowed);
+                Either::Right(GapAwareSampler::new(uniform, grid, ref_pk.map(|pk| &l.placed_items[pk])))
+            }
+        });
 
     //Perform the focussed sampling
     if let Some(focussed_sampler) = focussed_sampler {
@@ -52,34 +96,53 @@ pub fn search_placement(l: &Layout, item: &Item, ref_pk: Option<PItemKey>, mut e
     //Perform the container-wide sampling
     if let Some(container_sampler) = container_sampler {
         for _ in 0..sample_config.n_container_samples {
-            let dt = container_sampler.sample(rng);
+            if sample_config.adaptive && is_clear(&best_samples) {
+                break;
+            }
+            let dt = match &container_sampler {
+                Either::Left(uniform) => uniform.sample(rng),
+                Either::Right(gap_aware) => gap_aware.sample(rng),
+            };
             let eval = evaluator.evaluate_sample(dt, Some(best_samples.upper_bound()));
             best_samples.report(dt, eval);
         }
     }
 
+    let n_sample_evals = evaluator.n_evals();
+
     //Refine some of the best random samples to a local minimum in two steps:
 
     //1. Do a first refinement of all 'best samples' using coordinate descent
-    for start in best_samples.samples.clone() {
-        let descended = refine_coord_desc(start, &mut evaluator, prerefine_cd_config(item), rng);
-        best_samples.report(descended.0, descended.1);
+    //   (unnecessary when adaptive and a collision-free sample has already been found)
+    if !(sample_config.adaptive && is_clear(&best_samples)) {
+        for start in best_samples.samples.clone() {
+            let descended = refine(sample_config.refiner, start, &mut evaluator, prerefine_cd_config(item), rng);
+            best_samples.report(descended.0, descended.1);
+        }
     }
 
 
     //2. Take the best one and do an even finer coordinate descent refinement
     let final_sample = best_samples.best().map(|s|
-        refine_coord_desc(s, &mut evaluator, final_refine_cd_config(item), rng)
+        refine(sample_config.refiner, s, &mut evaluator, final_refine_cd_config(item), rng)
     );
 
     debug!("[S] {} samples evaluated, final: {:?}",evaluator.n_evals(),final_sample);
-    (final_sample, evaluator.n_evals())
+    let stats = SearchStats {
+        n_evals: evaluator.n_evals(),
+        n_refine_evals: evaluator.n_evals() - n_sample_evals,
+    };
+    (final_sample, stats)
+}
+
+fn is_clear(best_samples: &BestSamples) -> bool {
+    matches!(best_samples.best(), Some((_, SampleEval::Clear { .. })))
 }
 
-fn prerefine_cd_config(item: &Item) -> CDConfig {
+fn prerefine_cd_config(item: &Item) -> RefineConfig {
     let item_min_dim = f32::min(item.shape_cd.bbox.width(), item.shape_cd.bbox.height());
     let wiggle = item.allowed_rotation == RotationRange::Continuous;
-    CDConfig {
+    RefineConfig {
         t_step_init: item_min_dim * PRE_REFINE_CD_TL_RATIOS.0,
         t_step_limit: item_min_dim * PRE_REFINE_CD_TL_RATIOS.1,
         r_step_init: PRE_REFINE_CD_R_STEPS.0,
@@ -88,10 +151,10 @@ fn prerefine_cd_config(item: &Item) -> CDConfig {
     }
 }
 
-fn final_refine_cd_config(item: &Item) -> CDConfig {
+fn final_refine_cd_config(item: &Item) -> RefineConfig {
     let item_min_dim = f32::min(item.shape_cd.bbox.width(), item.shape_cd.bbox.height());
     let wiggle = item.allowed_rotation == RotationRange::Continuous;
-    CDConfig {
+    RefineConfig {
         t_step_init: item_min_dim * SND_REFINE_CD_TL_RATIOS.0,
         t_step_limit: item_min_dim * SND_REFINE_CD_TL_RATIOS.1,
         r_step_init: SND_REFINE_CD_R_STEPS.0,
@@ -1,88 +1,184 @@
+use crate::consts::{ROT_FALLBACK_N_ROTATIONS, ROT_JITTER, ROT_JITTER_SAMPLE_RATIO, ROT_UNIFORM_SAMPLE_RATIO, ZONE_SAMPLE_ATTEMPTS};
+use crate::sample::rotations::hull_aligned_rotations;
+use crate::util::geometry::rotated_bbox;
 use itertools::Itertools;
 use jagua_rs::entities::Item;
 use jagua_rs::geometry::geo_enums::RotationRange;
-use jagua_rs::geometry::geo_traits::TransformableFrom;
-use jagua_rs::geometry::primitives::Rect;
+use jagua_rs::geometry::geo_traits::{CollidesWith, TransformableFrom};
+use jagua_rs::geometry::primitives::{Point, Rect, SPolygon};
 use jagua_rs::geometry::{normalize_rotation, DTransformation, Transformation};
 use ordered_float::OrderedFloat;
 use rand::prelude::IndexedRandom;
 use rand::{Rng, RngExt};
 use std::f32::consts::PI;
 use std::ops::Range;
-
-const ROT_N_SAMPLES: usize = 16; // number of rotations to sample for continuous rotation
+use std::sync::Arc;
 
 /// A sampler that creates uniform samples for an item within a bounding box
 #[derive(Clone, Debug)]
 pub struct UniformBBoxSampler {
     /// The list of possible rotations and their corresponding x and y ranges
     rot_entries: Vec<RotEntry>,
+    /// Only for items with continuous rotation: enables sampling of arbitrary rotations
+    cont_rot: Option<ContinuousRotation>,
+    /// Zones of the container the item should not overlap, samples centered in them are rejected (a limited number of times)
+    forbidden_zones: Vec<Arc<SPolygon>>,
 }
 
 #[derive(Clone, Debug)]
 struct RotEntry {
     pub r: f32,
+    /// Bounding box of the item in this rotation (before translation)
+    pub r_bbox: Rect,
     pub x_range: Range<f32>,
     pub y_range: Range<f32>,
 }
 
+#[derive(Clone, Debug)]
+struct ContinuousRotation {
+    /// Vertices of the convex hull of the item, used to compute its bounding box in any rotation
+    hull: Vec<Point>,
+    sample_bbox: Rect,
+    container_bbox: Rect,
+    /// Current rotation of the item, which is sampled with some jitter
+    ref_rotation: Option<f32>,
+}
+
 impl UniformBBoxSampler {
+    /// Returns `None` if the item cannot be placed within both bounding boxes in any of its allowed rotations
     pub fn new(sample_bbox: Rect, item: &Item, container_bbox: Rect) -> Option<Self> {
         let rotations = match &item.allowed_rotation {
-            RotationRange::None => &vec![0.0],
-            RotationRange::Discrete(r) => r,
-            RotationRange::Continuous => {
-                // for continuous rotation, we sample a set of rotations spaced evenly
-                let step = (2.0 * PI) / ROT_N_SAMPLES as f32;
-                &(0..ROT_N_SAMPLES)
-                    .map(|i| i as f32 * step)
-                    .collect_vec()
-            }
+            RotationRange::None => vec![0.0],
+            RotationRange::Discrete(r) => r.clone(),
+            // for continuous rotation, the rotations aligning the item's longest edges with the strip are always available
+            RotationRange::Continuous => hull_aligned_rotations(item),
         };
 
         let mut shape_buffer = item.shape_cd.as_ref().clone();
 
-        let sample_x_range = sample_bbox.x_min..sample_bbox.x_max;
-        let sample_y_range = sample_bbox.y_min..sample_bbox.y_max;
-
         // for each possible rotation, calculate the sample ranges (x and y)
         // where the item resides fully inside the container and is within the sample bounding box
-        let rot_entries = rotations.iter()
+        let mut rot_entries = rotations.iter()
             .filter_map(|&r| {
                 let r_shape_bbox = shape_buffer.transform_from(item.shape_cd.as_ref(), &Transformation::from_rotation(r)).bbox;
+                RotEntry::new(r, r_shape_bbox, sample_bbox, container_bbox)
+            }).collect_vec();
 
-                //narrow the container range to account for the rotated shape
-                let cont_x_range = (container_bbox.x_min - r_shape_bbox.x_min)..(container_bbox.x_max - r_shape_bbox.x_max);
-                let cont_y_range = (container_bbox.y_min - r_shape_bbox.y_min)..(container_bbox.y_max - r_shape_bbox.y_max);
-
-                //intersect with the sample bbox
-                let x_range = intersect_range(&cont_x_range, &sample_x_range);
-                let y_range = intersect_range(&cont_y_range, &sample_y_range);
+        let cont_rot = match item.allowed_rotation {
+            RotationRange::Continuous => {
+                let shape = item.shape_cd.as_ref();
+                let hull = shape.surrogate().convex_hull_indices.iter().map(|&i| shape.vertex(i)).collect_vec();
+                Some(ContinuousRotation { hull, sample_bbox, container_bbox, ref_rotation: None })
+            }
+            _ => None,
+        };
 
-                //make sure the ranges are not empty
-                if x_range.is_empty() || y_range.is_empty() {
-                    None
-                } else {
-                    Some(RotEntry { r, x_range, y_range })
-                }
-            }).collect_vec();
+        if let Some(cont_rot) = &cont_rot && rot_entries.is_empty() {
+            // none of the hull-aligned rotations fit, fall back to any rotation that does
+            rot_entries = (0..ROT_FALLBACK_N_ROTATIONS)
+                .filter_map(|i| cont_rot.rot_entry(i as f32 * 2.0 * PI / ROT_FALLBACK_N_ROTATIONS as f32))
+                .collect_vec();
+        }
 
         match rot_entries.is_empty() {
             true => None,
-            false => Some(Self { rot_entries }),
+            false => Some(Self { rot_entries, cont_rot, forbidden_zones: vec![] }),
         }
     }
 
+    /// Adds extra rotations to sample from and the current rotation of the item (to be sampled with jitter).
+    /// Only has an effect for items with continuous rotation.
+    pub fn with_rotation_hints(mut self, hints: &[f32], ref_rotation: Option<f32>) -> Self {
+        if let Some(cont_rot) = &mut self.cont_rot {
+            cont_rot.ref_rotation = ref_rotation;
+            let entries = hints.iter().filter_map(|&r| cont_rot.rot_entry(r)).collect_vec();
+            self.rot_entries.extend(entries);
+        }
+        self
+    }
+
+    pub fn with_forbidden_zones(mut self, zones: Vec<Arc<SPolygon>>) -> Self {
+        self.forbidden_zones = zones;
+        self
+    }
+
     pub fn sample(&self, rng: &mut impl Rng) -> DTransformation {
+        self.sample_with_bbox(rng).0
+    }
+
+    /// Samples a transformation and also returns the bounding box the item would occupy with it
+    pub fn sample_with_bbox(&self, rng: &mut impl Rng) -> (DTransformation, Rect) {
+        let mut sample = self.sample_any(rng);
+        for _ in 1..ZONE_SAMPLE_ATTEMPTS {
+            let center = sample.1.centroid();
+            if !self.forbidden_zones.iter().any(|z| z.collides_with(&center)) {
+                break;
+            }
+            sample = self.sample_any(rng);
+        }
+        sample
+    }
+
+    fn sample_any(&self, rng: &mut impl Rng) -> (DTransformation, Rect) {
+        if let Some(cont_rot) = &self.cont_rot {
+            // for continuous rotation, sometimes sample a completely random rotation or jitter the current one
+            let p = rng.random::<f32>();
+            let r = if p < ROT_UNIFORM_SAMPLE_RATIO {
+                Some(rng.random_range(0.0..2.0 * PI))
+            } else if p < ROT_UNIFORM_SAMPLE_RATIO + ROT_JITTER_SAMPLE_RATIO {
+                cont_rot.ref_rotation.map(|r| r + rng.random_range(-ROT_JITTER..ROT_JITTER))
+            } else {
+                None
+            };
+            // the item might not fit in the sampled rotation, fall back to the fixed rotations
+            if let Some(entry) = r.and_then(|r| cont_rot.rot_entry(r)) {
+                return entry.sample(rng);
+            }
+        }
+
         // randomly select a rotation
-        let r_entry = self.rot_entries.choose(rng).unwrap();
+        self.rot_entries.choose(rng).unwrap().sample(rng)
+    }
+}
+
+impl RotEntry {
+    fn new(r: f32, r_bbox: Rect, sample_bbox: Rect, container_bbox: Rect) -> Option<Self> {
+        //narrow the container range to account for the rotated shape
+        let cont_x_range = (container_bbox.x_min - r_bbox.x_min)..(container_bbox.x_max - r_bbox.x_max);
+        let cont_y_range = (container_bbox.y_min - r_bbox.y_min)..(container_bbox.y_max - r_bbox.y_max);
+
+        //intersect with the sample bbox
+        let x_range = intersect_range(&cont_x_range, &(sample_bbox.x_min..sample_bbox.x_max));
+        let y_range = intersect_range(&cont_y_range, &(sample_bbox.y_min..sample_bbox.y_max));
+
+        //make sure the ranges are not empty
+        if x_range.is_empty() || y_range.is_empty() {
+            None
+        } else {
+            Some(RotEntry { r, r_bbox, x_range, y_range })
+        }
+    }
 
+    fn sample(&self, rng: &mut impl Rng) -> (DTransformation, Rect) {
         // sample a random x and y value within the valid range
-        let r = r_entry.r;
-        let x_sample = rng.random_range(r_entry.x_range.clone());
-        let y_sample = rng.random_range(r_entry.y_range.clone());
+        let x_sample = rng.random_range(self.x_range.clone());
+        let y_sample = rng.random_range(self.y_range.clone());
+
+        let bbox = Rect {
+            x_min: self.r_bbox.x_min + x_sample,
+            y_min: self.r_bbox.y_min + y_sample,
+            x_max: self.r_bbox.x_max + x_sample,
+            y_max: self.r_bbox.y_max + y_sample,
+        };
+
+        (DTransformation::new(self.r, (x_sample, y_sample)), bbox)
+    }
+}
 
-        DTransformation::new(r, (x_sample, y_sample))
+impl ContinuousRotation {
+    fn rot_entry(&self, r: f32) -> Option<RotEntry> {
+        let r = normalize_rotation(r);
+        RotEntry::new(r, rotated_bbox(&self.hull, r), self.sample_bbox, self.container_bbox)
     }
 }
 
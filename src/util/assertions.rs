@@ -1,6 +1,7 @@
 use crate::eval::specialized_jaguars_pipeline::SpecializedHazardCollector;
-use crate::quantify::tracker::CollisionTracker;
-use crate::quantify::{quantify_collision_poly_container, quantify_collision_poly_poly};
+use crate::quantify::tracker::{CollisionTracker, PairCollisions};
+use crate::quantify::{quantify_collision_poly_container, quantify_collision_poly_poly, quantify_collision_poly_zone};
+use crate::zones::is_forbidden_zone;
 use float_cmp::{approx_eq, assert_approx_eq};
 use itertools::Itertools;
 use jagua_rs::collision_detection::hazards::collector::{BasicHazardCollector, HazardCollector};
@@ -15,6 +16,9 @@ use std::collections::HashSet;
 
 pub fn tracker_matches_layout(ct: &CollisionTracker, l: &Layout) -> bool {
     assert!(l.placed_items.keys().all(|k| ct.pk_idx_map.contains_key(k)));
+    if let PairCollisions::Sparse(sm) = &ct.pair_collisions {
+        assert!(sm.is_consistent());
+    }
     assert!(assertions::layout_qt_matches_fresh_qt(l));
 
     for (pk1, pi1) in l.placed_items.iter() {
@@ -123,13 +127,21 @@ pub fn tracker_matches_layout(ct: &CollisionTracker, l: &Layout) -> bool {
                 }
             }
         }
-        if collector.contains_entity(&HazardEntity::Exterior) {
-            let stored_loss = ct.get_container_loss(pk1);
-            let calc_loss = quantify_collision_poly_container(&pi1.shape, l.container.outer_cd.bbox);
-            assert_approx_eq!(f32, stored_loss, calc_loss, ulps = 5);
-        } else {
-            assert_eq!(ct.get_container_loss(pk1), 0.0);
+        // Collisions with the exterior and with forbidden zones are summed in the container loss
+        let calc_loss = collector.iter()
+            .map(|(hkey, he)| match he {
+                HazardEntity::Exterior => quantify_collision_poly_container(&pi1.shape, l.container.outer_cd.bbox),
+                _ if is_forbidden_zone(he, ct.get_min_quality(pk1)) => quantify_collision_poly_zone(&pi1.shape, &l.cde().hazards_map[hkey].shape),
+                _ => 0.0,
+            })
+            .sum::<f32>();
+        match calc_loss > 0.0 {
+            true => assert_approx_eq!(f32, ct.get_container_loss(pk1), calc_loss, epsilon = 1e-3 * calc_loss),
+            false => assert_eq!(ct.get_container_loss(pk1), 0.0),
         }
+        let group_excess = ct.item_groups.window(l, pi1.item_id, Some(pk1))
+            .map_or(0.0, |w| w.excess(pi1.shape.bbox.x_min, pi1.shape.bbox.x_max));
+        assert_eq!(group_excess > 0.0, ct.get_group_loss(pk1) > 0.0);
     }
 
     true
@@ -146,6 +158,12 @@ pub fn custom_pipeline_matches_jaguars(shape: &SPolygon, det: &SpecializedHazard
         if coll.contains_entity(&HazardEntity::from((pk, pi))){
             coll.remove_by_entity(&HazardEntity::from((pk, pi)));
         }
+        // Zones the item is allowed to overlap are ignored by the custom pipeline
+        for hkey in det.allowed_zones.0.keys() {
+            if coll.contains_key(hkey) {
+                coll.remove_by_key(hkey);
+            }
+        }
         coll
     };
 
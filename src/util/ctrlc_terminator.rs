@@ -1,4 +1,5 @@
-use crate::util::terminator::Terminator;
+use crate::config::WorkBudget;
+use crate::util::terminator::{Terminator, WorkCounter};
 use jagua_rs::Instant;
 use log::warn;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -9,6 +10,7 @@ use std::time::Duration;
 pub struct CtrlCTerminator {
     pub timeout: Option<Instant>,
     pub ctrlc: Arc<AtomicBool>,
+    pub work: WorkCounter,
 }
 
 impl Default for CtrlCTerminator {
@@ -31,6 +33,7 @@ impl CtrlCTerminator {
         Self {
             timeout: None,
             ctrlc,
+            work: WorkCounter::new(None),
         }
     }
 }
@@ -39,15 +42,32 @@ impl Terminator for CtrlCTerminator {
     fn kill(&self) -> bool {
         self.timeout.is_some_and(|timeout| Instant::now() > timeout)
             || self.ctrlc.load(Ordering::SeqCst)
+            || self.work.spent()
     }
 
     fn new_timeout(&mut self, timeout: Duration){
         // Reset the Ctrl-C flag and set a new timeout
         self.ctrlc.store(false, Ordering::SeqCst);
         self.timeout = Some(Instant::now() + timeout);
+        self.work = WorkCounter::new(None);
+    }
+
+    fn new_work_budget(&mut self, budget: WorkBudget) {
+        // Reset the Ctrl-C flag and set a new work budget
+        self.ctrlc.store(false, Ordering::SeqCst);
+        self.timeout = None;
+        self.work = WorkCounter::new(Some(budget));
     }
 
     fn timeout_at(&self) -> Option<Instant> {
         self.timeout
     }
+
+    fn register_work(&self, n_iterations: usize, n_evals: usize) {
+        self.work.register(n_iterations, n_evals);
+    }
+
+    fn work_progress(&self) -> Option<f32> {
+        self.work.progress()
+    }
 }
\ No newline at end of file
@@ -0,0 +1,15 @@
+use jagua_rs::geometry::primitives::{Point, Rect};
+
+/// Bounding box of the points after rotation (around the origin)
+pub fn rotated_bbox(points: &[Point], rotation: f32) -> Rect {
+    let (sin, cos) = rotation.sin_cos();
+    let (mut x_min, mut y_min, mut x_max, mut y_max) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    for Point(x, y) in points {
+        let (rx, ry) = (x * cos - y * sin, x * sin + y * cos);
+        x_min = x_min.min(rx);
+        y_min = y_min.min(ry);
+        x_max = x_max.max(rx);
+        y_max = y_max.max(ry);
+    }
+    Rect { x_min, y_min, x_max, y_max }
+}
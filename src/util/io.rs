@@ -1,6 +1,14 @@
+use crate::config::MigrationTopology;
+use crate::groups::ItemGroup;
+use crate::guillotine::CutTree;
+use crate::optimizer::demand::DemandRange;
+use crate::optimizer::heights::{StripHeightOption, StripHeightStatus};
+use crate::sample::refine::RefinerType;
 use crate::EPOCH;
 use anyhow::{Context, Result};
 use clap::Parser;
+use itertools::Itertools;
+use jagua_rs::io::ext_repr::ExtQualityZone;
 use jagua_rs::probs::spp::io::ext_repr::{ExtSPInstance, ExtSPSolution};
 use log::{log, Level, LevelFilter};
 use serde::{Deserialize, Serialize};
@@ -34,6 +42,62 @@ pub struct MainCli {
 
     #[arg(short = 's', long, help = "Fixed seed for the random number generator")]
     pub rng_seed: Option<u64>,
+
+    /// Exploration budget in separator iterations (requires compression iterations)
+    #[arg(long, requires = "compression_iterations", help = "Limit the exploration phase by a number of separator iterations instead of time (deterministic with a fixed seed)")]
+    pub exploration_iterations: Option<usize>,
+
+    /// Compression budget in separator iterations (requires exploration iterations)
+    #[arg(long, requires = "exploration_iterations", help = "Limit the compression phase by a number of separator iterations instead of time (deterministic with a fixed seed)")]
+    pub compression_iterations: Option<usize>,
+
+    /// Number of parallel islands during exploration
+    #[arg(short = 'n', long, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..), help = "Explore with multiple parallel islands that periodically exchange solutions")]
+    pub islands: Option<usize>,
+
+    /// Time between two migrations of the islands in seconds
+    #[arg(long, requires = "islands", value_parser = clap::builder::RangedU64ValueParser::<u64>::new().range(1..), help = "Time between two consecutive migrations of solutions between the islands (in seconds)")]
+    pub migration_interval: Option<u64>,
+
+    /// Topology of the migrations between the islands
+    #[arg(long, requires = "islands", value_enum, default_value = "ring", help = "Which islands receive each other's best solutions during a migration")]
+    pub migration_topology: MigrationTopology,
+
+    /// Adaptive per-item sampling budgets
+    #[arg(long, help = "Adapt the number of samples to every item's size and recent move success")]
+    pub adaptive_sampling: bool,
+
+    /// Gap-aware container-wide sampling
+    #[arg(long, help = "Bias container-wide samples toward empty regions of the layout")]
+    pub gap_aware_sampling: bool,
+
+    /// Refiner used during exploration
+    #[arg(long, value_enum, default_value = "coord-descent", help = "Local search used to refine samples during exploration")]
+    pub exploration_refiner: RefinerType,
+
+    /// Refiner used during compression
+    #[arg(long, value_enum, default_value = "coord-descent", help = "Local search used to refine samples during compression")]
+    pub compression_refiner: RefinerType,
+
+    /// Simulated annealing during compression, with the initial temperature
+    #[arg(long, conflicts_with = "early_termination", value_name = "INIT_TEMP", help = "Accept failed compression attempts as the next starting point according to a simulated annealing criterion, starting at the given temperature")]
+    pub annealing_temp: Option<f32>,
+
+    /// Open-dimension mode, alternating between the width and the height
+    #[arg(long, help = "Minimize the area of the strip by shrinking its height as well as its width")]
+    pub open_dimension: bool,
+
+    /// Open-dimension mode with a fixed aspect ratio
+    #[arg(long, conflicts_with = "open_dimension", help = "Minimize the area of the strip while keeping its aspect ratio (width / height) fixed, 1.0 for a square")]
+    pub aspect_ratio: Option<f32>,
+
+    /// Candidate strip heights with their cost per unit of area
+    #[arg(long, value_delimiter = ',', value_name = "HEIGHT:COST", help = "Optimize for multiple candidate strip heights (with a cost per unit of area each) and select the cheapest layout")]
+    pub strip_heights: Vec<StripHeightOption>,
+
+    /// Guillotine-cut feasibility
+    #[arg(long, help = "Repair the final layout to be separable by edge-to-edge guillotine cuts (based on the bounding boxes of the items) and export the cut tree")]
+    pub guillotine: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -41,6 +105,93 @@ pub struct ExtSPOutput {
     #[serde(flatten)]
     pub instance: ExtSPInstance,
     pub solution: ExtSPSolution,
+    /// Area of the free rectangle at the right end of the strip
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remnant_area: Option<f32>,
+    /// Lower bound on the strip width of the instance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lower_bound: Option<f32>,
+    /// Relative gap between the strip width of the solution and the lower bound
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gap: Option<f32>,
+    /// Comparison of all candidate strip heights, if multiple were provided
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip_heights: Option<Vec<ExtStripHeightReport>>,
+    /// Achieved quantity of every item, if the instance has demand ranges
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_quantities: Option<Vec<ExtItemQuantity>>,
+    /// Groups of items with a maximum x-span (and their span in the solution), if the instance has item groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_groups: Option<Vec<ExtItemGroup>>,
+    /// Quality zones of the strip, if the instance has quality zones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality_zones: Option<Vec<ExtQualityZone>>,
+    /// Guillotine cut tree of the solution, if guillotine cuts were required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cut_tree: Option<CutTree>,
+    /// Whether the solution is separable by guillotine cuts, if they were required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guillotine_feasible: Option<bool>,
+}
+
+/// Outcome of a candidate strip height, see [`optimize_strip_heights`](crate::optimizer::heights::optimize_strip_heights)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExtStripHeightReport {
+    pub strip_height: f32,
+    pub cost_per_area: f32,
+    pub status: StripHeightStatus,
+    /// Strip width of the best layout at this height, if any
+    pub strip_width: Option<f32>,
+    pub cost: Option<f32>,
+    /// Cost of a strip of the lower bound width
+    pub lower_bound_cost: f32,
+}
+
+/// Placed quantity of an item with a demand range, see [`DemandRange`]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExtItemQuantity {
+    pub item_id: u64,
+    /// Required (minimum) quantity
+    pub demand: u64,
+    pub max_demand: u64,
+    pub placed: u64,
+}
+
+/// Item group as defined in an instance JSON (see [`ItemGroup`]), output JSONs also contain the achieved span
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ExtItemGroup {
+    pub item_ids: Vec<u64>,
+    pub max_x_span: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub x_span: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct ExtQualityZones {
+    #[serde(default)]
+    quality_zones: Option<Vec<ExtQualityZone>>,
+}
+
+#[derive(Deserialize)]
+struct ExtItemGroups {
+    #[serde(default)]
+    item_groups: Option<Vec<ExtItemGroup>>,
+}
+
+/// Demands of the items of an instance (or output) JSON, with the `max_demand` extension of the jagua-rs format
+#[derive(Deserialize)]
+struct ExtDemands {
+    items: Vec<ExtItemDemand>,
+    #[serde(default)]
+    item_quantities: Option<Vec<ExtItemQuantity>>,
+}
+
+#[derive(Deserialize)]
+struct ExtItemDemand {
+    id: u64,
+    demand: u64,
+    #[serde(default)]
+    max_demand: Option<u64>,
 }
 
 pub fn init_logger(level_filter: LevelFilter, log_file_path: &Path) -> Result<()> {
@@ -126,3 +277,39 @@ pub fn read_spp_input(path: &Path) -> Result<(ExtSPInstance, Option<ExtSPSolutio
         }
     }
 }
+
+/// Reads the demand ranges of the items (indexed by id) from the optional `max_demand` fields of the items of an instance JSON,
+/// or from the `item_quantities` of an output JSON. Returns `None` if none of the items has a maximum demand.
+pub fn read_demand_ranges(path: &Path) -> Result<Option<Vec<DemandRange>>> {
+    let input_str = fs::read_to_string(path).context("could not read input file")?;
+    let ext_demands = serde_json::from_str::<ExtDemands>(&input_str).context("could not parse item demands from input file")?;
+    let ranges = match ext_demands.item_quantities {
+        Some(quantities) => quantities.iter()
+            .sorted_by_key(|q| q.item_id)
+            .map(|q| DemandRange { min: q.demand as usize, max: q.max_demand as usize })
+            .collect_vec(),
+        None if ext_demands.items.iter().all(|i| i.max_demand.is_none()) => return Ok(None),
+        None => ext_demands.items.iter()
+            .sorted_by_key(|i| i.id)
+            .map(|i| DemandRange { min: i.demand as usize, max: i.max_demand.unwrap_or(i.demand) as usize })
+            .collect_vec(),
+    };
+    Ok(Some(ranges))
+}
+
+/// Reads the item groups from the optional top-level `item_groups` field of an instance or output JSON
+pub fn read_item_groups(path: &Path) -> Result<Option<Vec<ItemGroup>>> {
+    let input_str = fs::read_to_string(path).context("could not read input file")?;
+    let ext_groups = serde_json::from_str::<ExtItemGroups>(&input_str).context("could not parse item groups from input file")?;
+    let groups = ext_groups.item_groups.map(|groups| groups.into_iter()
+        .map(|g| ItemGroup { item_ids: g.item_ids.iter().map(|&id| id as usize).collect(), max_x_span: g.max_x_span })
+        .collect());
+    Ok(groups)
+}
+
+/// Reads the quality zones of the strip from the optional top-level `quality_zones` field of an instance or output JSON
+pub fn read_quality_zones(path: &Path) -> Result<Option<Vec<ExtQualityZone>>> {
+    let input_str = fs::read_to_string(path).context("could not read input file")?;
+    let ext_zones = serde_json::from_str::<ExtQualityZones>(&input_str).context("could not parse quality zones from input file")?;
+    Ok(ext_zones.quality_zones)
+}
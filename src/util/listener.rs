@@ -19,6 +19,14 @@ pub enum ReportType {
     Final
 }
 
+impl<L: SolutionListener> SolutionListener for Option<L> {
+    fn report(&mut self, report: ReportType, solution: &SPSolution, instance: &SPInstance) {
+        if let Some(listener) = self {
+            listener.report(report, solution, instance);
+        }
+    }
+}
+
 /// A dummy implementation of the `SolutionListener` trait that does nothing.
 pub struct DummySolListener;
 
@@ -1,7 +1,8 @@
 use crate::consts::DRAW_OPTIONS;
 use crate::util::io;
 use crate::util::listener::{ReportType, SolutionListener};
-use jagua_rs::io::svg::s_layout_to_svg;
+use crate::zones::has_quality_zones;
+use jagua_rs::io::svg::{s_layout_to_svg, SvgDrawOptions};
 use jagua_rs::probs::spp::entities::{SPInstance, SPSolution};
 use log::Level;
 use std::path::Path;
@@ -45,21 +46,30 @@ impl SolutionListener for SvgExporter{
             ReportType::Final => "final",
             ReportType::ExplImproving => "expl_i"
         };
+        let draw_options = draw_options(solution);
         let file_name = format!("{}_{:.3}_{}", self.svg_counter, solution.strip_width(), suffix);
         if let Some(live_path) = &self.live_path {
-            let svg = s_layout_to_svg(&solution.layout_snapshot, instance, DRAW_OPTIONS, file_name.as_str());
+            let svg = s_layout_to_svg(&solution.layout_snapshot, instance, draw_options, file_name.as_str());
             io::write_svg(&svg, Path::new(live_path), Level::Trace).expect("failed to write live svg");
         }
         if let Some(intermediate_dir) = &self.intermediate_dir && report_type != ReportType::ExplImproving {
-            let svg = s_layout_to_svg(&solution.layout_snapshot, instance, DRAW_OPTIONS, file_name.as_str());
+            let svg = s_layout_to_svg(&solution.layout_snapshot, instance, draw_options, file_name.as_str());
             let file_path = &*format!("{intermediate_dir}/{file_name}.svg");
             io::write_svg(&svg, Path::new(file_path), Level::Trace).expect("failed to write intermediate svg");
             self.svg_counter += 1;
         }
         if let Some(final_path) = &self.final_path && report_type == ReportType::Final {
             let stem = Path::new(final_path).file_stem().unwrap();
-            let svg = s_layout_to_svg(&solution.layout_snapshot, instance, DRAW_OPTIONS, stem.to_str().unwrap());
+            let svg = s_layout_to_svg(&solution.layout_snapshot, instance, draw_options, stem.to_str().unwrap());
             io::write_svg(&svg, Path::new(final_path), Level::Info).expect("failed to write final svg");
         }
     }
-}
\ No newline at end of file
+}
+
+/// jagua-rs highlights any overlap with a quality zone as an unexpected collision, even if the item is allowed to overlap it
+fn draw_options(solution: &SPSolution) -> SvgDrawOptions {
+    match has_quality_zones(&solution.layout_snapshot.container) {
+        true => SvgDrawOptions { highlight_collisions: false, ..DRAW_OPTIONS },
+        false => DRAW_OPTIONS,
+    }
+}
@@ -1,4 +1,7 @@
+use crate::config::WorkBudget;
 use jagua_rs::Instant;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Generic trait for any struct that can determine if the optimization process should terminate.
@@ -9,13 +12,25 @@ pub trait Terminator {
     /// Sets a new timeout duration
     fn new_timeout(&mut self, timeout: Duration);
 
+    /// Sets a new work budget, replacing any timeout
+    fn new_work_budget(&mut self, budget: WorkBudget);
+
     /// Returns the instant when a timeout was set, if any
     fn timeout_at(&self) -> Option<Instant>;
+
+    /// Registers work performed by the optimization, counted against the work budget (if any)
+    fn register_work(&self, _n_iterations: usize, _n_evals: usize) {}
+
+    /// Returns the ratio of the work budget that has been spent, if a work budget was set
+    fn work_progress(&self) -> Option<f32> {
+        None
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct BasicTerminator {
     pub timeout: Option<Instant>,
+    pub work: WorkCounter,
 }
 
 impl Default for BasicTerminator {
@@ -26,20 +41,131 @@ impl Default for BasicTerminator {
 
 impl BasicTerminator {
     pub fn new() -> Self {
-        Self { timeout: None }
+        Self { timeout: None, work: WorkCounter::new(None) }
     }
 }
 
 impl Terminator for BasicTerminator {
     fn kill(&self) -> bool {
         self.timeout.is_some_and(|timeout| Instant::now() > timeout)
+            || self.work.spent()
     }
 
     fn new_timeout(&mut self, timeout: Duration){
         self.timeout = Some(Instant::now() + timeout);
+        self.work = WorkCounter::new(None);
+    }
+
+    fn new_work_budget(&mut self, budget: WorkBudget) {
+        self.timeout = None;
+        self.work = WorkCounter::new(Some(budget));
     }
 
     fn timeout_at(&self) -> Option<Instant> {
         self.timeout
     }
-}
\ No newline at end of file
+
+    fn register_work(&self, n_iterations: usize, n_evals: usize) {
+        self.work.register(n_iterations, n_evals);
+    }
+
+    fn work_progress(&self) -> Option<f32> {
+        self.work.progress()
+    }
+}
+
+/// [`BasicTerminator`] which can additionally be cancelled through a shared flag, e.g. from another thread.
+/// Unlike the Ctrl-C flag of [`CtrlCTerminator`](crate::util::ctrlc_terminator::CtrlCTerminator),
+/// the flag is never reset: a cancellation ends all remaining phases of the optimization.
+#[derive(Debug, Clone, Default)]
+pub struct CancelTerminator {
+    pub basic: BasicTerminator,
+    pub cancelled: Arc<AtomicBool>,
+}
+
+impl CancelTerminator {
+    pub fn new(cancelled: Arc<AtomicBool>) -> Self {
+        Self { basic: BasicTerminator::new(), cancelled }
+    }
+}
+
+impl Terminator for CancelTerminator {
+    fn kill(&self) -> bool {
+        self.basic.kill() || self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn new_timeout(&mut self, timeout: Duration) {
+        self.basic.new_timeout(timeout);
+    }
+
+    fn new_work_budget(&mut self, budget: WorkBudget) {
+        self.basic.new_work_budget(budget);
+    }
+
+    fn timeout_at(&self) -> Option<Instant> {
+        self.basic.timeout_at()
+    }
+
+    fn register_work(&self, n_iterations: usize, n_evals: usize) {
+        self.basic.register_work(n_iterations, n_evals);
+    }
+
+    fn work_progress(&self) -> Option<f32> {
+        self.basic.work_progress()
+    }
+}
+
+/// Keeps track of the work performed against an optional [`WorkBudget`].
+/// Unlike a timeout, a work budget is independent of the speed of the machine,
+/// so an optimization with a fixed seed and work budget always produces the same result.
+#[derive(Debug, Default)]
+pub struct WorkCounter {
+    pub budget: Option<WorkBudget>,
+    n_iterations: AtomicUsize,
+    n_evals: AtomicUsize,
+}
+
+impl WorkCounter {
+    pub fn new(budget: Option<WorkBudget>) -> Self {
+        Self {
+            budget,
+            n_iterations: AtomicUsize::new(0),
+            n_evals: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn register(&self, n_iterations: usize, n_evals: usize) {
+        self.n_iterations.fetch_add(n_iterations, Ordering::SeqCst);
+        self.n_evals.fetch_add(n_evals, Ordering::SeqCst);
+    }
+
+    pub fn n_iterations(&self) -> usize {
+        self.n_iterations.load(Ordering::SeqCst)
+    }
+
+    pub fn n_evals(&self) -> usize {
+        self.n_evals.load(Ordering::SeqCst)
+    }
+
+    /// Ratio of the budget that has been spent, `None` if there is no budget
+    pub fn progress(&self) -> Option<f32> {
+        self.budget.map(|budget| match budget {
+            WorkBudget::Iterations(n) => self.n_iterations() as f32 / n as f32,
+            WorkBudget::Evaluations(n) => self.n_evals() as f32 / n as f32,
+        })
+    }
+
+    pub fn spent(&self) -> bool {
+        self.progress().is_some_and(|p| p >= 1.0)
+    }
+}
+
+impl Clone for WorkCounter {
+    fn clone(&self) -> Self {
+        Self {
+            budget: self.budget,
+            n_iterations: AtomicUsize::new(self.n_iterations()),
+            n_evals: AtomicUsize::new(self.n_evals()),
+        }
+    }
+}
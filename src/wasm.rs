@@ -0,0 +1,190 @@
+use crate::config::{WorkBudget, DEFAULT_SPARROW_CONFIG};
+use crate::consts::{DEFAULT_COMPRESS_TIME_RATIO, DEFAULT_EXPLORE_TIME_RATIO};
+use crate::optimizer::compact::remnant_area;
+use crate::optimizer::state::{OptimizerState, Phase};
+use crate::util::io::ExtSPOutput;
+use crate::util::listener::DummySolListener;
+use crate::util::terminator::{BasicTerminator, Terminator};
+use crate::EPOCH;
+use jagua_rs::io::import::Importer;
+use jagua_rs::probs::spp::io::ext_repr::ExtSPInstance;
+use jagua_rs::Instant;
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::SeedableRng;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+/// Optimizer for use from JavaScript, which is advanced in bounded slices of time by [`WasmOptimizer::step`],
+/// so it never blocks the (browser) thread for longer than requested.
+#[wasm_bindgen]
+pub struct WasmOptimizer {
+    state: OptimizerState,
+    ext_instance: ExtSPInstance,
+    term: SliceTerminator,
+}
+
+#[wasm_bindgen]
+impl WasmOptimizer {
+    /// Creates an optimizer for an instance in the JSON format of jagua-rs and constructs an initial solution.
+    /// `time_limit_ms` is split between exploration and compression, only the time spent inside [`WasmOptimizer::step`] counts towards it.
+    /// If iterations are provided, the phases are limited by separator iterations instead (deterministic with a seed).
+    #[wasm_bindgen(constructor)]
+    pub fn new(instance_json: &str, time_limit_ms: f64, seed: Option<u32>, exploration_iterations: Option<u32>, compression_iterations: Option<u32>) -> Result<WasmOptimizer, JsError> {
+        let ext_instance = serde_json::from_str::<ExtSPInstance>(instance_json)?;
+
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        let time_limit = Duration::try_from_secs_f64(time_limit_ms / 1000.0)?;
+        config.expl_cfg.time_limit = time_limit.mul_f32(DEFAULT_EXPLORE_TIME_RATIO);
+        config.cmpr_cfg.time_limit = time_limit.mul_f32(DEFAULT_COMPRESS_TIME_RATIO);
+        config.expl_cfg.work_budget = exploration_iterations.map(|n| WorkBudget::Iterations(n as usize));
+        config.cmpr_cfg.work_budget = compression_iterations.map(|n| WorkBudget::Iterations(n as usize));
+
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &ext_instance)
+            .map_err(|e| JsError::new(&format!("{e:#}")))?;
+
+        let rng = match seed {
+            Some(seed) => Xoshiro256PlusPlus::seed_from_u64(seed as u64),
+            None => Xoshiro256PlusPlus::seed_from_u64(rand::random()),
+        };
+
+        let mut term = SliceTerminator::default();
+        let state = OptimizerState::new(instance, rng, &mut DummySolListener, &mut term, &config.expl_cfg, &config.cmpr_cfg, None);
+        term.pause();
+
+        Ok(Self { state, ext_instance, term })
+    }
+
+    /// Advances the optimization for at most (roughly) `budget_ms` milliseconds and returns the best solution so far.
+    /// Moves on to the next phase when the time limit (or work budget) of the current phase is reached.
+    pub fn step(&mut self, budget_ms: f64) -> StepResult {
+        self.term.slice_end = Some(Instant::now() + Duration::from_secs_f64(budget_ms.max(0.0) / 1000.0));
+        self.term.resume();
+
+        while !self.term.slice_over() && !self.state.is_done() {
+            match self.term.phase_over() {
+                true => self.state.next_phase(&mut DummySolListener, &mut self.term),
+                false => _ = self.state.step(&mut DummySolListener, &mut self.term),
+            }
+        }
+        self.term.slice_end = None;
+        self.term.pause();
+
+        let best = self.state.best_solution();
+        StepResult {
+            phase: self.phase(),
+            strip_width: best.strip_width(),
+            density: best.density(&self.state.instance),
+            solution_json: serde_json::to_string(&jagua_rs::probs::spp::io::export(&self.state.instance, best, *EPOCH)).unwrap(),
+        }
+    }
+
+    /// Ends the current phase immediately and moves on to the next one
+    pub fn skip_phase(&mut self) {
+        self.state.next_phase(&mut DummySolListener, &mut self.term);
+        self.term.pause();
+    }
+
+    /// "exploration", "compression" or "done"
+    #[wasm_bindgen(getter)]
+    pub fn phase(&self) -> String {
+        match self.state.phase() {
+            Phase::Exploration => "exploration",
+            Phase::Compression => "compression",
+            Phase::Done => "done",
+        }.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn done(&self) -> bool {
+        self.state.is_done()
+    }
+
+    /// Best solution found so far, in the same JSON format as the output of the CLI
+    pub fn output_json(&self) -> String {
+        let (best, lower_bounds) = (self.state.best_solution(), &self.state.lower_bounds);
+        let output = ExtSPOutput {
+            instance: self.ext_instance.clone(),
+            solution: jagua_rs::probs::spp::io::export(&self.state.instance, best, *EPOCH),
+            remnant_area: Some(remnant_area(best)),
+            lower_bound: Some(lower_bounds.best()),
+            gap: Some(lower_bounds.gap(best.strip_width())),
+            strip_heights: None,
+            item_quantities: None,
+            item_groups: None,
+            quality_zones: None,
+            cut_tree: None,
+            guillotine_feasible: None,
+        };
+        serde_json::to_string(&output).unwrap()
+    }
+}
+
+/// Outcome of a [`WasmOptimizer::step`]
+#[wasm_bindgen(getter_with_clone)]
+pub struct StepResult {
+    /// Phase after the step: "exploration", "compression" or "done"
+    pub phase: String,
+    /// Strip width of the best solution so far
+    pub strip_width: f32,
+    /// Density of the best solution so far
+    pub density: f32,
+    /// Best solution so far, in the JSON format of jagua-rs
+    pub solution_json: String,
+}
+
+/// Terminator of a phase, which additionally fires at the end of the current time slice.
+/// The timeout of the phase is paused between slices, so only the time spent inside slices counts towards it.
+#[derive(Debug, Default)]
+struct SliceTerminator {
+    phase: BasicTerminator,
+    slice_end: Option<Instant>,
+    /// Remaining time of the phase while paused
+    remaining: Option<Duration>,
+}
+
+impl SliceTerminator {
+    fn pause(&mut self) {
+        self.remaining = self.phase.timeout.map(|timeout| timeout.saturating_duration_since(Instant::now()));
+    }
+
+    fn resume(&mut self) {
+        if let Some(remaining) = self.remaining.take() {
+            self.phase.timeout = Some(Instant::now() + remaining);
+        }
+    }
+
+    fn phase_over(&self) -> bool {
+        self.phase.kill()
+    }
+
+    fn slice_over(&self) -> bool {
+        self.slice_end.is_some_and(|end| Instant::now() > end)
+    }
+}
+
+impl Terminator for SliceTerminator {
+    fn kill(&self) -> bool {
+        self.phase_over() || self.slice_over()
+    }
+
+    fn new_timeout(&mut self, timeout: Duration) {
+        self.phase.new_timeout(timeout);
+    }
+
+    fn new_work_budget(&mut self, budget: WorkBudget) {
+        self.phase.new_work_budget(budget);
+    }
+
+    fn timeout_at(&self) -> Option<Instant> {
+        self.phase.timeout_at()
+    }
+
+    fn register_work(&self, n_iterations: usize, n_evals: usize) {
+        self.phase.register_work(n_iterations, n_evals);
+    }
+
+    fn work_progress(&self) -> Option<f32> {
+        self.phase.work_progress()
+    }
+}
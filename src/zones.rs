@@ -0,0 +1,137 @@
+use anyhow::{bail, ensure, Result};
+use itertools::Itertools;
+use jagua_rs::collision_detection::hazards::filter::HazKeyFilter;
+use jagua_rs::collision_detection::hazards::HazardEntity;
+use jagua_rs::entities::{Container, InferiorQualityZone, Instance, Layout, N_QUALITIES};
+use jagua_rs::geometry::primitives::{Rect, SPolygon};
+use jagua_rs::geometry::shape_modification::ShapeModifyMode;
+use jagua_rs::geometry::{DTransformation, OriginalShape};
+use jagua_rs::io::ext_repr::{ExtQualityZone, ExtSPolygon, ExtShape};
+use jagua_rs::io::import::import_simple_polygon;
+use jagua_rs::probs::spp::entities::{SPInstance, SPProblem, SPSolution, Strip};
+use std::sync::Arc;
+
+/// Converts the quality zones of an instance to the zones of the strip's container, one per quality level.
+/// Zones are fixed regions of the strip (e.g. a graded slab), so they do not move when the strip is resized.
+/// A zone of quality 0 is a hole, which no item can overlap.
+pub fn import_quality_zones(ext_zones: &[ExtQualityZone], strip: &Strip) -> Result<Vec<InferiorQualityZone>> {
+    ensure!(ext_zones.iter().all(|z| z.quality < N_QUALITIES), "quality of a zone should be below {N_QUALITIES}");
+    ext_zones.iter()
+        .into_group_map_by(|z| z.quality)
+        .into_iter()
+        .sorted_by_key(|(quality, _)| *quality)
+        .map(|(quality, zones)| {
+            let shapes = zones.iter()
+                .map(|z| {
+                    let shape = match &z.shape {
+                        ExtShape::Rectangle { x_min, y_min, width, height } => Rect::try_new(*x_min, *y_min, x_min + width, y_min + height)?.into(),
+                        ExtShape::SimplePolygon(sp) => import_simple_polygon(sp)?,
+                        ExtShape::Polygon(_) | ExtShape::MultiPolygon(_) => bail!("quality zones should be rectangles or simple polygons"),
+                    };
+                    Ok(OriginalShape {
+                        shape,
+                        pre_transform: DTransformation::empty(),
+                        modify_mode: ShapeModifyMode::Inflate,
+                        modify_config: strip.shape_modify_config,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut zone = InferiorQualityZone::new(quality, shapes)?;
+            // Violations are quantified using the poles of the zones
+            for shape in zone.shapes_cd.iter_mut() {
+                Arc::make_mut(shape).generate_surrogate(strip.cde_config.item_surrogate_config)?;
+            }
+            Ok(zone)
+        })
+        .collect()
+}
+
+/// External representation of the quality zones of a container (with their original shapes), `None` if it has none
+pub fn export_quality_zones(container: &Container) -> Option<Vec<ExtQualityZone>> {
+    let zones = container.quality_zones.iter().flatten()
+        .flat_map(|zone| zone.shapes_orig.iter().map(|orig| ExtQualityZone {
+            quality: zone.quality,
+            shape: ExtShape::SimplePolygon(ExtSPolygon(orig.shape.vertices.iter().map(|p| (p.0, p.1)).collect())),
+        }))
+        .collect_vec();
+    (!zones.is_empty()).then_some(zones)
+}
+
+/// Container of the strip, with the given quality zones
+pub fn strip_container(strip: Strip, quality_zones: Vec<InferiorQualityZone>) -> Container {
+    let container = Container::from(strip);
+    match quality_zones.is_empty() {
+        true => container,
+        false => Container::new(container.id, container.outer_orig.as_ref().clone(), quality_zones, strip.cde_config)
+            .expect("quality zones were validated on import"),
+    }
+}
+
+/// Replaces the container of the problem with one matching its current strip, keeping the quality zones of the current container
+pub fn swap_strip_container(prob: &mut SPProblem) {
+    let zones = prob.layout.container.quality_zones.iter().flatten().cloned().collect_vec();
+    prob.layout.swap_container(strip_container(prob.strip, zones));
+}
+
+/// Adds the quality zones to the container of the problem
+pub fn set_quality_zones(prob: &mut SPProblem, quality_zones: &[InferiorQualityZone]) {
+    if !quality_zones.is_empty() {
+        prob.layout.swap_container(strip_container(prob.strip, quality_zones.to_vec()));
+    }
+}
+
+/// Problem restored to a solution, including the quality zones of the solution's container
+pub fn restore_problem(instance: &SPInstance, solution: &SPSolution) -> SPProblem {
+    let mut prob = SPProblem::new(instance.clone());
+    // If the strip of the solution is that of the instance, the layout is restored in place (keeping its container)
+    prob.layout.swap_container(solution.layout_snapshot.container.clone());
+    prob.restore(solution);
+    prob
+}
+
+pub fn has_quality_zones(container: &Container) -> bool {
+    container.quality_zones.iter().any(Option::is_some)
+}
+
+/// Whether an item requiring `min_quality` (full quality if `None`) should not overlap a zone of `quality`
+pub fn violates_zone(min_quality: Option<usize>, quality: usize) -> bool {
+    quality == 0 || min_quality.is_none_or(|m| quality < m)
+}
+
+/// Whether a hazard is a zone of the container which an item requiring `min_quality` should not overlap
+pub fn is_forbidden_zone(entity: &HazardEntity, min_quality: Option<usize>) -> bool {
+    match entity {
+        HazardEntity::Hole { .. } => true,
+        HazardEntity::InferiorQualityZone { quality, .. } => violates_zone(min_quality, *quality),
+        _ => false,
+    }
+}
+
+/// Filter for the zones of the layout's container that an item requiring `min_quality` is allowed to overlap
+pub fn allowed_zones(l: &Layout, min_quality: Option<usize>) -> HazKeyFilter {
+    if !has_quality_zones(&l.container) {
+        return HazKeyFilter::from_keys([]);
+    }
+    let keys = l.cde().hazards_map.iter()
+        .filter(|(_, h)| matches!(h.entity, HazardEntity::InferiorQualityZone { quality, .. } if !violates_zone(min_quality, quality)))
+        .map(|(hkey, _)| hkey);
+    HazKeyFilter::from_keys(keys)
+}
+
+/// Shapes of the zones of the container that an item requiring `min_quality` should not overlap
+pub fn forbidden_zones(container: &Container, min_quality: Option<usize>) -> Vec<Arc<SPolygon>> {
+    container.quality_zones.iter().flatten()
+        .filter(|zone| violates_zone(min_quality, zone.quality))
+        .flat_map(|zone| zone.shapes_cd.iter().cloned())
+        .collect()
+}
+
+/// Equivalent to [`Layout::is_feasible`], but items are allowed to overlap the zones of sufficient quality
+pub fn is_feasible(l: &Layout, instance: &impl Instance) -> bool {
+    l.placed_items.iter().all(|(pk, pi)| {
+        let mut filter = allowed_zones(l, instance.item(pi.item_id).min_quality);
+        let hkey = l.cde().haz_key_from_pi_key(pk).expect("all placed items should be registered in the CDE");
+        filter.0.insert(hkey, ());
+        !l.cde().detect_poly_collision(&pi.shape, &filter)
+    })
+}
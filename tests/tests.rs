@@ -1,22 +1,54 @@
 #[cfg(test)]
 mod integration_tests {
     use anyhow::Result;
+    use jagua_rs::entities::Instance;
+    use jagua_rs::io::ext_repr::{ExtItem, ExtQualityZone, ExtShape};
+    use jagua_rs::probs::spp::io::ext_repr::{ExtItem as ExtSPItem, ExtSPInstance};
+    use jagua_rs::probs::spp::entities::SPPlacement;
+    use jagua_rs::geometry::DTransformation;
+    use jagua_rs::geometry::geo_traits::Transformable;
+    use sparrow::optimizer::separator::change_strip_width;
     use jagua_rs::io::import::Importer;
-    use sparrow::config::DEFAULT_SPARROW_CONFIG;
+    use sparrow::bounds::{min_strip_height, LowerBounds};
+    use itertools::Itertools;
+    use sparrow::config::{DisruptionStrategy, DisruptorSelection, IslandConfig, MigrationTopology, OpenDimension, ShrinkDecayStrategy, WorkBudget, DEFAULT_SPARROW_CONFIG};
+    use sparrow::consts::ADAPTIVE_DSRP_MIN_WEIGHT;
     use sparrow::consts::LBF_SAMPLE_CONFIG;
+    use jagua_rs::probs::spp::entities::{SPInstance, SPProblem, SPSolution};
+    use sparrow::groups::{ItemGroup, ItemGroups};
+    use sparrow::guillotine::{cut_tree, guillotine_repair, CutTree};
+    use sparrow::optimizer::compact::{compaction_phase, remnant_area};
     use sparrow::optimizer::compress::compression_phase;
+    use sparrow::optimizer::disrupt::crossover::crossover;
+    use sparrow::optimizer::disrupt::{build_disruptor, DisruptionContext, DisruptorSelector};
+    use sparrow::optimizer::demand::{full_instance, insert_optional_items, DemandRange};
     use sparrow::optimizer::explore::exploration_phase;
+    use sparrow::optimizer::island::island_exploration_phase;
     use sparrow::optimizer::lbf::LBFBuilder;
+    use sparrow::optimizer::heights::{optimize_strip_heights, StripHeightOption, StripHeightStatus};
+    use sparrow::optimizer::optimize;
+    use sparrow::optimizer::state::{OptimizerState, Phase};
     use sparrow::optimizer::separator::Separator;
+    use sparrow::eval::sample_eval::SampleEvaluator;
+    use sparrow::eval::sep_evaluator::SeparationEvaluator;
+    use sparrow::sample::refine::{refine, RefineConfig, RefinerType};
+    use sparrow::sample::gap_sampler::OccupancyGrid;
+    use sparrow::sample::search::ContainerSampler;
+    use sparrow::sample::uniform_sampler::UniformBBoxSampler;
+    use jagua_rs::geometry::primitives::Rect;
+    use sparrow::quantify::tracker::{CollisionTracker, PairCollisions};
+    use sparrow::util::assertions::tracker_matches_layout;
     use sparrow::util::io;
     use sparrow::util::terminator::BasicTerminator;
     use sparrow::util::terminator::Terminator;
+    use sparrow::zones;
     use std::path::Path;
     use std::time::Duration;
     use rand::rngs::Xoshiro256PlusPlus;
-    use rand::SeedableRng;
+    use rand::{Rng, SeedableRng};
     use test_case::test_case;
     use sparrow::util::listener::DummySolListener;
+    use sparrow::Sparrow;
 
     const EXPLORE_TIMEOUT: Duration = Duration::from_secs(10);
     const COMPRESS_TIMEOUT: Duration = Duration::from_secs(10);
@@ -62,4 +94,744 @@ mod integration_tests {
         compression_phase(&instance, &mut separator, final_explore_sol, &mut sol_listener, &terminator, &config.cmpr_cfg);
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test_case("swim.json"; "swim")]
+    #[test_case("shapes0.json"; "shapes0")]
+    fn compaction_enlarges_remnant(path: &str) -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+        config.cmpr_cfg.post_compaction = false;
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/{path}");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let cmpr_sol = optimize(instance.clone(), rng, &mut DummySolListener, &mut BasicTerminator::new(), &config.expl_cfg, &config.cmpr_cfg, None);
+
+        let compacted_sol = compaction_phase(&instance, &cmpr_sol, &ItemGroups::default());
+        let mut compacted_prob = SPProblem::new(instance.clone());
+        compacted_prob.restore(&compacted_sol);
+        assert!(compacted_prob.layout.is_feasible());
+        assert_eq!(compacted_sol.layout_snapshot.placed_items.len(), instance.total_item_qty());
+        assert!(compacted_sol.strip_width() <= cmpr_sol.strip_width());
+        assert!(remnant_area(&compacted_sol) >= remnant_area(&cmpr_sol));
+        assert!(LowerBounds::new(&instance).best() <= compacted_sol.strip_width());
+        Ok(())
+    }
+
+    #[test_case(MigrationTopology::Ring; "ring")]
+    #[test_case(MigrationTopology::FullyConnected; "fully_connected")]
+    fn simulate_island_exploration(topology: MigrationTopology) -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let island_config = IslandConfig {
+            n_islands: 2,
+            migration_interval: Duration::from_secs(2),
+            topology,
+        };
+
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng.clone(), LBF_SAMPLE_CONFIG).construct();
+        let seps = (0..island_config.n_islands)
+            .map(|_| Separator::new(instance.clone(), builder.prob.clone(), Xoshiro256PlusPlus::seed_from_u64(rng.next_u64()), config.expl_cfg.separator_config))
+            .collect();
+
+        let mut terminator = BasicTerminator::new();
+        terminator.new_timeout(EXPLORE_TIMEOUT);
+
+        let sols = island_exploration_phase(&instance, seps, &mut DummySolListener, &terminator, &config.expl_cfg, &island_config);
+        assert!(sols.windows(2).all(|w| w[1].strip_width() < w[0].strip_width()));
+        Ok(())
+    }
+
+    #[test]
+    fn annealing_compression_stays_feasible() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.cmpr_cfg.shrink_decay = ShrinkDecayStrategy::Annealing { init_temp: 2.0 };
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng, LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.cmpr_cfg.separator_config);
+        let init_sol = separator.prob.save();
+
+        let mut terminator = BasicTerminator::new();
+        terminator.new_timeout(COMPRESS_TIMEOUT);
+        let cmpr_sol = compression_phase(&instance, &mut separator, &init_sol, &mut DummySolListener, &terminator, &config.cmpr_cfg);
+
+        let mut prob = SPProblem::new(instance.clone());
+        prob.restore(&cmpr_sol);
+        assert!(prob.layout.is_feasible());
+        assert!(cmpr_sol.strip_width() <= init_sol.strip_width());
+        Ok(())
+    }
+
+    #[test]
+    fn zero_islands_rejected() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.island_config = Some(IslandConfig {
+            n_islands: 0,
+            migration_interval: Duration::from_secs(2),
+            topology: MigrationTopology::Ring,
+        });
+        let (json_instance, _) = io::read_spp_input(Path::new(&format!("{INSTANCE_BASE_PATH}/swim.json")))?;
+        assert!(Sparrow::builder().instance(json_instance).config(config).build().is_err());
+        Ok(())
+    }
+
+    #[test_case(false, ContainerSampler::Uniform; "fixed_sampling")]
+    #[test_case(true, ContainerSampler::Uniform; "adaptive_sampling")]
+    #[test_case(false, ContainerSampler::GapAware; "gap_aware_sampling")]
+    fn deterministic_work_budget(adaptive_sampling: bool, container_sampler: ContainerSampler) -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        for sample_config in [&mut config.expl_cfg.separator_config.sample_config, &mut config.cmpr_cfg.separator_config.sample_config] {
+            sample_config.adaptive = adaptive_sampling;
+            sample_config.container_sampler = container_sampler;
+        }
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(10));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(5));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let placements = (0..2)
+            .map(|_| {
+                let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+                let sol = optimize(instance.clone(), rng, &mut DummySolListener, &mut BasicTerminator::new(), &config.expl_cfg, &config.cmpr_cfg, None);
+                sol.layout_snapshot.placed_items.values().map(|pi| (pi.item_id, pi.d_transf)).collect_vec()
+            })
+            .collect_vec();
+        assert_eq!(placements[0], placements[1]);
+        Ok(())
+    }
+
+    #[test]
+    fn builder_matches_optimize() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(10));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(5));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+
+        let result = Sparrow::builder()
+            .instance(json_instance)
+            .config(config)
+            .seed(RNG_SEED.unwrap() as u64)
+            .build()?
+            .run();
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let sol = optimize(result.instance.clone(), rng, &mut DummySolListener, &mut BasicTerminator::new(), &config.expl_cfg, &config.cmpr_cfg, None);
+
+        let placements = |sol: &SPSolution| sol.layout_snapshot.placed_items.values().map(|pi| (pi.item_id, pi.d_transf)).collect_vec();
+        assert_eq!(placements(&result.solution), placements(&sol));
+        assert!(result.to_ext_output().is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn optimizer_state_switch_phases_manually() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(20));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/albano.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let mut term = BasicTerminator::new();
+        let mut state = OptimizerState::new(instance, rng, &mut DummySolListener, &mut term, &config.expl_cfg, &config.cmpr_cfg, None);
+        assert_eq!(state.phase(), Phase::Exploration);
+
+        // Explore for a few steps only, far less than the default time limit
+        let init_width = state.best_solution().strip_width();
+        for _ in 0..5 {
+            assert!(state.step(&mut DummySolListener, &mut term));
+            assert!(state.best_solution().strip_width() <= init_width);
+        }
+        let expl_width = state.best_solution().strip_width();
+        state.next_phase(&mut DummySolListener, &mut term);
+        assert_eq!(state.phase(), Phase::Compression);
+        assert_eq!(state.best_solution().strip_width(), expl_width);
+
+        while !state.is_done() {
+            match term.kill() {
+                true => state.next_phase(&mut DummySolListener, &mut term),
+                false => _ = state.step(&mut DummySolListener, &mut term),
+            }
+        }
+        let (sol, report) = state.finish(&mut DummySolListener, &mut term);
+        assert_eq!(report.exploration_solution.strip_width(), expl_width);
+        assert!(sol.strip_width() <= expl_width);
+        Ok(())
+    }
+
+    #[test]
+    fn strip_heights_select_cheapest() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/shapes0.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let options = ["30:1.0", "50:0.8", "1"].map(|s| s.parse::<StripHeightOption>().unwrap());
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let result = optimize_strip_heights(&json_instance, &options, &config, rng, &mut DummySolListener, &mut BasicTerminator::new())?;
+
+        assert_eq!(result.candidates[2].status, StripHeightStatus::Infeasible);
+        let best = result.best();
+        assert_eq!(best.status, StripHeightStatus::Selected);
+        for c in &result.candidates {
+            if let Some(cost) = c.cost() {
+                assert!(best.cost().unwrap() <= cost);
+            }
+        }
+        let output = result.to_ext_output(&json_instance);
+        assert_eq!(output.instance.strip_height, best.option.height);
+        assert_eq!(output.strip_heights.unwrap().len(), options.len());
+        Ok(())
+    }
+
+    #[test]
+    fn builder_strip_heights() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/shapes0.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let options = ["30:1.0", "50:0.8"].map(|s| s.parse::<StripHeightOption>().unwrap()).to_vec();
+        let builder = || Sparrow::builder().instance(json_instance.clone()).config(config).seed(RNG_SEED.unwrap() as u64).strip_heights(options.clone());
+
+        let result = builder().build()?.run();
+        let comparison = result.strip_heights.as_ref().expect("strip heights were provided");
+        let selected = comparison.iter().find(|r| r.status == StripHeightStatus::Selected).unwrap();
+        assert_eq!(result.instance.base_strip.fixed_height, selected.strip_height);
+        assert_eq!(result.to_ext_output().unwrap().strip_heights.unwrap().len(), options.len());
+
+        // Incompatible options are rejected when building
+        assert!(builder().item_groups(vec![ItemGroup { item_ids: vec![0, 1], max_x_span: 30.0 }]).build().is_err());
+        assert!(builder().strip_heights(vec!["1".parse()?]).build().is_err());
+        Ok(())
+    }
+
+    #[test_case(OpenDimension::Alternating; "alternating")]
+    #[test_case(OpenDimension::AspectRatio(1.5); "aspect_ratio")]
+    fn open_dimension_shrinks_area(open_dimension: OpenDimension) -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+        config.expl_cfg.open_dimension = Some(open_dimension);
+        config.cmpr_cfg.open_dimension = Some(open_dimension);
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/shapes0.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let sol = optimize(instance.clone(), rng, &mut DummySolListener, &mut BasicTerminator::new(), &config.expl_cfg, &config.cmpr_cfg, None);
+
+        let mut prob = SPProblem::new(instance.clone());
+        prob.restore(&sol);
+        assert!(prob.layout.is_feasible());
+        assert_eq!(sol.layout_snapshot.placed_items.len(), instance.total_item_qty());
+        let (width, height) = (sol.strip_width(), sol.strip.fixed_height);
+        match open_dimension {
+            OpenDimension::Alternating => assert!(height < instance.base_strip.fixed_height && height >= min_strip_height(&instance)),
+            OpenDimension::AspectRatio(ratio) => assert!((width / height - ratio).abs() < 1e-3 * ratio),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn demand_ranges_insert_optional_items() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/shapes0.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        // Item 3 is a filler item without required copies
+        let ranges = [(10, 15), (7, 7), (9, 9), (0, 12)].map(|(min, max)| DemandRange { min, max }).to_vec();
+
+        let result = Sparrow::builder()
+            .instance(json_instance)
+            .config(config)
+            .seed(RNG_SEED.unwrap() as u64)
+            .demand_ranges(ranges.clone())
+            .build()?
+            .run();
+
+        let full_instance = full_instance(&result.instance, &ranges);
+        let mut prob = SPProblem::new(full_instance.clone());
+        prob.restore(&result.solution);
+        assert!(prob.layout.is_feasible());
+        let quantities = result.item_quantities().unwrap();
+        for (q, range) in quantities.iter().zip(&ranges) {
+            assert!((range.min..=range.max).contains(&(q.placed as usize)));
+        }
+
+        // A constructed (sparse) solution leaves room for optional copies, without widening the strip
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let lbf_sol = LBFBuilder::new(result.instance.clone(), rng.clone(), LBF_SAMPLE_CONFIG).construct().prob.save();
+        let filled_sol = insert_optional_items(&result.instance, &ranges, &ItemGroups::default(), &lbf_sol, &mut rng);
+        assert_eq!(filled_sol.strip_width(), lbf_sol.strip_width());
+        assert!(filled_sol.layout_snapshot.placed_items.len() > lbf_sol.layout_snapshot.placed_items.len());
+        prob.restore(&filled_sol);
+        assert!(prob.layout.is_feasible());
+        Ok(())
+    }
+
+    #[test]
+    fn item_groups_respect_max_span() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/shapes0.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let groups = vec![ItemGroup { item_ids: vec![1, 3], max_x_span: 30.0 }];
+
+        let result = Sparrow::builder()
+            .instance(json_instance)
+            .config(config)
+            .seed(RNG_SEED.unwrap() as u64)
+            .item_groups(groups.clone())
+            .build()?
+            .run();
+
+        let mut prob = SPProblem::new(result.instance.clone());
+        prob.restore(&result.solution);
+        assert!(prob.layout.is_feasible());
+        assert_eq!(result.solution.layout_snapshot.placed_items.len(), result.instance.total_item_qty());
+        for (group, ext_group) in groups.iter().zip(result.item_group_spans().unwrap()) {
+            assert!(ext_group.x_span.unwrap() <= group.max_x_span);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn quality_zones_respected() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/shapes0.json");
+        let (mut json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        // Only items 2 and 3 can be placed on the inferior corner of the strip
+        for id in [2, 3] {
+            json_instance.items[id].base.min_quality = Some(1);
+        }
+        let zones = vec![ExtQualityZone {
+            quality: 1,
+            shape: ExtShape::Rectangle { x_min: 0.0, y_min: 0.0, width: 20.0, height: 20.0 },
+        }];
+
+        let result = Sparrow::builder()
+            .instance(json_instance)
+            .config(config)
+            .seed(RNG_SEED.unwrap() as u64)
+            .quality_zones(zones)
+            .build()?
+            .run();
+
+        let prob = zones::restore_problem(&result.instance, &result.solution);
+        assert!(zones::has_quality_zones(&prob.layout.container));
+        assert!(zones::is_feasible(&prob.layout, &result.instance));
+        assert_eq!(result.solution.layout_snapshot.placed_items.len(), result.instance.total_item_qty());
+        assert_eq!(result.to_ext_output().unwrap().quality_zones.map(|z| z.len()), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn guillotine_layout_is_separable() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.work_budget = Some(WorkBudget::Iterations(50));
+        config.cmpr_cfg.work_budget = Some(WorkBudget::Iterations(10));
+        config.cmpr_cfg.guillotine = true;
+
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/shapes0.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let result = Sparrow::builder()
+            .instance(json_instance)
+            .config(config)
+            .seed(RNG_SEED.unwrap() as u64)
+            .build()?
+            .run();
+
+        let mut prob = SPProblem::new(result.instance.clone());
+        prob.restore(&result.solution);
+        assert!(prob.layout.is_feasible());
+        assert_eq!(result.solution.layout_snapshot.placed_items.len(), result.instance.total_item_qty());
+
+        // Every placed item is a leaf of the cut tree exactly once
+        fn leaves(tree: &CutTree, indices: &mut Vec<usize>) {
+            match tree {
+                CutTree::Item { index, .. } => indices.push(*index),
+                CutTree::Cuts { positions, parts, .. } => {
+                    assert_eq!(positions.len() + 1, parts.len());
+                    parts.iter().for_each(|p| leaves(p, indices));
+                }
+            }
+        }
+        assert_eq!(result.guillotine_feasible, Some(true));
+        let mut indices = vec![];
+        leaves(result.cut_tree.as_ref().expect("layout should be separable by guillotine cuts"), &mut indices);
+        assert!(indices.into_iter().sorted().eq(0..result.instance.total_item_qty()));
+        Ok(())
+    }
+
+    #[test]
+    fn guillotine_repair_pinwheel() -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        // Four 19x9 rectangles around a 9x9 square, in a 30x30 strip
+        let item = |id, width, height, demand| ExtSPItem {
+            base: ExtItem { id, allowed_orientations: Some(vec![0.0, 90.0]), shape: ExtShape::Rectangle { x_min: 0.0, y_min: 0.0, width, height }, min_quality: None },
+            demand,
+        };
+        let json_instance = ExtSPInstance { name: "pinwheel".into(), items: vec![item(0, 19.0, 9.0, 4), item(1, 9.0, 9.0, 1)], strip_height: 30.0 };
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let mut prob = SPProblem::new(instance.clone());
+        change_strip_width(&mut prob, 30.0);
+        // Item, rotation and position of the bottom left corner of every item, interlocking as a pinwheel
+        let pinwheel: [(usize, f32, (f32, f32)); 5] = [(0, 0.0, (0.5, 0.5)), (0, 90.0, (20.5, 0.5)), (0, 0.0, (10.5, 20.5)), (0, 90.0, (0.5, 10.5)), (1, 0.0, (10.5, 10.5))];
+        for (item_id, rot, (x, y)) in pinwheel {
+            let bbox = instance.item(item_id).shape_cd.transform_clone(&DTransformation::new(rot.to_radians(), (0.0, 0.0)).compose()).bbox;
+            prob.place_item(SPPlacement { item_id, d_transf: DTransformation::new(rot.to_radians(), (x - bbox.x_min, y - bbox.y_min)) });
+        }
+        assert!(prob.layout.is_feasible());
+        let sol = prob.save();
+        assert!(cut_tree(&sol).is_none());
+
+        let repaired = guillotine_repair(&instance, &sol)?;
+        let mut prob = SPProblem::new(instance.clone());
+        prob.restore(&repaired);
+        assert!(prob.layout.is_feasible());
+        assert_eq!(repaired.layout_snapshot.placed_items.len(), instance.total_item_qty());
+        assert!(cut_tree(&repaired).is_some());
+        assert!(repaired.strip_width() > sol.strip_width());
+        Ok(())
+    }
+
+    #[test]
+    fn continuous_rotation_sampler_fits_diagonally() -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        // A 10x1 rectangle with continuous rotation only fits an 8x8 box diagonally, not in any hull-aligned rotation
+        let json_instance = ExtSPInstance {
+            name: "diagonal".into(),
+            items: vec![ExtSPItem {
+                base: ExtItem { id: 0, allowed_orientations: None, shape: ExtShape::Rectangle { x_min: 0.0, y_min: 0.0, width: 10.0, height: 1.0 }, min_quality: None },
+                demand: 1,
+            }],
+            strip_height: 8.0,
+        };
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+        let item = instance.item(0);
+
+        let bbox = Rect::try_new(0.0, 0.0, 8.0, 8.0)?;
+        let sampler = UniformBBoxSampler::new(bbox, item, bbox).expect("item fits diagonally");
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        for _ in 0..100 {
+            let (dt, sample_bbox) = sampler.sample_with_bbox(&mut rng);
+            let shape_bbox = item.shape_cd.transform_clone(&dt.compose()).bbox;
+            assert!(shape_bbox.x_min >= bbox.x_min - 1e-3 && shape_bbox.x_max <= bbox.x_max + 1e-3);
+            assert!(shape_bbox.y_min >= bbox.y_min - 1e-3 && shape_bbox.y_max <= bbox.y_max + 1e-3);
+            assert!((sample_bbox.x_min - shape_bbox.x_min).abs() < 1e-3 && (sample_bbox.y_max - shape_bbox.y_max).abs() < 1e-3);
+        }
+        Ok(())
+    }
+
+    #[test_case(RefinerType::CoordDescent; "coord_descent")]
+    #[test_case(RefinerType::PatternSearch; "pattern_search")]
+    #[test_case(RefinerType::NelderMead; "nelder_mead")]
+    #[test_case(RefinerType::PoleGradient; "pole_gradient")]
+    fn refiners_never_worsen(refiner: RefinerType) -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng.clone(), LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+        // Shrink the strip to create collisions
+        separator.change_strip_width(separator.prob.strip_width() * 0.9, None);
+
+        let layout = &separator.prob.layout;
+        let colliding = layout.placed_items.keys().filter(|pk| separator.ct.get_loss(*pk) > 0.0).collect_vec();
+        assert!(!colliding.is_empty());
+        for pk in colliding {
+            let item = instance.item(layout.placed_items[pk].item_id);
+            let mut evaluator = SeparationEvaluator::new(layout, item, pk, &separator.ct);
+            let dt = layout.placed_items[pk].d_transf;
+            let start = (dt, evaluator.evaluate_sample(dt, None));
+            let item_min_dim = f32::min(item.shape_cd.bbox.width(), item.shape_cd.bbox.height());
+            let refine_config = RefineConfig {
+                t_step_init: item_min_dim * 0.25,
+                t_step_limit: item_min_dim * 0.02,
+                r_step_init: 5.0f32.to_radians(),
+                r_step_limit: 1.0f32.to_radians(),
+                wiggle: true,
+            };
+            let refined = refine(refiner, start, &mut evaluator, refine_config, &mut rng);
+            assert!(refined.1 <= start.1, "{:?} worsened a sample: {:?} -> {:?}", refiner, start.1, refined.1);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn sparse_tracker_separates() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        // Force the sparse pair storage, which is otherwise only used for large instances
+        config.expl_cfg.separator_config.sparse_tracker_threshold = 0;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng, LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+        assert!(matches!(separator.ct.pair_collisions, PairCollisions::Sparse(_)));
+        // Shrink the strip to create collisions
+        separator.change_strip_width(separator.prob.strip_width() * 0.95, None);
+        assert!(separator.ct.total.loss > 0.0);
+
+        let mut terminator = BasicTerminator::new();
+        terminator.new_timeout(EXPLORE_TIMEOUT);
+        separator.separate(&terminator, &mut DummySolListener);
+
+        assert!(matches!(separator.ct.pair_collisions, PairCollisions::Sparse(_)));
+        assert!(tracker_matches_layout(&separator.ct, &separator.prob.layout));
+        assert!(separator.ct.totals_match_entries());
+        Ok(())
+    }
+
+    #[test]
+    fn occupancy_grid_tracks_layout() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.separator_config.sample_config.container_sampler = ContainerSampler::GapAware;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng, LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+        // Shrink the strip to create collisions
+        separator.change_strip_width(separator.prob.strip_width() * 0.95, None);
+
+        let mut terminator = BasicTerminator::new();
+        terminator.new_timeout(EXPLORE_TIMEOUT);
+        separator.separate(&terminator, &mut DummySolListener);
+
+        // The incrementally updated grids should match ones rasterized from scratch
+        assert_eq!(separator.grid, Some(OccupancyGrid::new(&separator.prob.layout)));
+        for worker in separator.workers.iter() {
+            assert_eq!(worker.grid, Some(OccupancyGrid::new(&worker.prob.layout)));
+        }
+        Ok(())
+    }
+
+    #[test_case(DisruptionStrategy::LargeItemSwap; "large_item_swap")]
+    #[test_case(DisruptionStrategy::RuinRecreateBand; "ruin_recreate_band")]
+    #[test_case(DisruptionStrategy::RotateCluster; "rotate_cluster")]
+    #[test_case(DisruptionStrategy::ReflectPositions; "reflect_positions")]
+    #[test_case(DisruptionStrategy::RandomReplace; "random_replace")]
+    fn disruption_is_repairable(strategy: DisruptionStrategy) -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng, LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+        let pool = vec![(separator.prob.save(), 0.0)];
+        let ctx = DisruptionContext { pool: &pool, selected_idx: 0 };
+
+        let disruptor = build_disruptor(strategy, &config.expl_cfg);
+        assert!(disruptor.is_applicable(&separator, &ctx));
+        disruptor.disrupt(&mut separator, &ctx);
+        assert_eq!(separator.prob.layout.placed_items.len(), instance.total_item_qty());
+
+        let mut terminator = BasicTerminator::new();
+        terminator.new_timeout(EXPLORE_TIMEOUT);
+        let (sol, _) = separator.separate(&terminator, &mut DummySolListener);
+
+        let mut prob = SPProblem::new(instance.clone());
+        prob.restore(&sol);
+        assert_eq!(prob.layout.placed_items.len(), instance.total_item_qty());
+        assert!(prob.layout.is_feasible());
+        Ok(())
+    }
+
+    #[test]
+    fn adaptive_disruption_avoids_failing_strategy() -> Result<()> {
+        let mut config = DEFAULT_SPARROW_CONFIG;
+        config.expl_cfg.disruption.strategies = &[DisruptionStrategy::LargeItemSwap, DisruptionStrategy::RandomReplace];
+        config.expl_cfg.disruption.selection = DisruptorSelection::Adaptive;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng, LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+        let init_sol = separator.prob.save();
+        let pool = vec![(init_sol.clone(), 0.0)];
+        let ctx = DisruptionContext { pool: &pool, selected_idx: 0 };
+
+        // Large item swaps never lead to a feasible solution, random replacements always do
+        let mut selector = DisruptorSelector::new(&config.expl_cfg);
+        for _ in 0..200 {
+            selector.disrupt(&mut separator, &ctx);
+            let strategy = selector.pending_strategy().expect("a strategy should have been applied");
+            selector.report_outcome(strategy == DisruptionStrategy::RandomReplace);
+            assert!(selector.weights.iter().all(|w| *w >= ADAPTIVE_DSRP_MIN_WEIGHT));
+            separator.rollback(&init_sol, None);
+        }
+        assert!(selector.weights[0] < selector.weights[1]);
+        assert_eq!(selector.weights[0], ADAPTIVE_DSRP_MIN_WEIGHT);
+        Ok(())
+    }
+
+    /// Two different layouts of swim, at the strip width of the first one (plus the instance)
+    fn crossover_parents() -> Result<(SPInstance, Separator, SPSolution)> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let [sep_a, mut sep_b] = [0, 1].map(|seed| {
+            let builder = LBFBuilder::new(instance.clone(), Xoshiro256PlusPlus::seed_from_u64(seed), LBF_SAMPLE_CONFIG).construct();
+            Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config)
+        });
+        sep_b.change_strip_width(sep_a.prob.strip_width(), None);
+        let parent_b = sep_b.prob.save();
+        Ok((instance, sep_a, parent_b))
+    }
+
+    #[test]
+    fn crossover_preserves_demand() -> Result<()> {
+        let (instance, mut separator, parent_b) = crossover_parents()?;
+        assert_eq!(separator.prob.strip_width(), parent_b.strip_width());
+        let qtys = |sep: &Separator| sep.prob.layout.placed_items.values().counts_by(|pi| pi.item_id);
+        let init_qtys = qtys(&separator);
+
+        crossover(&mut separator, &parent_b);
+        assert_eq!(qtys(&separator), init_qtys);
+        assert!(instance.items.iter().enumerate().all(|(id, (_, qty))| init_qtys.get(&id) == Some(qty)));
+
+        let mut terminator = BasicTerminator::new();
+        terminator.new_timeout(EXPLORE_TIMEOUT);
+        let (sol, _) = separator.separate(&terminator, &mut DummySolListener);
+        let mut prob = SPProblem::new(instance.clone());
+        prob.restore(&sol);
+        assert!(prob.layout.is_feasible());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "same strip width")]
+    fn crossover_rejects_different_widths() {
+        let (_, mut separator, parent_b) = crossover_parents().unwrap();
+        separator.change_strip_width(parent_b.strip_width() * 0.95, None);
+        crossover(&mut separator, &parent_b);
+    }
+
+    #[test]
+    fn tracker_running_totals() -> Result<()> {
+        let config = DEFAULT_SPARROW_CONFIG;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+
+        let rng = Xoshiro256PlusPlus::seed_from_u64(RNG_SEED.unwrap() as u64);
+        let builder = LBFBuilder::new(instance.clone(), rng, LBF_SAMPLE_CONFIG).construct();
+        let mut separator = Separator::new(builder.instance, builder.prob, builder.rng, config.expl_cfg.separator_config);
+        assert_eq!(separator.ct.get_total_loss(), 0.0);
+
+        // Move items onto the positions of others, and back again in reverse order
+        let pks = separator.prob.layout.placed_items.keys().collect_vec();
+        let mut moves = vec![];
+        for (&pk, &other) in pks.iter().zip(pks.iter().rev()).take(pks.len() / 2) {
+            let (old_dt, new_dt) = (separator.prob.layout.placed_items[pk].d_transf, separator.prob.layout.placed_items[other].d_transf);
+            moves.push((separator.move_item(pk, new_dt), old_dt));
+
+            // The running totals match the individual collisions, and a tracker built from scratch
+            let fresh = CollisionTracker::new(&separator.prob.layout, &instance, &ItemGroups::default(), config.expl_cfg.separator_config.sparse_tracker_threshold);
+            assert!(separator.ct.totals_match_entries());
+            let close = |a: f32, b: f32| (a - b).abs() <= 1e-3 * f32::max(1.0, b.abs());
+            assert!(close(separator.ct.get_total_loss(), fresh.get_total_loss()));
+            for pk in separator.prob.layout.placed_items.keys() {
+                assert!(close(separator.ct.get_loss(pk), fresh.get_loss(pk)));
+            }
+        }
+        assert!(separator.ct.get_total_loss() > 0.0);
+        for (pk, old_dt) in moves.into_iter().rev() {
+            separator.move_item(pk, old_dt);
+        }
+        // Without collisions, the totals are exactly zero again
+        assert_eq!(separator.ct.get_total_loss(), 0.0);
+        assert!(separator.prob.layout.placed_items.keys().all(|pk| separator.ct.get_loss(pk) == 0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn simd_overlap_proxy_matches_scalar() -> Result<()> {
+        use sparrow::consts::OVERLAP_PROXY_EPSILON_DIAM_RATIO;
+        use sparrow::quantify::overlap_proxy::overlap_area_proxy;
+        use sparrow::quantify::simd::circles_soa::CirclesSoA;
+        use sparrow::quantify::simd::overlap_proxy_simd::{poles_overlap_area_proxy_simd, SIMD_LEVEL};
+
+        let config = DEFAULT_SPARROW_CONFIG;
+        let input_file_path = format!("{INSTANCE_BASE_PATH}/swim.json");
+        let (json_instance, _) = io::read_spp_input(Path::new(&input_file_path))?;
+        let importer = Importer::new(config.cde_config, config.poly_simpl_tolerance, config.min_item_separation, config.narrow_concavity_cutoff_ratio);
+        let instance = jagua_rs::probs::spp::io::import_instance(&importer, &json_instance)?;
+        println!("[TEST] SIMD level: {:?} ({} lanes)", *SIMD_LEVEL, SIMD_LEVEL.lanes());
+
+        // Every pair of items, overlapping to varying degrees (including not at all)
+        let mut poles2 = CirclesSoA::new();
+        for ((item1, _), (item2, _)) in instance.items.iter().cartesian_product(instance.items.iter()) {
+            for offset in [0.0, 0.25, 0.5, 1.0, 2.0] {
+                let s1 = item1.shape_cd.as_ref();
+                let dt = DTransformation::new(1.0, (offset * item1.shape_cd.diameter, 0.0));
+                let s2 = item2.shape_cd.transform_clone(&dt.compose());
+                let epsilon = f32::max(s1.diameter, s2.diameter) * OVERLAP_PROXY_EPSILON_DIAM_RATIO;
+
+                let scalar = overlap_area_proxy(s1.surrogate(), s2.surrogate(), epsilon);
+                let simd = poles_overlap_area_proxy_simd(s1.surrogate(), s2.surrogate(), epsilon, poles2.load(&s2.surrogate().poles));
+                assert!((simd - scalar).abs() <= scalar * 1e-4, "SIMD and scalar overlap proxies differ: {simd} vs {scalar}");
+            }
+        }
+        Ok(())
+    }
+}
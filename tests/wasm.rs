@@ -0,0 +1,58 @@
+#![cfg(feature = "wasm")]
+//! Tests of the JavaScript bindings.
+//! Run under node with `cargo test --features wasm --target wasm32-unknown-unknown` (requires `wasm-bindgen-test-runner`),
+//! or natively with `cargo test --features wasm`.
+
+use jagua_rs::probs::spp::io::ext_repr::ExtSPSolution;
+use sparrow::util::io::ExtSPOutput;
+use sparrow::wasm::WasmOptimizer;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const INSTANCE: &str = include_str!("../data/input/shapes0.json");
+
+#[wasm_bindgen_test(unsupported = test)]
+fn step_until_done() {
+    let mut optimizer = WasmOptimizer::new(INSTANCE, 10_000.0, Some(0), Some(20), Some(20)).unwrap();
+    assert_eq!(optimizer.phase(), "exploration");
+
+    let mut n_steps = 0;
+    let mut prev_width = f32::INFINITY;
+    while !optimizer.done() {
+        let result = optimizer.step(50.0);
+        let solution = serde_json::from_str::<ExtSPSolution>(&result.solution_json).unwrap();
+        assert_eq!(solution.strip_width, result.strip_width);
+        assert!(result.strip_width <= prev_width || result.phase == "compression");
+        prev_width = result.strip_width;
+        n_steps += 1;
+        assert!(n_steps < 10_000, "optimizer did not finish");
+    }
+    assert!(n_steps > 1, "work should have been spread over multiple steps");
+
+    let output = serde_json::from_str::<ExtSPOutput>(&optimizer.output_json()).unwrap();
+    assert_eq!(output.solution.strip_width, prev_width);
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn skip_phases() {
+    let mut optimizer = WasmOptimizer::new(INSTANCE, 60_000.0, Some(0), None, None).unwrap();
+    optimizer.step(10.0);
+    optimizer.skip_phase();
+    assert_eq!(optimizer.phase(), "compression");
+    optimizer.step(10.0);
+    optimizer.skip_phase();
+    assert!(optimizer.done());
+    let result = optimizer.step(10.0);
+    assert_eq!(result.phase, "done");
+}
+
+/// Time between steps does not count towards the time limit of a phase
+#[cfg(not(target_arch = "wasm32"))]
+#[test]
+fn time_limit_excludes_time_between_steps() {
+    // Exploration gets 3.2s, which the (slightly overshooting) steps themselves stay well below
+    let mut optimizer = WasmOptimizer::new(INSTANCE, 4_000.0, Some(0), None, None).unwrap();
+    optimizer.step(10.0);
+    std::thread::sleep(std::time::Duration::from_millis(4_000));
+    let result = optimizer.step(10.0);
+    assert_eq!(result.phase, "exploration");
+}